@@ -0,0 +1,62 @@
+//! Compresses the repo's `packs/` and `skills/` into `$OUT_DIR/bundled.bin`,
+//! which `src/bundled.rs` embeds and `src/archive.rs` extracts on first use.
+
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let repo_root = manifest_dir.join("../..");
+    let mut files = Vec::new();
+    for top in ["packs", "skills"] {
+        let dir = repo_root.join(top);
+        println!("cargo:rerun-if-changed={}", dir.display());
+        collect(&dir, top, &mut files);
+    }
+    files.sort();
+
+    let entries: Vec<(String, Vec<u8>)> = files
+        .into_iter()
+        .map(|(rel, path)| {
+            let data =
+                std::fs::read(&path).unwrap_or_else(|err| panic!("read {}: {err}", path.display()));
+            (rel, data)
+        })
+        .collect();
+    let blob = pack(&entries);
+    let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap()).join("bundled.bin");
+    std::fs::write(&out, blob).unwrap_or_else(|err| panic!("write {}: {err}", out.display()));
+}
+
+/// Entries are `u32` path length, path, `u64` data length, data (all
+/// little-endian); the whole stream is deflated.
+fn pack(entries: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut raw = Vec::new();
+    for (path, data) in entries {
+        let path_len =
+            u32::try_from(path.len()).unwrap_or_else(|_| panic!("bundled path too long: {path}"));
+        let data_len =
+            u64::try_from(data.len()).unwrap_or_else(|_| panic!("bundled file too large: {path}"));
+        raw.extend_from_slice(&path_len.to_le_bytes());
+        raw.extend_from_slice(path.as_bytes());
+        raw.extend_from_slice(&data_len.to_le_bytes());
+        raw.extend_from_slice(data);
+    }
+    miniz_oxide::deflate::compress_to_vec(&raw, 9)
+}
+
+fn collect(dir: &Path, rel: &str, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child = format!("{rel}/{name}");
+        if path.is_dir() {
+            collect(&path, &child, files);
+        } else {
+            files.push((child, path));
+        }
+    }
+}
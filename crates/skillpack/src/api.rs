@@ -0,0 +1,163 @@
+//! Entry points for driving skillpack from other Rust code without the CLI.
+//!
+//! These functions return typed results and never print. State is read from and
+//! written to the user state file (`$SKILLPACK_HOME/state.json`, default `~/.skillpack`).
+//!
+//! [`install_into`] and [`uninstall_from`] work on a caller-owned [`StateFile`]
+//! instead, for callers that batch several changes into one state write.
+//!
+//! ```
+//! use skillpack::api::{InstallOptions, StateFile};
+//! use std::fs;
+//!
+//! let temp = tempfile::tempdir()?;
+//! let repo = temp.path().join("repo");
+//! fs::create_dir_all(repo.join("skills/alpha"))?;
+//! fs::write(repo.join("skills/alpha/SKILL.md"), "# alpha")?;
+//! fs::create_dir_all(repo.join("packs"))?;
+//! fs::write(repo.join("packs/demo.yaml"), "name: demo\ninclude:\n  - alpha\n")?;
+//!
+//! let resolved = skillpack::api::resolve(&repo, "demo", &temp.path().join("cache"))?;
+//! assert_eq!(resolved.final_skills[0].id, "alpha");
+//!
+//! // An in-memory state keeps the doc test away from the user state file.
+//! let mut state = StateFile::default();
+//! let sink = temp.path().join("sink");
+//! let record = skillpack::api::install_into(
+//!     &mut state,
+//!     &resolved,
+//!     "custom",
+//!     &sink,
+//!     &InstallOptions::default(),
+//! )?;
+//! assert!(sink.join("demo__alpha/SKILL.md").exists());
+//! assert_eq!(state.installs.len(), 1);
+//!
+//! skillpack::api::uninstall_from(&mut state, &sink, &record.pack)?;
+//! assert!(!sink.join("demo__alpha").exists());
+//! assert!(state.installs.is_empty());
+//! # Ok::<(), color_eyre::eyre::Report>(())
+//! ```
+
+use crate::bundled::{bundled_root_path, enabled_bundled_root};
+use crate::index::sync_index;
+use crate::install::{install_pack, uninstall_pack};
+use crate::pack::resolve_pack_path;
+use crate::resolve::{detect_collisions, resolve_pack};
+use crate::state::{load_state, write_state};
+use crate::util::{discover_repo_root, make_absolute};
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::path::{Path, PathBuf};
+
+pub use crate::install::InstallOptions;
+pub use crate::resolve::{
+    ResolveOptions, ResolvedImport, ResolvedPack, ResolvedSkill, SkillSource,
+};
+pub use crate::state::{InstallRecord, StateFile};
+
+/// Resolves a pack name or pack file against `repo_root`, failing on install name collisions.
+pub fn resolve(repo_root: &Path, pack: &str, cache_dir: &Path) -> Result<ResolvedPack> {
+    resolve_with(repo_root, pack, cache_dir, ResolveOptions::default())
+}
+
+/// Like [`resolve`], with per-run settings such as a namespace or an import ref override.
+pub fn resolve_with(
+    repo_root: &Path,
+    pack: &str,
+    cache_dir: &Path,
+    options: ResolveOptions,
+) -> Result<ResolvedPack> {
+    let (pack_path, pack_root) = locate_pack(repo_root, pack, &options)?;
+    let resolved = resolve_pack(&pack_root, &pack_path, cache_dir, options)?;
+    detect_collisions(&resolved.final_skills, &resolved.pack)?;
+    Ok(resolved)
+}
+
+/// Installs a resolved pack into `sink_path` and records it in state.
+pub fn install(resolved: &ResolvedPack, sink: &str, sink_path: &Path) -> Result<InstallRecord> {
+    install_with(resolved, sink, sink_path, &InstallOptions::default())
+}
+
+pub fn install_with(
+    resolved: &ResolvedPack,
+    sink: &str,
+    sink_path: &Path,
+    options: &InstallOptions,
+) -> Result<InstallRecord> {
+    let mut state = load_state()?;
+    let record = install_into(&mut state, resolved, sink, sink_path, options)?;
+    write_state(&state)?;
+    sync_index(&state)?;
+    Ok(record)
+}
+
+/// Removes a pack's recorded folders from `sink_path` and drops its state record.
+pub fn uninstall(sink_path: &Path, pack: &str) -> Result<InstallRecord> {
+    let mut state = load_state()?;
+    let record = uninstall_from(&mut state, sink_path, pack)?;
+    write_state(&state)?;
+    sync_index(&state)?;
+    Ok(record)
+}
+
+/// Installs a resolved pack into `sink_path`, recording it in `state` without writing it.
+pub fn install_into(
+    state: &mut StateFile,
+    resolved: &ResolvedPack,
+    sink: &str,
+    sink_path: &Path,
+    options: &InstallOptions,
+) -> Result<InstallRecord> {
+    install_pack(resolved, sink, &make_absolute(sink_path)?, state, options)
+}
+
+/// Removes a pack's recorded folders from `sink_path` and drops its record from `state`
+/// without writing it.
+pub fn uninstall_from(
+    state: &mut StateFile,
+    sink_path: &Path,
+    pack: &str,
+) -> Result<InstallRecord> {
+    uninstall_pack(state, &make_absolute(sink_path)?, pack)
+}
+
+/// Lists all recorded installs.
+pub fn installed() -> Result<Vec<InstallRecord>> {
+    Ok(load_state()?.installs)
+}
+
+/// Finds a pack file and the repo root its skills resolve against
+/// (the bundled root for bundled packs).
+pub fn locate_pack(
+    repo_root: &Path,
+    pack: &str,
+    options: &ResolveOptions,
+) -> Result<(PathBuf, PathBuf)> {
+    let pack_path = make_absolute(&resolve_pack_path(repo_root, pack, options.no_bundled)?)?;
+    if let Some(bundled_root) = enabled_bundled_root(options.no_bundled)?
+        && pack_path.starts_with(&bundled_root)
+    {
+        return Ok((pack_path, bundled_root));
+    }
+    Ok((pack_path, repo_root.to_path_buf()))
+}
+
+/// Repo root for a pack file recorded in state, found from the file's location.
+pub fn recorded_pack_root(pack_path: &Path, options: &ResolveOptions) -> Result<PathBuf> {
+    // Installs recorded from bundled packs keep working when bundled content is
+    // disabled afterwards, as long as the extracted files are still there.
+    if pack_path.starts_with(bundled_root_path()?) {
+        return match enabled_bundled_root(options.no_bundled)? {
+            Some(root) => Ok(root),
+            None => bundled_root_path(),
+        };
+    }
+    pack_path
+        .parent()
+        .and_then(discover_repo_root)
+        .ok_or_else(|| {
+            eyre!("repo root not found for pack file: {}", pack_path.display())
+                .suggestion("Reinstall the pack with sp install")
+        })
+}
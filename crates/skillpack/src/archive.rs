@@ -0,0 +1,115 @@
+//! Reader for the compressed archive of bundled content that `build.rs` writes.
+//!
+//! Entries are `u32` path length, `/`-separated relative path, `u64` data length,
+//! data (all little-endian), and the whole stream is deflated.
+
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+/// Inflates a bundled archive and writes each entry under `dest`.
+pub fn unpack(blob: &[u8], dest: &Path) -> io::Result<()> {
+    let raw =
+        miniz_oxide::inflate::decompress_to_vec(blob).map_err(|err| invalid(format!("{err:?}")))?;
+    let mut rest = raw.as_slice();
+    while !rest.is_empty() {
+        let path_len = u32::from_le_bytes(take_array(&mut rest)?);
+        let path = std::str::from_utf8(take(&mut rest, path_len.into())?)
+            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+        let data_len = u64::from_le_bytes(take_array(&mut rest)?);
+        let data = take(&mut rest, data_len)?;
+        if path.split('/').any(|seg| seg.is_empty() || seg == "..") {
+            return Err(invalid(format!("invalid archive path: {path}")));
+        }
+        let target = dest.join(path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, data)?;
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn take_array<const N: usize>(rest: &mut &[u8]) -> io::Result<[u8; N]> {
+    let (head, tail) = rest.split_first_chunk::<N>().ok_or_else(truncated)?;
+    *rest = tail;
+    Ok(*head)
+}
+
+/// The next `len` bytes; a length beyond what is left (or beyond `usize`) means
+/// the archive is truncated.
+fn take<'a>(rest: &mut &'a [u8], len: u64) -> io::Result<&'a [u8]> {
+    let (head, tail) = usize::try_from(len)
+        .ok()
+        .and_then(|len| rest.split_at_checked(len))
+        .ok_or_else(truncated)?;
+    *rest = tail;
+    Ok(head)
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(ErrorKind::UnexpectedEof, "truncated bundled archive")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unpack;
+
+    /// The writer half of the format, as `build.rs` has it.
+    fn pack(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        for (path, data) in entries {
+            raw.extend_from_slice(&u32::try_from(path.len()).unwrap().to_le_bytes());
+            raw.extend_from_slice(path.as_bytes());
+            raw.extend_from_slice(&u64::try_from(data.len()).unwrap().to_le_bytes());
+            raw.extend_from_slice(data);
+        }
+        miniz_oxide::deflate::compress_to_vec(&raw, 9)
+    }
+
+    #[test]
+    fn round_trips_files() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let blob = pack(&[
+            ("packs/demo.yaml", b"name: demo\n".as_slice()),
+            ("skills/alpha/SKILL.md", b"# Alpha\n".as_slice()),
+        ]);
+        unpack(&blob, temp.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("packs/demo.yaml")).unwrap(),
+            "name: demo\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("skills/alpha/SKILL.md")).unwrap(),
+            "# Alpha\n"
+        );
+    }
+
+    #[test]
+    fn rejects_parent_paths() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let blob = pack(&[("../escape", b"x".as_slice())]);
+        assert!(unpack(&blob, &temp.path().join("root")).is_err());
+    }
+
+    #[test]
+    fn truncated_archive_is_an_error() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut raw = 3u32.to_le_bytes().to_vec();
+        raw.extend_from_slice(b"abc");
+        raw.extend_from_slice(&u64::MAX.to_le_bytes());
+        raw.extend_from_slice(b"short");
+        for cut in [raw.len(), 2, 6] {
+            let blob = miniz_oxide::deflate::compress_to_vec(&raw[..cut], 9);
+            let err = unpack(&blob, temp.path()).unwrap_err();
+            assert_eq!(
+                err.kind(),
+                std::io::ErrorKind::UnexpectedEof,
+                "cut at {cut}"
+            );
+        }
+    }
+}
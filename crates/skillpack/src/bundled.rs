@@ -1,21 +1,44 @@
+use crate::archive;
 use crate::config::config_dir;
-use color_eyre::eyre::Result;
-use include_dir::{Dir, include_dir};
+use color_eyre::eyre::{Result, WrapErr};
 use std::path::{Path, PathBuf};
 
-static PACKS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../../packs");
-static SKILLS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/../../skills");
+/// The repo's `packs/` and `skills/`, deflated by `build.rs`.
+static BUNDLED: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/bundled.bin"));
 
-pub fn bundled_repo_root() -> Result<PathBuf> {
-    let root = config_dir()?
+/// Set to a non-empty value other than `0` to ignore bundled packs and skills.
+pub const NO_BUNDLED_ENV: &str = "SKILLPACK_NO_BUNDLED";
+
+/// Whether `SKILLPACK_NO_BUNDLED` is set; the default for `--no-bundled`.
+pub fn no_bundled_from_env() -> bool {
+    std::env::var(NO_BUNDLED_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Where bundled content lives, without extracting it.
+pub fn bundled_root_path() -> Result<PathBuf> {
+    Ok(config_dir()?
         .join("bundled")
-        .join(env!("CARGO_PKG_VERSION"));
+        .join(env!("CARGO_PKG_VERSION")))
+}
+
+pub fn bundled_repo_root() -> Result<PathBuf> {
+    let root = bundled_root_path()?;
     ensure_extracted(&root)?;
     Ok(root)
 }
 
-pub fn bundled_pack_path(pack_name: &str) -> Result<Option<PathBuf>> {
-    let root = bundled_repo_root()?;
+/// The extracted bundled root, or None when bundled content is disabled.
+pub fn enabled_bundled_root(no_bundled: bool) -> Result<Option<PathBuf>> {
+    if no_bundled {
+        return Ok(None);
+    }
+    bundled_repo_root().map(Some)
+}
+
+pub fn bundled_pack_path(pack_name: &str, no_bundled: bool) -> Result<Option<PathBuf>> {
+    let Some(root) = enabled_bundled_root(no_bundled)? else {
+        return Ok(None);
+    };
     let path = root.join("packs").join(format!("{pack_name}.yaml"));
     if path.exists() {
         Ok(Some(path))
@@ -28,14 +51,23 @@ fn ensure_extracted(root: &Path) -> Result<()> {
     if root.exists() {
         return Ok(());
     }
-    std::fs::create_dir_all(root)?;
-    write_dir(&root.join("packs"), &PACKS_DIR)?;
-    write_dir(&root.join("skills"), &SKILLS_DIR)?;
+    std::fs::create_dir_all(root.join("packs"))?;
+    std::fs::create_dir_all(root.join("skills"))?;
+    archive::unpack(BUNDLED, root)
+        .wrap_err_with(|| format!("failed to extract bundled content to {}", root.display()))?;
     Ok(())
 }
 
-fn write_dir(dest_root: &Path, dir: &Dir) -> Result<()> {
-    std::fs::create_dir_all(dest_root)?;
-    dir.extract(dest_root)?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::ensure_extracted;
+
+    #[test]
+    fn extracts_bundled_packs_and_skills() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let root = temp.path().join("bundled");
+        ensure_extracted(&root).unwrap();
+        assert!(root.join("packs/skillpack.yaml").is_file());
+        assert!(root.join("skills/memory/agent-memory/SKILL.md").is_file());
+    }
 }
@@ -1,24 +1,38 @@
-use crate::bundled::bundled_repo_root;
-use crate::config::{load_config, load_config_detail, resolve_sink_path};
-use crate::discover::discover_local_skills;
-use crate::install::{install_pack, uninstall_pack};
-use crate::output::{
-    ConfigView, ImportView, InstallView, InstalledItem, InstalledView, Output, OutputFormat,
-    PackInfo, PackSummary, ShowView, SinkView, SwitchSinkView, SwitchView, UninstallView,
-};
-use crate::pack::{load_pack, resolve_pack_path};
-use crate::resolve::{detect_collisions, resolve_pack};
-use crate::state::{load_state, write_state};
-use crate::util::{discover_repo_root, install_name, make_absolute};
+mod cache;
+mod commands;
+mod config;
+mod context;
+mod doctor;
+mod install;
+mod listing;
+mod migrate;
+mod show;
+mod status;
+mod targets;
+mod uninstall;
+mod update;
+
+use crate::bundled::no_bundled_from_env;
+use crate::config::newer_version_warning;
+use crate::git::{GitOptions, git_toplevel, timeout_from_secs};
+use crate::output::{ColorChoice, DEFAULT_PAGER, Output, OutputFormat};
+use crate::resolve::ResolveOptions;
+use crate::util::{discover_repo_root, is_repo_root, make_absolute};
 use clap::builder::styling::{AnsiColor, Effects};
-use clap::{Args, Parser, Subcommand, ValueHint, builder::Styles};
+use clap::{Args, Parser, ValueHint, builder::Styles};
 use color_eyre::Section as _;
-use color_eyre::eyre::{Result, eyre};
-use std::collections::HashSet;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
 use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use tracing::debug;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+
+use commands::{Commands, run_command};
+use context::ResolveContext;
 
 const fn help_styles() -> Styles {
     Styles::styled()
@@ -64,10 +78,96 @@ pub struct Cli {
         help = "Output format"
     )]
     format: OutputFormat,
-    #[arg(long, global = true, help = "Disable ANSI colors")]
+    #[arg(
+        long,
+        global = true,
+        help = "Separate plain listing records with NUL (for xargs -0)"
+    )]
+    print0: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Print only this field of the JSON output (pack.name or /pack/name)"
+    )]
+    select: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorChoice::Auto,
+        help = "When to use ANSI colors"
+    )]
+    color: ColorChoice,
+    #[arg(
+        long,
+        global = true,
+        help = "Disable ANSI colors (same as --color never)"
+    )]
     no_color: bool,
     #[arg(long, global = true, help = "Show debug logs on stderr")]
     verbose: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Page pretty output on a terminal through $PAGER (default: less -R)"
+    )]
+    pager: bool,
+    #[arg(
+        long,
+        global = true,
+        value_hint = ValueHint::FilePath,
+        help = "Also write debug logs as JSON lines to this file"
+    )]
+    log_file: Option<PathBuf>,
+    #[arg(long, global = true, help = "Treat every warning as an error")]
+    strict: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "After the command succeeds, delete the repo caches it used"
+    )]
+    prune_cache_on_success: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Print how long each phase took (discover, imports, copy, ...) to stderr"
+    )]
+    timings: bool,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "prune_cache_on_success",
+        help = "Use cached repos as they are: no clone, fetch or checkout (same as SKILLPACK_CACHE_READONLY=1)"
+    )]
+    cache_readonly: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "REF",
+        help = "Resolve every import at REF instead of the ref in the pack"
+    )]
+    repo_ref: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Also select the skills listed under depends_on: in each selected skill's frontmatter"
+    )]
+    with_skill_deps: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Ignore bundled packs and skills (same as SKILLPACK_NO_BUNDLED=1)"
+    )]
+    no_bundled: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        help = "Kill git commands that run longer than SECS (same as SKILLPACK_GIT_TIMEOUT)"
+    )]
+    git_timeout: Option<u64>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -88,79 +188,32 @@ struct AgentTargets {
     custom: bool,
 }
 
-#[derive(Subcommand, Debug)]
-enum Commands {
-    #[command(about = "List local skills under ./skills", visible_alias = "list")]
-    Skills {
-        #[arg(long, alias = "all", help = "Include bundled skills")]
-        bundled: bool,
-    },
-    #[command(about = "List packs under ./packs")]
-    Packs,
-    #[command(about = "Show resolved contents of a pack", visible_alias = "pack")]
-    Show {
-        #[arg(value_name = "PACK")]
-        pack: String,
-    },
-    #[command(about = "Install a pack into an agent destination")]
-    Install {
-        #[arg(value_name = "PACK")]
-        pack: String,
-        #[command(flatten)]
-        targets: AgentTargets,
-        #[arg(
-            long,
-            value_hint = ValueHint::DirPath,
-            help = "Override agent destination path (required for custom)"
-        )]
-        path: Option<PathBuf>,
-    },
-    #[command(about = "Uninstall a pack from an agent destination")]
-    Uninstall {
-        #[arg(value_name = "PACK")]
-        pack: String,
-        #[command(flatten)]
-        targets: AgentTargets,
-        #[arg(
-            long,
-            value_hint = ValueHint::DirPath,
-            help = "Override agent destination path (required for custom)"
-        )]
-        path: Option<PathBuf>,
-    },
-    #[command(about = "List installed packs", visible_alias = "installs")]
-    Installed {
-        #[command(flatten)]
-        targets: AgentTargets,
-        #[arg(
-            long,
-            value_hint = ValueHint::DirPath,
-            help = "Override agent destination path (required for custom)"
-        )]
-        path: Option<PathBuf>,
-    },
-    #[command(about = "Switch packs: uninstall all current packs and install new ones")]
-    Switch {
-        #[arg(value_name = "PACK", required = true, num_args = 1..)]
-        packs: Vec<String>,
-        #[command(flatten)]
-        targets: AgentTargets,
-        #[arg(
-            long,
-            value_hint = ValueHint::DirPath,
-            help = "Override agent destination path (required for custom)"
-        )]
-        path: Option<PathBuf>,
-    },
-    #[command(about = "Show sink configuration", visible_alias = "sinks")]
-    Config,
-}
-
 pub fn run() -> Result<()> {
     let cli = Cli::parse();
-    init_diagnostics(cli.verbose, cli.no_color)?;
-    let output = Output::new(cli.format, cli.no_color);
-    run_inner(&cli, &output)
+    let color = if cli.no_color {
+        ColorChoice::Never
+    } else {
+        cli.color
+    };
+    init_diagnostics(cli.verbose, color, cli.log_file.as_deref())?;
+    if cli.select.is_some() && !matches!(cli.format, OutputFormat::Json | OutputFormat::CompactJson)
+    {
+        return Err(eyre!("--select only applies to JSON output")
+            .suggestion("Add --format json or --format compact-json"));
+    }
+    let output = Output::new(cli.format, color)
+        .with_print0(cli.print0)
+        .with_verbose(cli.verbose)
+        .with_select(cli.select.clone())
+        .with_pager(cli.pager.then(|| {
+            std::env::var("PAGER")
+                .ok()
+                .filter(|pager| !pager.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+        }));
+    let result = run_inner(&cli, &output);
+    output.finish()?;
+    result
 }
 
 fn run_inner(cli: &Cli, output: &Output) -> Result<()> {
@@ -168,507 +221,74 @@ fn run_inner(cli: &Cli, output: &Output) -> Result<()> {
         Some(ref path) => make_absolute(path)?,
         None => default_cache_dir()?,
     };
-    match cli.command {
-        Commands::Skills { bundled } => list_skills(&resolve_repo_root(cli)?, bundled, output),
-        Commands::Packs => list_packs(&resolve_repo_root(cli)?, output),
-        Commands::Show { ref pack } => {
-            show_pack(&resolve_repo_root(cli)?, &cache_dir, pack, output)
-        }
-        Commands::Install {
-            ref pack,
-            ref targets,
-            ref path,
-        } => install_cmd(
-            &resolve_repo_root(cli)?,
-            &cache_dir,
-            pack,
-            targets,
-            path.as_deref(),
-            output,
-        ),
-        Commands::Uninstall {
-            ref pack,
-            ref targets,
-            ref path,
-        } => uninstall_cmd(
-            &resolve_repo_root(cli)?,
-            pack,
-            targets,
-            path.as_deref(),
-            output,
-        ),
-        Commands::Installed {
-            ref targets,
-            ref path,
-        } => installed_cmd(targets, path.as_deref(), output),
-        Commands::Switch {
-            ref packs,
-            ref targets,
-            ref path,
-        } => switch_cmd(
-            &resolve_repo_root(cli)?,
-            &cache_dir,
-            packs,
-            targets,
-            path.as_deref(),
-            output,
-        ),
-        Commands::Config => config_cmd(output),
-    }
-}
-
-fn resolve_repo_root(cli: &Cli) -> Result<PathBuf> {
-    if let Some(ref root) = cli.repo_root {
-        return make_absolute(root);
-    }
-    let cwd = std::env::current_dir()?;
-    if let Some(found) = discover_repo_root(&cwd) {
-        return Ok(found);
-    }
-    Ok(cwd)
-}
-
-fn list_skills(repo_root: &Path, include_bundled: bool, output: &Output) -> Result<()> {
-    let skills_dir = repo_root.join("skills");
-    if !skills_dir.exists() {
-        return Err(eyre!("skills/ directory not found")
-            .suggestion("Create a skills/ directory or use --root to specify the repo root"));
-    }
-
-    let mut ids: Vec<String> = Vec::new();
-    ids.extend(discover_local_skills(repo_root)?.into_iter().map(|s| s.id));
-
-    if include_bundled {
-        let bundled_root = bundled_repo_root()?;
-        ids.extend(
-            discover_local_skills(&bundled_root)?
-                .into_iter()
-                .map(|s| s.id),
-        );
-    }
-
-    let mut unique = HashSet::new();
-    ids.retain(|id| unique.insert(id.clone()));
-    ids.sort();
-    output.print_skills(&ids)?;
-    Ok(())
-}
-
-fn list_packs(repo_root: &Path, output: &Output) -> Result<()> {
-    let mut packs = Vec::new();
-    let bundled_root = bundled_repo_root()?;
-    packs.extend(read_packs(
-        &bundled_root.join("packs"),
-        Some(&bundled_root),
-    )?);
-    packs.extend(read_packs(&repo_root.join("packs"), Some(repo_root))?);
-
-    let mut by_name = std::collections::BTreeMap::new();
-    for pack in packs {
-        by_name.insert(pack.name.clone(), pack);
-    }
-    let mut packs: Vec<PackSummary> = by_name.into_values().collect();
-    packs.sort_by(|a, b| a.name.cmp(&b.name));
-    output.print_packs(&packs)?;
-    Ok(())
-}
-
-fn read_packs(packs_dir: &Path, repo_root: Option<&Path>) -> Result<Vec<PackSummary>> {
-    if !packs_dir.exists() {
-        return Ok(Vec::new());
-    }
-    let mut packs = Vec::new();
-    for entry in std::fs::read_dir(packs_dir)? {
-        let entry = entry?;
-        if !entry.file_type()?.is_file() {
-            continue;
-        }
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) != Some("yaml") {
-            continue;
-        }
-        let pack = load_pack(&path)?;
-        let display_path = match repo_root {
-            Some(root) => path
-                .strip_prefix(root)
-                .unwrap_or(&path)
-                .display()
-                .to_string(),
-            None => path.display().to_string(),
-        };
-        packs.push(PackSummary {
-            name: pack.name,
-            path: display_path,
-        });
-    }
-    Ok(packs)
-}
-
-fn collect_agents(targets: &AgentTargets) -> Vec<String> {
-    let mut agents = Vec::new();
-    if targets.codex {
-        agents.push("codex".to_string());
-    }
-    if targets.claude {
-        agents.push("claude".to_string());
-    }
-    if targets.copilot {
-        agents.push("copilot".to_string());
-    }
-    if targets.cursor {
-        agents.push("cursor".to_string());
-    }
-    if targets.windsurf {
-        agents.push("windsurf".to_string());
+    let ctx = ResolveContext {
+        cache_dir,
+        strict: cli.strict,
+        warnings: output.warnings(),
+        used_caches: Mutex::new(BTreeSet::new()),
+        options: ResolveOptions {
+            // Only `sp install` takes `--namespace`; update and reinstall reuse
+            // the namespace each install recorded.
+            namespace: match &cli.command {
+                Commands::Install { namespace, .. } => namespace.clone(),
+                _ => None,
+            },
+            import_ref: cli.repo_ref.clone(),
+            with_skill_deps: cli.with_skill_deps,
+            remote_source: None,
+            no_bundled: cli.no_bundled || no_bundled_from_env(),
+            git: git_options(cli),
+        },
+        remote_packs: Mutex::new(BTreeMap::new()),
+        timings: Mutex::new(Vec::new()),
+        pack_defaults: OnceLock::new(),
+    };
+    if let Some(message) = newer_version_warning()? {
+        ctx.report(vec![message])?;
     }
-    if targets.custom {
-        agents.push("custom".to_string());
+    let result = run_command(cli, &ctx, output);
+    if cli.timings {
+        output.print_timings(&ctx.timings_view())?;
     }
-    let mut seen = HashSet::new();
-    agents.retain(|agent| seen.insert(agent.clone()));
-    agents
-}
-
-fn require_agents(targets: &AgentTargets) -> Result<Vec<String>> {
-    let agents = collect_agents(targets);
-    if agents.is_empty() {
-        return Err(eyre!("no agent targets specified")
-            .suggestion("Use --codex/--claude/--copilot/--cursor/--windsurf/--custom"));
-    }
-    Ok(agents)
-}
-
-fn validate_agent_selection(agents: &[String], path_override: Option<&Path>) -> Result<()> {
-    if agents.iter().any(|agent| agent == "custom") && agents.len() > 1 {
-        return Err(eyre!("custom agent cannot be combined with other targets")
-            .suggestion("Run separate installs per agent when using --custom"));
-    }
-    if path_override.is_some() && agents.len() != 1 {
-        return Err(eyre!("--path can only be used with a single agent target")
-            .suggestion("Run installs separately when overriding destinations"));
+    result?;
+    if cli.prune_cache_on_success {
+        ctx.prune_used_caches()?;
     }
     Ok(())
 }
 
-fn pack_repo_root(repo_root: &Path, pack_path: &Path) -> Result<PathBuf> {
-    let bundled_root = bundled_repo_root()?;
-    if pack_path.starts_with(&bundled_root) {
-        return Ok(bundled_root);
+/// `--git-timeout` and `--cache-readonly` over their environment variables.
+fn git_options(cli: &Cli) -> GitOptions {
+    let mut git = GitOptions::from_env();
+    if let Some(secs) = cli.git_timeout {
+        git.timeout = timeout_from_secs(secs);
     }
-    Ok(repo_root.to_path_buf())
+    git.cache_readonly |= cli.cache_readonly;
+    git
 }
 
-fn resolve_pack_context(repo_root: &Path, pack_arg: &str) -> Result<(PathBuf, PathBuf)> {
-    let pack_path = make_absolute(&resolve_pack_path(repo_root, pack_arg)?)?;
-    let pack_root = pack_repo_root(repo_root, &pack_path)?;
-    Ok((pack_path, pack_root))
-}
-
-fn show_pack(repo_root: &Path, cache_dir: &Path, pack_arg: &str, output: &Output) -> Result<()> {
-    let (pack_path, pack_root) = resolve_pack_context(repo_root, pack_arg)?;
-    let resolved = resolve_pack(&pack_root, &pack_path, cache_dir)?;
-    detect_collisions(
-        &resolved.final_skills,
-        &resolved.pack.install_prefix,
-        &resolved.pack.install_sep,
-        resolved.pack.install_flatten,
-    )?;
-
-    let pack_info = PackInfo {
-        name: resolved.pack.name.clone(),
-        file: pack_path.display().to_string(),
-        prefix: resolved.pack.install_prefix.clone(),
-        sep: resolved.pack.install_sep.clone(),
-        flatten: resolved.pack.install_flatten,
-    };
-    let local = resolved
-        .local
-        .iter()
-        .map(|skill| skill.id.clone())
-        .collect();
-    let imports = resolved
-        .imports
-        .iter()
-        .map(|import| ImportView {
-            repo: import.repo.clone(),
-            reference: import.ref_name.clone(),
-            commit: import.commit.clone(),
-            skills: import.skills.iter().map(|skill| skill.id.clone()).collect(),
-        })
-        .collect();
-    let final_install_names = resolved
-        .final_skills
-        .iter()
-        .map(|skill| {
-            install_name(
-                &resolved.pack.install_prefix,
-                &resolved.pack.install_sep,
-                &skill.id,
-                resolved.pack.install_flatten,
-            )
-        })
-        .collect();
-    let view = ShowView {
-        pack: pack_info,
-        local,
-        imports,
-        final_install_names,
-    };
-    output.print_show(&view)?;
-    Ok(())
-}
-
-fn install_cmd(
-    repo_root: &Path,
-    cache_dir: &Path,
-    pack_arg: &str,
-    targets: &AgentTargets,
-    path_override: Option<&Path>,
-    output: &Output,
-) -> Result<()> {
-    let (pack_path, pack_root) = resolve_pack_context(repo_root, pack_arg)?;
-    let config = load_config()?;
-    let agents = require_agents(targets)?;
-    validate_agent_selection(&agents, path_override)?;
-
-    let resolved = resolve_pack(&pack_root, &pack_path, cache_dir)?;
-    detect_collisions(
-        &resolved.final_skills,
-        &resolved.pack.install_prefix,
-        &resolved.pack.install_sep,
-        resolved.pack.install_flatten,
-    )?;
-
-    let mut state = load_state()?;
-    for agent in &agents {
-        let sink_path = resolve_sink_path(&config, agent, path_override)?;
-        let old_paths = state
-            .installs
-            .iter()
-            .find(|record| {
-                record.sink_path == sink_path.display().to_string()
-                    && record.pack == resolved.pack.name
-            })
-            .map(|record| record.installed_paths.clone())
-            .unwrap_or_default();
-        let record = install_pack(&resolved, agent, &sink_path, &mut state)?;
-        write_state(&state)?;
-
-        let old_set: HashSet<&str> = old_paths.iter().map(String::as_str).collect();
-        let new_set: HashSet<&str> = record.installed_paths.iter().map(String::as_str).collect();
-        let added = new_set.difference(&old_set).count();
-        let removed = old_set.difference(&new_set).count();
-        let updated = new_set.intersection(&old_set).count();
-        let view = InstallView {
-            pack: PackInfo {
-                name: resolved.pack.name.clone(),
-                file: pack_path.display().to_string(),
-                prefix: resolved.pack.install_prefix.clone(),
-                sep: resolved.pack.install_sep.clone(),
-                flatten: resolved.pack.install_flatten,
-            },
-            sink: agent.to_string(),
-            sink_path: sink_path.display().to_string(),
-            added,
-            updated,
-            removed,
-            installed_paths: record.installed_paths.clone(),
-        };
-        output.print_install(&view)?;
-        debug!(agent, added, updated, removed, "install summary");
-        for path in &record.installed_paths {
-            debug!(agent, path = %path, "installed path");
-        }
-    }
-    Ok(())
-}
-
-fn uninstall_cmd(
-    repo_root: &Path,
-    pack_arg: &str,
-    targets: &AgentTargets,
-    path_override: Option<&Path>,
-    output: &Output,
-) -> Result<()> {
-    let pack_name = if Path::new(pack_arg).exists() || pack_arg.ends_with(".yaml") {
-        let pack_path = make_absolute(&resolve_pack_path(repo_root, pack_arg)?)?;
-        load_pack(&pack_path)?.name
-    } else {
-        pack_arg.to_string()
-    };
-    let config = load_config()?;
-    let agents = require_agents(targets)?;
-    validate_agent_selection(&agents, path_override)?;
-
-    let mut state = load_state()?;
-    for agent in &agents {
-        let sink_path = resolve_sink_path(&config, agent, path_override)?;
-        let record = uninstall_pack(&mut state, &sink_path, &pack_name)?;
-        write_state(&state)?;
-
-        let view = UninstallView {
-            pack: pack_name.clone(),
-            sink: agent.to_string(),
-            sink_path: sink_path.display().to_string(),
-            removed: record.installed_paths.len(),
-        };
-        output.print_uninstall(&view)?;
+fn resolve_repo_root(cli: &Cli) -> Result<PathBuf> {
+    if let Some(ref root) = cli.repo_root {
+        return make_absolute(root);
     }
-    Ok(())
-}
-
-fn switch_cmd(
-    repo_root: &Path,
-    cache_dir: &Path,
-    pack_args: &[String],
-    targets: &AgentTargets,
-    path_override: Option<&Path>,
-    output: &Output,
-) -> Result<()> {
-    let config = load_config()?;
-    let agents = require_agents(targets)?;
-    validate_agent_selection(&agents, path_override)?;
-
-    // Pre-resolve all packs to fail early if any pack is invalid
-    let mut resolved_packs = Vec::new();
-    for pack_arg in pack_args {
-        let (pack_path, pack_root) = resolve_pack_context(repo_root, pack_arg)?;
-        let resolved = resolve_pack(&pack_root, &pack_path, cache_dir)?;
-        detect_collisions(
-            &resolved.final_skills,
-            &resolved.pack.install_prefix,
-            &resolved.pack.install_sep,
-            resolved.pack.install_flatten,
-        )?;
-        resolved_packs.push((pack_path, resolved));
+    let cwd = std::env::current_dir()?;
+    if let Some(found) = discover_repo_root(&cwd) {
+        return Ok(found);
     }
-
-    let mut state = load_state()?;
-    let mut sink_views = Vec::new();
-
-    for agent in &agents {
-        let sink_path = resolve_sink_path(&config, agent, path_override)?;
-        let sink_path_str = sink_path.display().to_string();
-
-        // Find all packs currently installed to this sink
-        let installed_packs: Vec<String> = state
-            .installs
-            .iter()
-            .filter(|r| r.sink_path == sink_path_str)
-            .map(|r| r.pack.clone())
-            .collect();
-
-        // Uninstall all existing packs from this sink
-        let mut uninstalled = Vec::new();
-        for pack_name in &installed_packs {
-            uninstall_pack(&mut state, &sink_path, pack_name)?;
-            uninstalled.push(pack_name.clone());
-        }
-
-        // Install new packs
-        let mut installed = Vec::new();
-        for (_pack_path, resolved) in &resolved_packs {
-            install_pack(resolved, agent, &sink_path, &mut state)?;
-            installed.push(resolved.pack.name.clone());
-        }
-
-        write_state(&state)?;
-
-        sink_views.push(SwitchSinkView {
-            sink: agent.to_string(),
-            sink_path: sink_path_str,
-            uninstalled,
-            installed,
-        });
+    // Any marker inside a work tree that contains the cwd was found above, so this
+    // only finds a root when GIT_WORK_TREE points git at a tree elsewhere (bare
+    // repo setups such as `git --git-dir=~/.dotfiles --work-tree=~`).
+    if let Some(toplevel) = git_toplevel(&cwd)
+        && is_repo_root(&toplevel)
+    {
+        debug!(root = %toplevel.display(), "repo root from git toplevel");
+        return Ok(toplevel);
     }
-
-    let view = SwitchView { sinks: sink_views };
-    output.print_switch(&view)?;
-    Ok(())
-}
-
-fn installed_cmd(
-    targets: &AgentTargets,
-    path_override: Option<&Path>,
-    output: &Output,
-) -> Result<()> {
-    let config = load_config()?;
-    let state = load_state()?;
-
-    let agents = collect_agents(targets);
-    validate_agent_selection(&agents, path_override)?;
-    let sink_filters: Option<HashSet<String>> = if agents.is_empty() {
-        None
-    } else {
-        let mut filters = HashSet::new();
-        for agent in &agents {
-            let sink_path = resolve_sink_path(&config, agent, path_override)?;
-            filters.insert(sink_path.display().to_string());
-        }
-        Some(filters)
-    };
-    let mut installs: Vec<InstalledItem> = state
-        .installs
-        .into_iter()
-        .filter(|record| {
-            if let Some(ref filters) = sink_filters {
-                return filters.contains(&record.sink_path);
-            }
-            true
-        })
-        .map(|record| InstalledItem {
-            sink: record.sink,
-            pack: record.pack,
-            skill_count: record.installed_paths.len(),
-            installed_at: record.installed_at,
-            sink_path: record.sink_path,
-        })
-        .collect();
-    installs.sort_by(|a, b| {
-        (a.sink.as_str(), a.pack.as_str()).cmp(&(b.sink.as_str(), b.pack.as_str()))
-    });
-    output.print_installed(&InstalledView { installs })?;
-    Ok(())
-}
-
-fn config_cmd(output: &Output) -> Result<()> {
-    let detail = load_config_detail()?;
-    let defaults = detail
-        .defaults
-        .iter()
-        .map(|(name, path)| SinkView {
-            name: name.clone(),
-            path: path.display().to_string(),
-        })
-        .collect();
-    let overrides = detail
-        .overrides
-        .iter()
-        .map(|(name, path)| SinkView {
-            name: name.clone(),
-            path: path.display().to_string(),
-        })
-        .collect();
-    let effective = detail
-        .effective
-        .iter()
-        .map(|(name, path)| SinkView {
-            name: name.clone(),
-            path: path.display().to_string(),
-        })
-        .collect();
-    let view = ConfigView {
-        config_path: detail.path.display().to_string(),
-        defaults,
-        overrides,
-        effective,
-    };
-    output.print_config(&view)?;
-    Ok(())
+    Ok(cwd)
 }
 
-fn init_diagnostics(verbose: bool, no_color: bool) -> Result<()> {
-    if no_color {
+fn init_diagnostics(verbose: bool, color: ColorChoice, log_file: Option<&Path>) -> Result<()> {
+    if color == ColorChoice::Never {
         // Safe: set before any threads spawn.
         unsafe { std::env::set_var("NO_COLOR", "1") };
     }
@@ -680,10 +300,33 @@ fn init_diagnostics(verbose: bool, no_color: bool) -> Result<()> {
             EnvFilter::new("warn")
         }
     });
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
+    let console = tracing_subscriber::fmt::layer()
         .with_writer(std::io::stderr)
-        .with_ansi(!no_color && std::io::stderr().is_terminal())
+        .with_ansi(match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+        })
+        .with_filter(filter);
+    // The file layer always records debug events, independent of console verbosity.
+    let file = match log_file {
+        Some(path) => {
+            let path = make_absolute(path)?;
+            let file = File::create(&path)
+                .wrap_err_with(|| format!("failed to create log file: {}", path.display()))?;
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Mutex::new(file))
+                .with_filter(EnvFilter::new("debug"));
+            Some(layer)
+        }
+        None => None,
+    };
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file)
         .try_init()
         .map_err(|err| eyre!("failed to initialize tracing subscriber: {err}"))?;
     Ok(())
@@ -0,0 +1,39 @@
+//! `sp cache`: locate, measure, list and clear the import cache.
+
+use super::commands::CacheAction;
+use crate::git::remote_origin;
+use crate::output::{CacheListView, CachePathView, CacheRepoItem, CacheSizeView, Output};
+use crate::util::dir_size;
+use color_eyre::eyre::Result;
+use std::path::Path;
+
+pub(super) fn cache_cmd(cache_dir: &Path, action: CacheAction, output: &Output) -> Result<()> {
+    let path = cache_dir.display().to_string();
+    match action {
+        CacheAction::Path => output.print_cache_path(&CachePathView { path })?,
+        CacheAction::Size => output.print_cache_size(&CacheSizeView {
+            path,
+            bytes: dir_size(cache_dir)?,
+        })?,
+        CacheAction::List => {
+            let mut repos = Vec::new();
+            if cache_dir.is_dir() {
+                for entry in std::fs::read_dir(cache_dir)? {
+                    let entry = entry?;
+                    if !entry.file_type()?.is_dir() {
+                        continue;
+                    }
+                    let dir = entry.path();
+                    repos.push(CacheRepoItem {
+                        path: dir.display().to_string(),
+                        origin: remote_origin(&dir),
+                        bytes: dir_size(&dir)?,
+                    });
+                }
+            }
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            output.print_cache_list(&CacheListView { path, repos })?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,489 @@
+//! Subcommand definitions and dispatch to their handlers.
+
+use super::cache::cache_cmd;
+use super::config::{config_check_cmd, config_cmd, config_edit_cmd, list_agents_cmd};
+use super::context::ResolveContext;
+use super::doctor::{doctor_cmd, doctor_repo_root};
+use super::install::{InstallCmdOptions, install_cmd, print_copy_progress};
+use super::listing::{list_packs, list_skills};
+use super::migrate::migrate_state_cmd;
+use super::show::{ShowOptions, explain_cmd, normalize_cmd, show_pack};
+use super::status::{installed_cmd, orphans_cmd, status_cmd};
+use super::uninstall::{UninstallOptions, uninstall_cmd};
+use super::update::{reinstall_cmd, switch_cmd, update_cmd};
+use super::{AgentTargets, Cli, resolve_repo_root};
+use crate::api::InstallOptions;
+use crate::output::{GroupBy, Output};
+use crate::util::parse_age;
+use clap::{Subcommand, ValueHint};
+use color_eyre::eyre::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Subcommand, Debug)]
+pub(super) enum Commands {
+    #[command(about = "List local skills under ./skills", visible_alias = "list")]
+    Skills {
+        #[arg(long, alias = "all", help = "Include bundled skills")]
+        bundled: bool,
+    },
+    #[command(about = "List packs under ./packs")]
+    Packs {
+        #[arg(
+            long,
+            help = "List each pack's local skills (imports are counted, not fetched)"
+        )]
+        with_skills: bool,
+    },
+    #[command(about = "Show resolved contents of a pack", visible_alias = "pack")]
+    Show {
+        #[arg(value_name = "PACK")]
+        pack: String,
+        #[arg(long, help = "Report install name collisions instead of failing")]
+        allow_collisions: bool,
+        #[arg(
+            long,
+            visible_alias = "tree-depth",
+            value_name = "N",
+            help = "List at most N skills per import (pretty output only)"
+        )]
+        max_skills: Option<usize>,
+        #[arg(
+            long,
+            conflicts_with = "local_only",
+            help = "Show only imported skills"
+        )]
+        imports_only: bool,
+        #[arg(long, help = "Show only local skills")]
+        local_only: bool,
+        #[arg(long, help = "Print only the number of skills the pack installs")]
+        count: bool,
+        #[arg(long, help = "List the skills each exclude pattern removed")]
+        explain_excludes: bool,
+        #[arg(
+            long,
+            help = "Show each skill's name and description from its frontmatter"
+        )]
+        with_metadata: bool,
+        #[arg(
+            long,
+            value_name = "PACK",
+            conflicts_with_all = ["count", "local_only", "imports_only"],
+            help = "Compare final skills with another pack"
+        )]
+        diff_against: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["count", "local_only", "imports_only", "diff_against"],
+            help = "Explain every matched skill: patterns, excludes, collisions, install name"
+        )]
+        explain: bool,
+        #[arg(
+            long,
+            help = "With --format plain, print only skill ids and install names, without section labels"
+        )]
+        bare: bool,
+    },
+    #[command(about = "Explain why a skill is or is not part of a pack")]
+    Explain {
+        #[arg(value_name = "PACK")]
+        pack: String,
+        #[arg(value_name = "SKILL", help = "Skill id (original or renamed)")]
+        skill: String,
+    },
+    #[command(about = "Print a pack file with every default filled in")]
+    Normalize {
+        #[arg(value_name = "PACK")]
+        pack: String,
+    },
+    #[command(about = "Install a pack into an agent destination")]
+    Install {
+        #[arg(
+            value_name = "PACK",
+            help = "Pack name, pack file, or - to read YAML from stdin"
+        )]
+        pack: String,
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Pin resolved commits so sp update skips this install")]
+        freeze: bool,
+        #[arg(long, help = "Overwrite destination folders not owned by this pack")]
+        force: bool,
+        #[arg(
+            long,
+            help = "Keep folders from the previous install that the pack no longer selects"
+        )]
+        no_reconcile: bool,
+        #[arg(
+            long,
+            help = "Keep running and reinstall when local skill files change (Ctrl-C to stop)"
+        )]
+        watch: bool,
+        #[arg(
+            long,
+            help = "Also record installed skills in index.json, next to state.json"
+        )]
+        link_manifest: bool,
+        #[arg(
+            long,
+            help = "Install packs listed under requires: that are missing from the sink"
+        )]
+        with_deps: bool,
+        #[arg(
+            long,
+            help = "Print each skill and its size on stderr as it is copied (not with json)"
+        )]
+        progress: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["watch", "link_manifest"],
+            help = "Copy without reading or writing state.json (existing folders need --force)"
+        )]
+        no_state: bool,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Prefix every install name with NAME and the pack's sep (for shared sinks)"
+        )]
+        namespace: Option<String>,
+    },
+    #[command(about = "Re-install installed packs from their pack files")]
+    Update {
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Also update frozen installs")]
+        force: bool,
+    },
+    #[command(about = "Uninstall and install a pack again, copying every folder from scratch")]
+    Reinstall {
+        #[arg(value_name = "PACK", help = "Pack name or pack file")]
+        pack: String,
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Uninstall a pack from an agent destination")]
+    Uninstall {
+        #[arg(
+            value_name = "PACK",
+            required_unless_present_any = ["all", "older_than"],
+            help = "Pack name, pack file, or a name glob like team-* (quote it)"
+        )]
+        pack: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "pack",
+            help = "Uninstall every pack recorded in the selected sinks"
+        )]
+        all: bool,
+        #[arg(
+            long,
+            value_name = "AGE",
+            value_parser = parse_age,
+            conflicts_with = "pack",
+            help = "Uninstall packs in the selected sinks installed more than AGE ago (30d, 12h)"
+        )]
+        older_than: Option<Duration>,
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Succeed without changes when the pack is not installed")]
+        if_installed: bool,
+        #[arg(long, help = "Remove the sink directory if it is empty afterwards")]
+        prune_empty: bool,
+        #[arg(long, help = "Report what would be removed without changing anything")]
+        dry_run: bool,
+        #[arg(long, help = "Stop tracking the pack but leave its folders in place")]
+        keep_files: bool,
+    },
+    #[command(about = "Summarize installed packs and skills per agent")]
+    Status {
+        #[arg(long, help = "Fetch imports and report packs with newer commits")]
+        check_updates: bool,
+    },
+    #[command(about = "Rewrite recorded paths after moving sinks or the home directory")]
+    MigrateState {
+        #[arg(value_name = "FROM", value_hint = ValueHint::DirPath)]
+        from_prefix: PathBuf,
+        #[arg(value_name = "TO", value_hint = ValueHint::DirPath)]
+        to_prefix: PathBuf,
+        #[arg(
+            long = "move",
+            help = "Also move installed skill folders to their new paths"
+        )]
+        move_files: bool,
+        #[arg(long, help = "Report what would change without changing anything")]
+        dry_run: bool,
+    },
+    #[command(about = "List installed packs", visible_alias = "installs")]
+    Installed {
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+        #[arg(long, help = "List sink folders not owned by any installed pack")]
+        orphans: bool,
+        #[arg(
+            long,
+            value_enum,
+            value_name = "KEY",
+            conflicts_with = "orphans",
+            help = "Group installs under sink or pack headers (JSON: installs keyed by name)"
+        )]
+        group_by: Option<GroupBy>,
+    },
+    #[command(about = "Switch packs: uninstall all current packs and install new ones")]
+    Switch {
+        #[arg(value_name = "PACK", required = true, num_args = 1..)]
+        packs: Vec<String>,
+        #[command(flatten)]
+        targets: AgentTargets,
+        #[arg(
+            long,
+            value_hint = ValueHint::DirPath,
+            help = "Override agent destination path (required for custom)"
+        )]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "List agent names you can install to, with their sink paths")]
+    ListAgents,
+    #[command(about = "Show sink configuration", visible_alias = "sinks")]
+    Config {
+        #[arg(long, help = "Check config.yaml sinks and fail on errors")]
+        check: bool,
+        #[arg(
+            long,
+            requires = "check",
+            help = "Stop checking at the first error instead of reporting every problem"
+        )]
+        fail_fast: bool,
+        #[arg(
+            long,
+            conflicts_with = "check",
+            help = "Open config.yaml in $EDITOR (created from a template if missing), then check it"
+        )]
+        edit: bool,
+    },
+    #[command(about = "Check state, the git cache and the repo layout for common problems")]
+    Doctor {
+        #[arg(
+            long,
+            help = "Repair findings: drop records of missing sinks, create missing skills/ and packs/"
+        )]
+        fix: bool,
+        #[arg(
+            long,
+            requires = "fix",
+            help = "With --fix, also delete cached repos no install imports from"
+        )]
+        yes: bool,
+    },
+    #[command(about = "Inspect the git cache")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub(super) enum CacheAction {
+    #[command(about = "Print the cache directory")]
+    Path,
+    #[command(about = "Print the total size of the cache")]
+    Size,
+    #[command(about = "List cached repos with their origin and size")]
+    List,
+}
+
+pub(super) fn run_command(cli: &Cli, ctx: &ResolveContext, output: &Output) -> Result<()> {
+    match cli.command {
+        Commands::Skills { bundled } => list_skills(&resolve_repo_root(cli)?, bundled, ctx, output),
+        Commands::Packs { with_skills } => {
+            list_packs(&resolve_repo_root(cli)?, with_skills, ctx, output)
+        }
+        Commands::Show {
+            ref pack,
+            allow_collisions,
+            max_skills,
+            imports_only,
+            local_only,
+            count,
+            explain_excludes,
+            with_metadata,
+            ref diff_against,
+            explain,
+            bare,
+        } => show_pack(
+            &resolve_repo_root(cli)?,
+            ctx,
+            pack,
+            &ShowOptions {
+                allow_collisions,
+                max_skills,
+                imports_only,
+                local_only,
+                count,
+                explain_excludes,
+                with_metadata,
+                diff_against: diff_against.clone(),
+                explain,
+                bare,
+            },
+            output,
+        ),
+        Commands::Explain {
+            ref pack,
+            ref skill,
+        } => explain_cmd(&resolve_repo_root(cli)?, ctx, pack, skill, output),
+        Commands::Normalize { ref pack } => {
+            normalize_cmd(&resolve_repo_root(cli)?, ctx, pack, output)
+        }
+        Commands::Install {
+            ref pack,
+            ref targets,
+            ref path,
+            freeze,
+            force,
+            no_reconcile,
+            watch,
+            link_manifest,
+            with_deps,
+            progress,
+            no_state,
+            // Read into the run's resolve options.
+            namespace: _,
+        } => install_cmd(
+            &resolve_repo_root(cli)?,
+            ctx,
+            pack,
+            targets,
+            path.as_deref(),
+            &InstallCmdOptions {
+                install: InstallOptions {
+                    freeze,
+                    force,
+                    no_reconcile,
+                    progress: (progress && !output.is_json()).then_some(print_copy_progress),
+                    skip_unchanged: false,
+                    discard_preserved: false,
+                },
+                watch,
+                link_manifest,
+                with_deps,
+                no_state,
+            },
+            output,
+        ),
+        Commands::Update {
+            ref targets,
+            ref path,
+            force,
+        } => update_cmd(ctx, targets, path.as_deref(), force, output),
+        Commands::Reinstall {
+            ref pack,
+            ref targets,
+            ref path,
+        } => reinstall_cmd(
+            &resolve_repo_root(cli)?,
+            ctx,
+            pack,
+            targets,
+            path.as_deref(),
+            output,
+        ),
+        Commands::Uninstall {
+            ref pack,
+            all: _,
+            older_than,
+            ref targets,
+            ref path,
+            if_installed,
+            prune_empty,
+            dry_run,
+            keep_files,
+        } => uninstall_cmd(
+            &resolve_repo_root(cli)?,
+            ctx,
+            pack.as_deref(),
+            targets,
+            path.as_deref(),
+            &UninstallOptions {
+                if_installed,
+                prune_empty,
+                dry_run,
+                older_than,
+                keep_files,
+            },
+            output,
+        ),
+        Commands::Status { check_updates } => status_cmd(ctx, check_updates, output),
+        Commands::MigrateState {
+            ref from_prefix,
+            ref to_prefix,
+            move_files,
+            dry_run,
+        } => migrate_state_cmd(ctx, from_prefix, to_prefix, move_files, dry_run, output),
+        Commands::Installed {
+            ref targets,
+            ref path,
+            orphans: false,
+            group_by,
+        } => installed_cmd(targets, path.as_deref(), group_by, output),
+        Commands::Installed {
+            ref targets,
+            ref path,
+            orphans: true,
+            ..
+        } => orphans_cmd(targets, path.as_deref(), output),
+        Commands::Switch {
+            ref packs,
+            ref targets,
+            ref path,
+        } => switch_cmd(
+            &resolve_repo_root(cli)?,
+            ctx,
+            packs,
+            targets,
+            path.as_deref(),
+            output,
+        ),
+        Commands::ListAgents => list_agents_cmd(output),
+        Commands::Config { edit: true, .. } => config_edit_cmd(output),
+        Commands::Config { check: false, .. } => config_cmd(output),
+        Commands::Config {
+            check: true,
+            fail_fast,
+            ..
+        } => config_check_cmd(fail_fast, output),
+        Commands::Doctor { fix, yes } => {
+            doctor_cmd(doctor_repo_root(cli)?.as_deref(), ctx, fix, yes, output)
+        }
+        Commands::Cache { action } => cache_cmd(&ctx.cache_dir, action, output),
+    }
+}
@@ -0,0 +1,101 @@
+//! `sp config` and `sp agents`: show, check and edit the user config.
+
+use crate::config::{
+    Severity, check_config, config_path, config_template, ensure_config_dir, load_config_detail,
+    write_version_marker,
+};
+use crate::output::{AgentsView, ConfigCheckView, ConfigView, Output, SinkView};
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub(super) fn config_cmd(output: &Output) -> Result<()> {
+    let detail = load_config_detail()?;
+    let sink_views = |sinks: &BTreeMap<String, PathBuf>, source: Option<&str>| {
+        sinks
+            .iter()
+            .map(|(name, path)| SinkView {
+                name: name.clone(),
+                path: path.display().to_string(),
+                source: source.unwrap_or_else(|| detail.source(name)).to_string(),
+            })
+            .collect()
+    };
+    let view = ConfigView {
+        config_path: detail.path.display().to_string(),
+        defaults: sink_views(&detail.defaults, Some("default")),
+        overrides: sink_views(&detail.overrides, None),
+        effective: sink_views(&detail.effective, None),
+    };
+    output.print_config(&view)?;
+    Ok(())
+}
+
+/// Effective sinks only, for scripts that loop over agent names.
+pub(super) fn list_agents_cmd(output: &Output) -> Result<()> {
+    let detail = load_config_detail()?;
+    let agents = detail
+        .effective
+        .iter()
+        .map(|(name, path)| SinkView {
+            name: name.clone(),
+            path: path.display().to_string(),
+            source: detail.source(name).to_string(),
+        })
+        .collect();
+    output.print_agents(&AgentsView { agents })?;
+    Ok(())
+}
+
+pub(super) fn config_check_cmd(fail_fast: bool, output: &Output) -> Result<()> {
+    let findings = check_config(fail_fast)?;
+    let errors = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    let view = ConfigCheckView {
+        config_path: config_path()?.display().to_string(),
+        errors,
+        warnings: findings.len() - errors,
+        findings,
+    };
+    output.print_config_check(&view)?;
+    if errors > 0 {
+        return Err(eyre!("config check found {errors} error(s)")
+            .suggestion(format!("Fix the sinks in {}", view.config_path)));
+    }
+    Ok(())
+}
+
+/// Opens `config.yaml` in `$EDITOR` (default `vi`), writing the commented template
+/// first when the file is missing, and checks the result once the editor exits.
+pub(super) fn config_edit_cmd(output: &Output) -> Result<()> {
+    ensure_config_dir()?;
+    let path = config_path()?;
+    if !path.exists() {
+        std::fs::write(&path, config_template()?)
+            .wrap_err_with(|| format!("failed to write config file: {}", path.display()))?;
+    }
+    let editor = std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // `EDITOR="code --wait"` style values carry their own arguments.
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .wrap_err_with(|| format!("failed to run editor: {editor}"))
+        .suggestion("Set EDITOR to an installed editor")?;
+    if !status.success() {
+        return Err(eyre!("editor exited with {status}").suggestion(format!(
+            "The config is unchanged on disk at {}",
+            path.display()
+        )));
+    }
+    write_version_marker()?;
+    config_check_cmd(false, output)
+}
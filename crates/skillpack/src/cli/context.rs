@@ -0,0 +1,247 @@
+//! Per-run resolution settings and the warnings, caches and timings they collect.
+
+use crate::api::locate_pack;
+use crate::config::{PackDefaults, load_pack_defaults};
+use crate::git::resolve_repo;
+use crate::output::{PhaseTiming, TimingsView, WarningSink};
+use crate::pack::{Pack, load_pack, split_remote_pack};
+use crate::resolve::{ResolveOptions, ResolvedPack, detect_collisions, resolve_loaded_pack};
+use crate::state::InstallRecord;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+pub(super) struct ResolveContext<'a> {
+    pub(super) cache_dir: PathBuf,
+    pub(super) strict: bool,
+    pub(super) warnings: &'a WarningSink,
+    /// Import checkouts resolved during this run, for `--prune-cache-on-success`.
+    pub(super) used_caches: Mutex<BTreeSet<PathBuf>>,
+    /// Resolve options from this run's flags; a pack fetched from a repo also
+    /// gets its `<repo>#<pack>` spec.
+    pub(super) options: ResolveOptions,
+    /// Pack files fetched from `<repo>#<pack>` this run, with that spec.
+    pub(super) remote_packs: Mutex<BTreeMap<PathBuf, String>>,
+    /// Summed duration per phase, in the order phases first ran, for `--timings`.
+    pub(super) timings: Mutex<Vec<(&'static str, Duration)>>,
+    /// `defaults:` from `config.yaml`, read by the first pack loaded this run.
+    pub(super) pack_defaults: OnceLock<PackDefaults>,
+}
+
+impl ResolveContext<'_> {
+    /// Loads a pack file with this run's config defaults.
+    pub(super) fn load_pack(&self, pack_path: &Path) -> Result<Pack> {
+        load_pack(pack_path, self.pack_defaults()?)
+    }
+
+    /// Config defaults, read on first use so commands that never load a pack
+    /// (like `sp config --edit`) still run with a broken `defaults:`.
+    pub(super) fn pack_defaults(&self) -> Result<&PackDefaults> {
+        if let Some(defaults) = self.pack_defaults.get() {
+            return Ok(defaults);
+        }
+        let defaults = load_pack_defaults()?;
+        Ok(self.pack_defaults.get_or_init(|| defaults))
+    }
+
+    /// Resolves a pack, reports its warnings, and checks install name collisions.
+    pub(super) fn resolve(&self, repo_root: &Path, pack_path: &Path) -> Result<ResolvedPack> {
+        let resolved = self.resolve_unchecked(repo_root, pack_path)?;
+        self.check(resolved, true)
+    }
+
+    pub(super) fn resolve_loaded(
+        &self,
+        repo_root: &Path,
+        pack: Pack,
+        pack_path: &Path,
+    ) -> Result<ResolvedPack> {
+        let resolved = self.resolve_parsed(repo_root, pack, pack_path)?;
+        self.check(resolved, true)
+    }
+
+    /// Resolves a pack file without `check`, for callers that report collisions
+    /// their own way.
+    pub(super) fn resolve_unchecked(
+        &self,
+        repo_root: &Path,
+        pack_path: &Path,
+    ) -> Result<ResolvedPack> {
+        self.resolve_parsed(repo_root, self.load_pack(pack_path)?, pack_path)
+    }
+
+    /// Resolves a pack the way `record` installed it: with its namespace, and
+    /// following `depends_on:` if that install did.
+    pub(super) fn resolve_recorded(
+        &self,
+        repo_root: &Path,
+        pack_path: &Path,
+        record: &InstallRecord,
+    ) -> Result<ResolvedPack> {
+        let options = ResolveOptions {
+            namespace: record.namespace.clone(),
+            with_skill_deps: record.with_skill_deps || self.options.with_skill_deps,
+            ..self.options.clone()
+        };
+        let resolved =
+            self.resolve_parsed_with(repo_root, self.load_pack(pack_path)?, pack_path, options)?;
+        self.check(resolved, true)
+    }
+
+    fn resolve_parsed(
+        &self,
+        repo_root: &Path,
+        pack: Pack,
+        pack_path: &Path,
+    ) -> Result<ResolvedPack> {
+        self.resolve_parsed_with(repo_root, pack, pack_path, self.options.clone())
+    }
+
+    fn resolve_parsed_with(
+        &self,
+        repo_root: &Path,
+        pack: Pack,
+        pack_path: &Path,
+        mut options: ResolveOptions,
+    ) -> Result<ResolvedPack> {
+        options.remote_source = self
+            .remote_packs
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(pack_path)
+            .cloned();
+        let resolved = resolve_loaded_pack(repo_root, pack, pack_path, &self.cache_dir, options)?;
+        for &(phase, elapsed) in &resolved.phase_times {
+            self.record_time(phase, elapsed);
+        }
+        Ok(resolved)
+    }
+
+    fn record_time(&self, phase: &'static str, elapsed: Duration) {
+        let mut timings = self.timings.lock().unwrap_or_else(|err| err.into_inner());
+        match timings.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => timings.push((phase, elapsed)),
+        }
+    }
+
+    pub(super) fn timed<T>(&self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let value = f();
+        self.record_time(phase, started.elapsed());
+        value
+    }
+
+    pub(super) fn timings_view(&self) -> TimingsView {
+        let timings = self.timings.lock().unwrap_or_else(|err| err.into_inner());
+        TimingsView {
+            phases: timings
+                .iter()
+                .map(|&(phase, elapsed)| PhaseTiming {
+                    phase: phase.to_string(),
+                    ms: elapsed.as_secs_f64() * 1000.0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Finds a pack like `locate_pack`, or, when no local pack matches, fetches
+    /// `<repo>#<pack>` into the cache and resolves the pack's skills against that
+    /// checkout.
+    pub(super) fn locate(&self, repo_root: &Path, pack_arg: &str) -> Result<(PathBuf, PathBuf)> {
+        let local = locate_pack(repo_root, pack_arg, &self.options);
+        match split_remote_pack(pack_arg) {
+            Some(_) if local.is_ok() => local,
+            Some((repo, pack)) => self.fetch_remote_pack(repo, pack),
+            None => local,
+        }
+    }
+
+    /// Fetches `repo` and finds `pack` in it (a name under `packs/`, or a path to a
+    /// YAML file), remembering the spec so installs record it.
+    pub(super) fn fetch_remote_pack(&self, repo: &str, pack: &str) -> Result<(PathBuf, PathBuf)> {
+        let checkout = self.timed("fetch pack repo", || {
+            resolve_repo(&self.cache_dir, repo, None, self.options.git)
+        })?;
+        let pack_path = if pack.ends_with(".yaml") || pack.ends_with(".yml") {
+            checkout.path.join(pack)
+        } else {
+            checkout.path.join("packs").join(format!("{pack}.yaml"))
+        };
+        if !pack_path.is_file() {
+            return Err(eyre!("pack {pack} not found in {repo}").suggestion(format!(
+                "Expected {} in the repo's default branch",
+                pack_path
+                    .strip_prefix(&checkout.path)
+                    .unwrap_or(&pack_path)
+                    .display()
+            )));
+        }
+        self.remote_packs
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(pack_path.clone(), format!("{repo}#{pack}"));
+        Ok((pack_path, checkout.path))
+    }
+
+    /// Reports warnings gathered outside pack resolution, such as skipped skills.
+    pub(super) fn report(&self, messages: Vec<String>) -> Result<()> {
+        if self.strict
+            && let Some(first) = messages.first()
+        {
+            return Err(eyre!("{first}").suggestion("Fix the warning or drop --strict"));
+        }
+        for message in messages {
+            self.warnings.push(message);
+        }
+        Ok(())
+    }
+
+    pub(super) fn check(
+        &self,
+        resolved: ResolvedPack,
+        collisions_fatal: bool,
+    ) -> Result<ResolvedPack> {
+        self.used_caches
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .extend(resolved.imports.iter().map(|import| import.path.clone()));
+        if self.strict
+            && let Some(first) = resolved.warnings.first()
+        {
+            return Err(eyre!("{first}").suggestion("Fix the warning or drop --strict"));
+        }
+        for message in &resolved.warnings {
+            self.warnings
+                .push(format!("{}: {message}", resolved.pack.name));
+        }
+        if collisions_fatal {
+            self.timed("collision check", || {
+                detect_collisions(&resolved.final_skills, &resolved.pack)
+            })?;
+        }
+        Ok(resolved)
+    }
+
+    /// Deletes the import checkouts this run resolved; other cached repos stay.
+    pub(super) fn prune_used_caches(&self) -> Result<()> {
+        let used = std::mem::take(
+            &mut *self
+                .used_caches
+                .lock()
+                .unwrap_or_else(|err| err.into_inner()),
+        );
+        for path in used {
+            if path.starts_with(&self.cache_dir) && path.is_dir() {
+                debug!(path = %path.display(), "prune used cache");
+                std::fs::remove_dir_all(&path)
+                    .wrap_err_with(|| format!("failed to remove cache: {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! `sp doctor`: find and, with `--fix`, repair drift between state and disk.
+
+use super::Cli;
+use super::context::ResolveContext;
+use crate::git::{git_toplevel, remote_origin, repo_cache_dir};
+use crate::index::sync_index;
+use crate::output::{DoctorFinding, DoctorView, Output};
+use crate::repo_config::load_repo_config;
+use crate::state::{InstallRecord, load_state, write_state};
+use crate::util::{discover_repo_root, is_repo_root, make_absolute};
+use color_eyre::eyre::{Result, WrapErr};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// The repo `sp doctor` checks for skills/ and packs/: `--root`, or one found from
+/// the working directory; unlike other commands, never the bare working directory.
+pub(super) fn doctor_repo_root(cli: &Cli) -> Result<Option<PathBuf>> {
+    if let Some(ref root) = cli.repo_root {
+        return make_absolute(root).map(Some);
+    }
+    let cwd = std::env::current_dir()?;
+    Ok(discover_repo_root(&cwd).or_else(|| git_toplevel(&cwd).filter(|top| is_repo_root(top))))
+}
+
+/// `sp doctor`: each fix stays inside a known location. Dropping records only edits
+/// state, missing dirs are created under the repo root, and cached repos (the one
+/// fix that deletes files) are removed only with `--yes` and only from the cache dir.
+pub(super) fn doctor_cmd(
+    repo_root: Option<&Path>,
+    ctx: &ResolveContext,
+    fix: bool,
+    yes: bool,
+    output: &Output,
+) -> Result<()> {
+    let mut findings = Vec::new();
+    let fixed = || fix.then(|| "fixed".to_string());
+
+    let mut state = load_state()?;
+    let sink_exists = |record: &InstallRecord| Path::new(&record.sink_path).is_dir();
+    for record in state.installs.iter().filter(|record| !sink_exists(record)) {
+        findings.push(DoctorFinding {
+            kind: "dangling-record".to_string(),
+            path: record.sink_path.clone(),
+            message: format!(
+                "{} is recorded in a sink that no longer exists",
+                record.pack
+            ),
+            fix: fixed(),
+        });
+    }
+    if fix && !findings.is_empty() {
+        state.installs.retain(sink_exists);
+        write_state(&state)?;
+        sync_index(&state)?;
+    }
+
+    let used: HashSet<PathBuf> = state
+        .installs
+        .iter()
+        .flat_map(|record| &record.imports)
+        .map(|import| repo_cache_dir(&ctx.cache_dir, &import.repo))
+        // Packs installed as `<repo>#<pack>` live in a cached checkout too.
+        .chain(state.installs.iter().filter_map(|record| {
+            let relative = Path::new(&record.pack_file)
+                .strip_prefix(&ctx.cache_dir)
+                .ok()?;
+            Some(ctx.cache_dir.join(relative.components().next()?))
+        }))
+        .collect();
+    if ctx.cache_dir.is_dir() {
+        let mut unused = Vec::new();
+        for entry in std::fs::read_dir(&ctx.cache_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() && !used.contains(&entry.path()) {
+                unused.push(entry.path());
+            }
+        }
+        unused.sort();
+        for dir in unused {
+            let status = match (fix, yes) {
+                (false, _) => None,
+                _ if ctx.options.git.cache_readonly => {
+                    Some("skipped: --cache-readonly".to_string())
+                }
+                (true, false) => Some("needs --yes".to_string()),
+                (true, true) => {
+                    debug!(path = %dir.display(), "remove unused cache");
+                    std::fs::remove_dir_all(&dir)
+                        .wrap_err_with(|| format!("failed to remove cache: {}", dir.display()))?;
+                    fixed()
+                }
+            };
+            findings.push(DoctorFinding {
+                kind: "orphaned-cache".to_string(),
+                message: format!(
+                    "cached repo {} is not imported by any install",
+                    remote_origin(&dir).unwrap_or_else(|| "(no origin)".to_string())
+                ),
+                path: dir.display().to_string(),
+                fix: status,
+            });
+        }
+    }
+
+    if let Some(root) = repo_root {
+        let mut dirs = load_repo_config(root)?.skills_roots();
+        dirs.push("packs".to_string());
+        for dir in dirs {
+            let path = root.join(&dir);
+            if path.is_dir() {
+                continue;
+            }
+            if fix {
+                std::fs::create_dir_all(&path)
+                    .wrap_err_with(|| format!("failed to create {}", path.display()))?;
+            }
+            findings.push(DoctorFinding {
+                kind: "missing-dir".to_string(),
+                path: path.display().to_string(),
+                message: format!("repo has no {dir}/ directory"),
+                fix: fixed(),
+            });
+        }
+    }
+
+    output.print_doctor(&DoctorView { fix, findings })?;
+    Ok(())
+}
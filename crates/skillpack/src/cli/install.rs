@@ -0,0 +1,287 @@
+//! `sp install`, including `requires:`, `--watch` and sink warnings.
+
+use super::AgentTargets;
+use super::context::ResolveContext;
+use super::targets::{install_agents, validate_agent_selection};
+use crate::config::{load_config, load_config_detail, resolve_sink_path};
+use crate::index::{load_index, write_index};
+use crate::install::{InstallOptions, foreign_entries, install_to_sink};
+use crate::output::{InstallView, Output, ReinstallView};
+use crate::pack::{Pack, STDIN_PACK_FILE, load_pack_str};
+use crate::state::{StateFile, find_record_index, load_state, write_state};
+use crate::watch::SkillWatcher;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{error, info, info_span};
+
+pub(super) fn install_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: &str,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    options: &InstallCmdOptions,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let (pack_path, resolved) = if pack_arg == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        let pack = load_pack_str(&content, STDIN_PACK_FILE, ctx.pack_defaults()?)?;
+        let pack_path = PathBuf::from(STDIN_PACK_FILE);
+        let resolved = ctx.resolve_loaded(repo_root, pack, &pack_path)?;
+        (pack_path, resolved)
+    } else {
+        let (pack_path, pack_root) = ctx.locate(repo_root, pack_arg)?;
+        let resolved = ctx.resolve(&pack_root, &pack_path)?;
+        (pack_path, resolved)
+    };
+    let agents = install_agents(&config, targets, &resolved.pack)?;
+    validate_agent_selection(&agents, path_override)?;
+
+    let mut watcher = if options.watch {
+        let watcher = SkillWatcher::new(&resolved.final_skills)?;
+        if watcher.is_empty() {
+            return Err(eyre!("--watch found no local skills to watch")
+                .suggestion("Imported skills come from the git cache; edit local skills instead"));
+        }
+        Some(watcher)
+    } else {
+        None
+    };
+
+    let sinks = agents
+        .iter()
+        .map(|agent| {
+            Ok((
+                agent.clone(),
+                resolve_sink_path(&config, agent, path_override)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let detail = load_config_detail()?;
+    for (agent, sink_path) in &sinks {
+        // Built-in sinks are expected to be missing on a fresh machine; only
+        // paths someone typed are worth a typo warning.
+        if path_override.is_some() || detail.source(agent) != "default" {
+            warn_missing_sink_parent(ctx, sink_path)?;
+        }
+    }
+    // Checked for every sink up front so `--strict` fails before any copy.
+    if !options.no_state {
+        let state = load_state()?;
+        for (_, sink_path) in &sinks {
+            warn_foreign_entries(ctx, &state, sink_path)?;
+        }
+    }
+    // Hands each sink's views to `emit` as soon as that sink's state is written,
+    // so a later sink failing does not swallow what was already installed.
+    let install_all = |emit: &mut dyn FnMut(InstallView) -> Result<()>| -> Result<()> {
+        // Without state nothing is owned, so nothing is reconciled and every
+        // existing destination folder counts as foreign.
+        let mut state = if options.no_state {
+            StateFile::default()
+        } else {
+            load_state()?
+        };
+        // `requires:` still checks what is recorded, read but never written.
+        let recorded = if options.no_state {
+            Some(load_state()?)
+        } else {
+            None
+        };
+        for (agent, sink_path) in &sinks {
+            let _span = info_span!("install", pack = %resolved.pack.name, agent = %agent).entered();
+            let mut views = Vec::new();
+            let target = RequirementsTarget {
+                repo_root,
+                ctx,
+                agent,
+                sink_path,
+                options,
+                recorded: recorded.as_ref(),
+            };
+            install_requirements(
+                &target,
+                &resolved.pack,
+                &mut state,
+                &mut vec![resolved.pack.name.clone()],
+                &mut views,
+            )?;
+            let view = ctx.timed("copy", || {
+                install_to_sink(
+                    &resolved,
+                    &pack_path,
+                    agent,
+                    sink_path,
+                    &mut state,
+                    &options.install,
+                )
+            })?;
+            if !options.no_state {
+                ctx.timed("state write", || write_state(&state))?;
+            }
+            views.push(view);
+            for view in views {
+                emit(view)?;
+            }
+        }
+        if options.link_manifest {
+            let mut index = load_index()?.unwrap_or_default();
+            for (agent, sink_path) in &sinks {
+                index.record_install(&resolved, agent, sink_path);
+            }
+            index.prune(&state);
+            write_index(&index)?;
+        }
+        Ok(())
+    };
+
+    install_all(&mut |view| Ok(output.print_install(&view)?))?;
+    let Some(watcher) = watcher.as_mut() else {
+        return Ok(());
+    };
+    info!(skills = watcher.len(), "watching local skills");
+    // Runs until Ctrl-C ends the process; state is written atomically, so an
+    // interrupted reinstall leaves the last complete state behind.
+    loop {
+        let changed = watcher.wait_for_changes(WATCH_INTERVAL)?;
+        let mut reinstalled = Vec::new();
+        let result = install_all(&mut |view| {
+            reinstalled.push(view.sink);
+            Ok(())
+        });
+        // A bad edit should not end the session; the next change retries.
+        if let Err(err) = result {
+            error!("reinstall failed, still watching: {err:#}");
+            continue;
+        }
+        output.print_reinstall(&ReinstallView {
+            pack: resolved.pack.name.clone(),
+            skills: changed,
+            sinks: reinstalled,
+        })?;
+    }
+}
+
+/// Reports a sink skillpack has never installed into that already holds other
+/// content, since another tool may manage it.
+fn warn_foreign_entries(ctx: &ResolveContext, state: &StateFile, sink_path: &Path) -> Result<()> {
+    let names = foreign_entries(state, sink_path)?;
+    if names.is_empty() {
+        return Ok(());
+    }
+    const SHOWN: usize = 5;
+    let mut listed = names
+        .iter()
+        .take(SHOWN)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > SHOWN {
+        listed.push_str(&format!(" and {} more", names.len() - SHOWN));
+    }
+    ctx.report(vec![format!(
+        "sink {} already holds entries skillpack did not install ({listed}); \
+consider a dedicated directory, and note --force takes over same-named folders",
+        sink_path.display()
+    )])
+}
+
+/// Reports a sink whose parent folder does not exist either, which is more
+/// likely a mistyped path than a sink to create; install still creates it.
+fn warn_missing_sink_parent(ctx: &ResolveContext, sink_path: &Path) -> Result<()> {
+    let Some(parent) = sink_path.parent() else {
+        return Ok(());
+    };
+    if sink_path.exists() || parent.exists() {
+        return Ok(());
+    }
+    ctx.report(vec![format!(
+        "sink {} will be created along with its missing parent {}; check the path for a typo",
+        sink_path.display(),
+        parent.display()
+    )])
+}
+
+/// `--progress`: one stderr line per copied skill folder.
+pub(super) fn print_copy_progress(id: &str, bytes: u64) {
+    eprintln!("copied {id} ({bytes} bytes)");
+}
+
+/// How often `install --watch` polls skill files.
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+pub(super) struct InstallCmdOptions {
+    pub(super) install: InstallOptions,
+    pub(super) watch: bool,
+    pub(super) link_manifest: bool,
+    pub(super) with_deps: bool,
+    pub(super) no_state: bool,
+}
+
+/// The sink `install_requirements` checks and installs required packs into.
+struct RequirementsTarget<'a> {
+    repo_root: &'a Path,
+    ctx: &'a ResolveContext<'a>,
+    agent: &'a str,
+    sink_path: &'a Path,
+    options: &'a InstallCmdOptions,
+    /// Recorded installs under `--no-state`, when `state` starts empty.
+    recorded: Option<&'a StateFile>,
+}
+
+/// Checks that every pack in `pack.requires` is recorded in `sink_path`. With
+/// `--with-deps`, missing ones are installed there first (their own requirements
+/// before them); `chain` holds the packs being installed, to catch cycles.
+fn install_requirements(
+    target: &RequirementsTarget,
+    pack: &Pack,
+    state: &mut StateFile,
+    chain: &mut Vec<String>,
+    views: &mut Vec<InstallView>,
+) -> Result<()> {
+    let RequirementsTarget {
+        repo_root,
+        ctx,
+        agent,
+        sink_path,
+        options,
+        recorded,
+    } = *target;
+    for required in &pack.requires {
+        let installed = |state: &StateFile| find_record_index(state, sink_path, required).is_some();
+        if installed(state) || recorded.is_some_and(installed) {
+            continue;
+        }
+        if !options.with_deps {
+            return Err(eyre!(
+                "pack {} requires {required}, which is not installed in {}",
+                pack.name,
+                sink_path.display()
+            )
+            .suggestion(format!(
+                "Run sp install {required} for this agent first, or pass --with-deps"
+            )));
+        }
+        if chain.contains(required) {
+            return Err(
+                eyre!("requires cycle: {} -> {required}", chain.join(" -> "))
+                    .suggestion("Remove one of the requires: entries"),
+            );
+        }
+        let (dep_path, dep_root) = ctx.locate(repo_root, required)?;
+        let dep = ctx.resolve(&dep_root, &dep_path)?;
+        chain.push(required.clone());
+        install_requirements(target, &dep.pack, state, chain, views)?;
+        chain.pop();
+        info!(pack = %dep.pack.name, required_by = %pack.name, "installing required pack");
+        views.push(ctx.timed("copy", || {
+            install_to_sink(&dep, &dep_path, agent, sink_path, state, &options.install)
+        })?);
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! `sp skills` and `sp packs`.
+
+use super::context::ResolveContext;
+use crate::bundled::enabled_bundled_root;
+use crate::discover::discover_local_skills;
+use crate::output::{Output, PackSummary};
+use crate::repo_config::load_repo_config;
+use crate::resolve::local_skill_ids;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+pub(super) fn list_skills(
+    repo_root: &Path,
+    include_bundled: bool,
+    ctx: &ResolveContext,
+    output: &Output,
+) -> Result<()> {
+    let roots = load_repo_config(repo_root)?.skills_roots();
+    if !roots.iter().any(|root| repo_root.join(root).exists()) {
+        return Err(eyre!("skills/ directory not found")
+            .suggestion("Create a skills/ directory or use --root to specify the repo root"));
+    }
+
+    let mut warnings = Vec::new();
+    // Id → source; repo skills shadow bundled ones, as repo packs do.
+    let mut sources: BTreeMap<String, &str> = discover_local_skills(repo_root, &mut warnings)?
+        .into_iter()
+        .map(|s| (s.id, "repo"))
+        .collect();
+
+    if include_bundled && let Some(bundled_root) = enabled_bundled_root(ctx.options.no_bundled)? {
+        for skill in discover_local_skills(&bundled_root, &mut warnings)? {
+            sources.entry(skill.id).or_insert("bundled");
+        }
+    }
+    ctx.report(warnings)?;
+
+    let ids: Vec<String> = sources.keys().cloned().collect();
+    output.print_skills(&ids, include_bundled.then_some(&sources))?;
+    Ok(())
+}
+
+pub(super) fn list_packs(
+    repo_root: &Path,
+    with_skills: bool,
+    ctx: &ResolveContext,
+    output: &Output,
+) -> Result<()> {
+    let mut packs = Vec::new();
+    let mut warnings = Vec::new();
+    if let Some(bundled_root) = enabled_bundled_root(ctx.options.no_bundled)? {
+        packs.extend(read_packs(&bundled_root, with_skills, ctx, &mut warnings)?);
+    }
+    packs.extend(read_packs(repo_root, with_skills, ctx, &mut warnings)?);
+    // Every pack rediscovers the same skills tree, so report each skip once.
+    let mut seen = HashSet::new();
+    warnings.retain(|message| seen.insert(message.clone()));
+    ctx.report(warnings)?;
+
+    let mut by_name = BTreeMap::new();
+    for pack in packs {
+        by_name.insert(pack.name.clone(), pack);
+    }
+    let mut packs: Vec<PackSummary> = by_name.into_values().collect();
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    output.print_packs(&packs)?;
+    Ok(())
+}
+
+fn read_packs(
+    repo_root: &Path,
+    with_skills: bool,
+    ctx: &ResolveContext,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<PackSummary>> {
+    let packs_dir = repo_root.join("packs");
+    if !packs_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut packs = Vec::new();
+    for entry in std::fs::read_dir(packs_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("yaml") {
+            continue;
+        }
+        let pack = ctx.load_pack(&path)?;
+        let display_path = path
+            .strip_prefix(repo_root)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let (skills, imports) = if with_skills {
+            (
+                Some(local_skill_ids(repo_root, &pack, warnings)?),
+                Some(pack.imports.len()),
+            )
+        } else {
+            (None, None)
+        };
+        packs.push(PackSummary {
+            name: pack.name,
+            path: display_path,
+            skills,
+            imports,
+        });
+    }
+    Ok(packs)
+}
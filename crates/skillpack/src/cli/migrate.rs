@@ -0,0 +1,126 @@
+//! `sp migrate-state`: rewrite recorded sink paths under a new prefix.
+
+use super::context::ResolveContext;
+use crate::index::sync_index;
+use crate::output::{MigrateItem, MigrateStateView, Output};
+use crate::state::{load_state, write_state};
+use crate::util::make_absolute;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Rewrites `sink_path`, `installed_paths`, and `pack_file` in every record that
+/// lies under `from_prefix` (compared by path components, so `/home/a` does not
+/// match `/home/ab`). With `move_files`, installed folders are renamed first.
+pub(super) fn migrate_state_cmd(
+    ctx: &ResolveContext,
+    from_prefix: &Path,
+    to_prefix: &Path,
+    move_files: bool,
+    dry_run: bool,
+    output: &Output,
+) -> Result<()> {
+    let from_prefix = make_absolute(from_prefix)?;
+    let to_prefix = make_absolute(to_prefix)?;
+    if from_prefix == to_prefix {
+        return Err(
+            eyre!("FROM and TO are the same path: {}", from_prefix.display())
+                .suggestion("Pass the old prefix first and the new one second"),
+        );
+    }
+    let rewrite = |path: &str| -> Option<String> {
+        Path::new(path)
+            .strip_prefix(&from_prefix)
+            .ok()
+            .map(|rest| to_prefix.join(rest).display().to_string())
+    };
+
+    let mut state = load_state()?;
+    let mut items = Vec::new();
+    let mut moves = Vec::new();
+    for record in &mut state.installs {
+        let sink_path = rewrite(&record.sink_path);
+        let paths: Vec<(String, String)> = record
+            .installed_paths
+            .iter()
+            .filter_map(|path| Some((path.clone(), rewrite(path)?)))
+            .collect();
+        let pack_file = rewrite(&record.pack_file);
+        if sink_path.is_none() && paths.is_empty() && pack_file.is_none() {
+            continue;
+        }
+        items.push(MigrateItem {
+            sink: record.sink.clone(),
+            pack: record.pack.clone(),
+            old_sink_path: record.sink_path.clone(),
+            sink_path: sink_path
+                .clone()
+                .unwrap_or_else(|| record.sink_path.clone()),
+            paths: paths.len(),
+        });
+        if let Some(sink_path) = sink_path {
+            record.sink_path = sink_path;
+        }
+        if let Some(pack_file) = pack_file {
+            record.pack_file = pack_file;
+        }
+        for path in &mut record.installed_paths {
+            if let Some(new_path) = rewrite(path) {
+                *path = new_path;
+            }
+        }
+        moves.extend(paths);
+    }
+
+    let mut keys = HashSet::new();
+    for record in &state.installs {
+        if !keys.insert((&record.sink_path, &record.pack)) {
+            return Err(eyre!(
+                "{} would be recorded twice in {}",
+                record.pack,
+                record.sink_path
+            )
+            .suggestion("Uninstall one of the copies before migrating"));
+        }
+    }
+
+    if move_files && !dry_run {
+        // Reported up front, so --strict fails before any folder moves.
+        ctx.report(
+            moves
+                .iter()
+                .filter(|(from, _)| !Path::new(from).exists())
+                .map(|(from, _)| format!("recorded path missing, not moved: {from}"))
+                .collect(),
+        )?;
+        for (from, to) in &moves {
+            let (from, to) = (Path::new(from), Path::new(to));
+            if !from.exists() {
+                continue;
+            }
+            if to.exists() {
+                return Err(eyre!("destination already exists: {}", to.display())
+                    .suggestion("Remove it or migrate without --move"));
+            }
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(from, to).wrap_err_with(|| {
+                format!("failed to move {} to {}", from.display(), to.display())
+            })?;
+        }
+    }
+    if !dry_run && !items.is_empty() {
+        write_state(&state)?;
+        sync_index(&state)?;
+    }
+    output.print_migrate_state(&MigrateStateView {
+        from_prefix: from_prefix.display().to_string(),
+        to_prefix: to_prefix.display().to_string(),
+        moved: move_files,
+        dry_run,
+        records: items,
+    })?;
+    Ok(())
+}
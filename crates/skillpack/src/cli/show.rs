@@ -0,0 +1,251 @@
+//! `sp show`, `sp explain` and `sp normalize`: inspect a pack without installing it.
+
+use super::context::ResolveContext;
+use crate::api::locate_pack;
+use crate::discover::read_skill_metadata;
+use crate::output::{
+    ExplainSource, ExplainView, ImportView, Output, PackDiffView, PackInfo, ShowExplainView,
+    ShowView,
+};
+use crate::pack::NormalizedPack;
+use crate::resolve::{ResolvedPack, SkillSource, explain_skill, find_collisions};
+use color_eyre::eyre::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+pub(super) struct ShowOptions {
+    pub(super) allow_collisions: bool,
+    pub(super) max_skills: Option<usize>,
+    pub(super) imports_only: bool,
+    pub(super) local_only: bool,
+    pub(super) count: bool,
+    pub(super) explain_excludes: bool,
+    pub(super) with_metadata: bool,
+    pub(super) diff_against: Option<String>,
+    pub(super) explain: bool,
+    pub(super) bare: bool,
+}
+
+pub(super) fn show_pack(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: &str,
+    options: &ShowOptions,
+    output: &Output,
+) -> Result<()> {
+    let (pack_path, pack_root) = ctx.locate(repo_root, pack_arg)?;
+    // --explain reports collisions per skill instead of failing on them.
+    let mut resolved = ctx.check(
+        ctx.resolve_unchecked(&pack_root, &pack_path)?,
+        !(options.allow_collisions || options.explain),
+    )?;
+    if let Some(other_arg) = &options.diff_against {
+        let (other_path, other_root) = ctx.locate(repo_root, other_arg)?;
+        let other = ctx.check(
+            ctx.resolve_unchecked(&other_root, &other_path)?,
+            !options.allow_collisions,
+        )?;
+        output.print_pack_diff(&pack_diff(&resolved, &other))?;
+        return Ok(());
+    }
+    if options.explain {
+        output.print_show_explain(&explain_all(&resolved)?)?;
+        return Ok(());
+    }
+    // The filters only narrow the view; resolution and collision checks saw everything.
+    if options.local_only {
+        resolved.imports.clear();
+    }
+    if options.imports_only {
+        resolved.local.clear();
+    }
+    if options.local_only || options.imports_only {
+        resolved
+            .final_skills
+            .retain(|skill| matches!(skill.source, SkillSource::Local) == options.local_only);
+        let kept = &resolved.final_skills;
+        resolved
+            .renamed
+            .retain(|(_, to)| kept.iter().any(|skill| &skill.id == to));
+    }
+    if options.count {
+        output.print_count(resolved.final_skills.len())?;
+        return Ok(());
+    }
+
+    let pack_info = PackInfo {
+        name: resolved.pack.name.clone(),
+        file: pack_path.display().to_string(),
+        prefix: resolved.pack.install_prefix.clone(),
+        sep: resolved.pack.install_sep.clone(),
+        flatten: resolved.pack.install_flatten,
+        prefix_mode: resolved.pack.install_prefix_mode,
+    };
+    let local = resolved
+        .local
+        .iter()
+        .map(|skill| skill.id.clone())
+        .collect();
+    let imports = resolved
+        .imports
+        .iter()
+        .map(|import| ImportView {
+            repo: import.repo.clone(),
+            reference: import.ref_name.clone(),
+            resolved_ref: import.display_ref.clone(),
+            commit: import.commit.clone(),
+            skills: import.skills.iter().map(|skill| skill.id.clone()).collect(),
+            ref_overridden: import.ref_overridden,
+        })
+        .collect();
+    let final_install_names = resolved
+        .final_skills
+        .iter()
+        .map(|skill| resolved.install_name(&skill.id))
+        .collect();
+    let collisions = find_collisions(&resolved.final_skills, &resolved.pack);
+    let mut metadata = BTreeMap::new();
+    if options.with_metadata {
+        let listed = resolved
+            .local
+            .iter()
+            .chain(resolved.imports.iter().flat_map(|import| &import.skills));
+        for skill in listed {
+            if metadata.contains_key(&skill.id) {
+                continue;
+            }
+            if let Some(found) = read_skill_metadata(&skill.dir)? {
+                metadata.insert(skill.id.clone(), found);
+            }
+        }
+    }
+    let view = ShowView {
+        pack: pack_info,
+        local,
+        imports,
+        final_install_names,
+        renames: resolved.renamed.clone(),
+        collisions,
+        excluded: if options.explain_excludes {
+            resolved.excluded.clone()
+        } else {
+            Vec::new()
+        },
+        metadata,
+        max_skills: options.max_skills,
+        bare: options.bare,
+    };
+    output.print_show(&view)?;
+    Ok(())
+}
+
+fn pack_diff(pack: &ResolvedPack, other: &ResolvedPack) -> PackDiffView {
+    let ids = |resolved: &ResolvedPack| -> BTreeSet<String> {
+        resolved
+            .final_skills
+            .iter()
+            .map(|skill| skill.id.clone())
+            .collect()
+    };
+    let (ours, theirs) = (ids(pack), ids(other));
+    PackDiffView {
+        pack: pack.pack.name.clone(),
+        other: other.pack.name.clone(),
+        only_in_pack: ours.difference(&theirs).cloned().collect(),
+        only_in_other: theirs.difference(&ours).cloned().collect(),
+        in_both: ours.intersection(&theirs).cloned().collect(),
+    }
+}
+
+/// Loads a pack without resolving it (imports are not fetched) and prints it back
+/// with defaults applied.
+pub(super) fn normalize_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: &str,
+    output: &Output,
+) -> Result<()> {
+    let (pack_path, _) = locate_pack(repo_root, pack_arg, &ctx.options)?;
+    let pack = ctx.load_pack(&pack_path)?;
+    ctx.report(
+        pack.warnings
+            .iter()
+            .map(|message| format!("{}: {message}", pack.name))
+            .collect(),
+    )?;
+    output.print_normalized(&NormalizedPack::from(&pack))?;
+    Ok(())
+}
+
+pub(super) fn explain_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: &str,
+    skill: &str,
+    output: &Output,
+) -> Result<()> {
+    let (pack_path, pack_root) = ctx.locate(repo_root, pack_arg)?;
+    let resolved = ctx.check(ctx.resolve_unchecked(&pack_root, &pack_path)?, false)?;
+    let collisions = find_collisions(&resolved.final_skills, &resolved.pack);
+    let view = explain_view(&resolved, skill, &collisions)?;
+    output.print_explain(&view)?;
+    Ok(())
+}
+
+/// `sp show --explain`: a decision record for every skill some include matched,
+/// whether it ended up installed, excluded, or colliding.
+fn explain_all(resolved: &ResolvedPack) -> Result<ShowExplainView> {
+    let mut ids: BTreeSet<&str> = BTreeSet::new();
+    ids.extend(resolved.local.iter().map(|skill| skill.id.as_str()));
+    for import in &resolved.imports {
+        ids.extend(import.skills.iter().map(|skill| skill.id.as_str()));
+    }
+    for (_, removed) in &resolved.excluded {
+        ids.extend(removed.iter().map(String::as_str));
+    }
+    let collisions = find_collisions(&resolved.final_skills, &resolved.pack);
+    Ok(ShowExplainView {
+        pack: resolved.pack.name.clone(),
+        skills: ids
+            .into_iter()
+            .map(|id| explain_view(resolved, id, &collisions))
+            .collect::<Result<_>>()?,
+    })
+}
+
+fn explain_view(
+    resolved: &ResolvedPack,
+    skill: &str,
+    collisions: &[(String, Vec<String>)],
+) -> Result<ExplainView> {
+    let explanation = explain_skill(resolved, skill)?;
+    let final_id = explanation.renamed_to.as_deref().unwrap_or(&explanation.id);
+    let collides_with = match explanation.install_name {
+        Some(_) => collisions
+            .iter()
+            .find(|(_, ids)| ids.iter().any(|id| id == final_id))
+            .map(|(_, ids)| ids.iter().filter(|id| *id != final_id).cloned().collect())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    Ok(ExplainView {
+        pack: resolved.pack.name.clone(),
+        skill: explanation.id,
+        included: explanation.install_name.is_some(),
+        sources: explanation
+            .sources
+            .into_iter()
+            .map(|source| ExplainSource {
+                source: source.source,
+                include: source.include,
+                exclude: source.exclude,
+                selected: source.selected,
+            })
+            .collect(),
+        exclude: explanation.exclude,
+        renamed_to: explanation.renamed_to,
+        install_name: explanation.install_name,
+        collides_with,
+        reason: explanation.reason,
+    })
+}
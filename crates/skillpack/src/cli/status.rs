@@ -0,0 +1,182 @@
+//! `sp installed`, `sp status` and `sp orphans`: report on recorded installs.
+
+use super::AgentTargets;
+use super::context::ResolveContext;
+use super::targets::{collect_agents, sink_filters, validate_agent_selection};
+use crate::config::{load_config, resolve_sink_path};
+use crate::git::resolve_repo;
+use crate::install::find_orphans;
+use crate::output::{
+    GroupBy, InstalledGroupsView, InstalledItem, InstalledView, OrphanItem, OrphansView, Output,
+    StatusSinkItem, StatusView,
+};
+use crate::state::{InstallRecord, load_state};
+use color_eyre::eyre::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub(super) fn installed_cmd(
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    group_by: Option<GroupBy>,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let state = load_state()?;
+
+    let sink_filters = sink_filters(&config, targets, path_override)?;
+    let mut installs: Vec<InstalledItem> = state
+        .installs
+        .into_iter()
+        .filter(|record| {
+            if let Some(ref filters) = sink_filters {
+                return filters.contains(&record.sink_path);
+            }
+            true
+        })
+        .map(|record| InstalledItem {
+            sink: record.sink,
+            pack: record.pack,
+            skill_count: record.installed_paths.len(),
+            installed_at: record.installed_at,
+            sink_path: record.sink_path,
+            frozen: record.frozen,
+        })
+        .collect();
+    installs.sort_by(|a, b| {
+        (a.sink.as_str(), a.pack.as_str()).cmp(&(b.sink.as_str(), b.pack.as_str()))
+    });
+    let Some(group_by) = group_by else {
+        output.print_installed(&InstalledView { installs })?;
+        return Ok(());
+    };
+    let mut groups: BTreeMap<String, Vec<InstalledItem>> = BTreeMap::new();
+    for item in installs {
+        let key = match group_by {
+            GroupBy::Sink => item.sink.clone(),
+            GroupBy::Pack => item.pack.clone(),
+        };
+        groups.entry(key).or_default().push(item);
+    }
+    output.print_installed_groups(&InstalledGroupsView {
+        group_by,
+        installs: groups,
+    })?;
+    Ok(())
+}
+
+pub(super) fn status_cmd(ctx: &ResolveContext, check_updates: bool, output: &Output) -> Result<()> {
+    let config = load_config()?;
+    let state = load_state()?;
+
+    // Keyed by sink path, like orphans: configured sinks first, then any recorded elsewhere.
+    let mut sinks: BTreeMap<String, (String, Vec<&InstallRecord>)> = BTreeMap::new();
+    for (name, path) in &config.sinks {
+        sinks.insert(path.display().to_string(), (name.clone(), Vec::new()));
+    }
+    for record in &state.installs {
+        sinks
+            .entry(record.sink_path.clone())
+            .or_insert_with(|| (record.sink.clone(), Vec::new()))
+            .1
+            .push(record);
+    }
+
+    let mut heads: BTreeMap<(String, Option<String>), String> = BTreeMap::new();
+    let mut items = Vec::new();
+    for (sink_path, (sink, records)) in sinks {
+        let missing = records
+            .iter()
+            .flat_map(|record| &record.installed_paths)
+            .filter(|path| !Path::new(path).exists())
+            .cloned()
+            .collect();
+        let stale = if check_updates {
+            let mut stale = Vec::new();
+            for record in &records {
+                let mut outdated = false;
+                for import in &record.imports {
+                    let key = (import.repo.clone(), import.ref_name.clone());
+                    let head = match heads.get(&key) {
+                        Some(head) => head.clone(),
+                        None => {
+                            let head = resolve_repo(
+                                &ctx.cache_dir,
+                                &import.repo,
+                                import.ref_name.as_deref(),
+                                ctx.options.git,
+                            )?
+                            .commit;
+                            heads.insert(key, head.clone());
+                            head
+                        }
+                    };
+                    outdated |= head != import.commit;
+                }
+                if outdated {
+                    stale.push(record.pack.clone());
+                }
+            }
+            stale.sort();
+            Some(stale)
+        } else {
+            None
+        };
+        items.push(StatusSinkItem {
+            sink,
+            sink_path,
+            packs: records.len(),
+            skills: records
+                .iter()
+                .map(|record| record.installed_paths.len())
+                .sum(),
+            missing,
+            stale,
+        });
+    }
+    items.sort_by(|a, b| (&a.sink, &a.sink_path).cmp(&(&b.sink, &b.sink_path)));
+    output.print_status(&StatusView { sinks: items })?;
+    Ok(())
+}
+
+pub(super) fn orphans_cmd(
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let state = load_state()?;
+    let agents = collect_agents(targets);
+    validate_agent_selection(&agents, path_override)?;
+
+    // Keyed by sink path so a sink shared by several names is scanned once.
+    let mut sinks = BTreeMap::new();
+    if agents.is_empty() {
+        for (name, path) in &config.sinks {
+            sinks.insert(path.display().to_string(), name.clone());
+        }
+        for record in &state.installs {
+            sinks
+                .entry(record.sink_path.clone())
+                .or_insert_with(|| record.sink.clone());
+        }
+    } else {
+        for agent in &agents {
+            let sink_path = resolve_sink_path(&config, agent, path_override)?;
+            sinks.insert(sink_path.display().to_string(), agent.clone());
+        }
+    }
+
+    let mut orphans = Vec::new();
+    for (sink_path, sink) in sinks {
+        for path in find_orphans(&state, Path::new(&sink_path))? {
+            orphans.push(OrphanItem {
+                sink: sink.clone(),
+                sink_path: sink_path.clone(),
+                path: path.display().to_string(),
+            });
+        }
+    }
+    output.print_orphans(&OrphansView { orphans })?;
+    Ok(())
+}
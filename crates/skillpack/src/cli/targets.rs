@@ -0,0 +1,105 @@
+//! Agent and sink selection shared by the install and state commands.
+
+use super::AgentTargets;
+use crate::config::{Config, resolve_sink_path};
+use crate::pack::Pack;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::collections::HashSet;
+use std::path::Path;
+
+pub(super) fn collect_agents(targets: &AgentTargets) -> Vec<String> {
+    let mut agents = Vec::new();
+    if targets.codex {
+        agents.push("codex".to_string());
+    }
+    if targets.claude {
+        agents.push("claude".to_string());
+    }
+    if targets.copilot {
+        agents.push("copilot".to_string());
+    }
+    if targets.cursor {
+        agents.push("cursor".to_string());
+    }
+    if targets.windsurf {
+        agents.push("windsurf".to_string());
+    }
+    if targets.custom {
+        agents.push("custom".to_string());
+    }
+    let mut seen = HashSet::new();
+    agents.retain(|agent| seen.insert(agent.clone()));
+    agents
+}
+
+pub(super) fn require_agents(targets: &AgentTargets) -> Result<Vec<String>> {
+    let agents = collect_agents(targets);
+    if agents.is_empty() {
+        return Err(eyre!("no agent targets specified")
+            .suggestion("Use --codex/--claude/--copilot/--cursor/--windsurf/--custom"));
+    }
+    Ok(agents)
+}
+
+/// Agent flags win; without any, falls back to the pack's `agents:` list.
+pub(super) fn install_agents(
+    config: &Config,
+    targets: &AgentTargets,
+    pack: &Pack,
+) -> Result<Vec<String>> {
+    if !collect_agents(targets).is_empty() || pack.agents.is_empty() {
+        return require_agents(targets);
+    }
+    for agent in &pack.agents {
+        if !config.sinks.contains_key(agent) {
+            let names: Vec<&str> = config.sinks.keys().map(String::as_str).collect();
+            return Err(
+                eyre!("pack {} lists unknown agent: {agent}", pack.name).suggestion(format!(
+                    "Available agents: {}. Fix agents: in the pack file",
+                    names.join(", ")
+                )),
+            );
+        }
+    }
+    let mut seen = HashSet::new();
+    Ok(pack
+        .agents
+        .iter()
+        .filter(|agent| seen.insert(agent.as_str()))
+        .cloned()
+        .collect())
+}
+
+pub(super) fn validate_agent_selection(
+    agents: &[String],
+    path_override: Option<&Path>,
+) -> Result<()> {
+    if agents.iter().any(|agent| agent == "custom") && agents.len() > 1 {
+        return Err(eyre!("custom agent cannot be combined with other targets")
+            .suggestion("Run separate installs per agent when using --custom"));
+    }
+    if path_override.is_some() && agents.len() > 1 {
+        return Err(eyre!("--path can only be used with a single agent target")
+            .suggestion("Run installs separately when overriding destinations"));
+    }
+    Ok(())
+}
+
+pub(super) fn sink_filters(
+    config: &Config,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+) -> Result<Option<HashSet<String>>> {
+    let agents = collect_agents(targets);
+    validate_agent_selection(&agents, path_override)?;
+    if agents.is_empty() {
+        return Ok(None);
+    }
+    let mut filters = HashSet::new();
+    for agent in &agents {
+        let sink_path = resolve_sink_path(config, agent, path_override)?;
+        filters.insert(sink_path.display().to_string());
+    }
+    Ok(Some(filters))
+}
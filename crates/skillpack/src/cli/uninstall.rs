@@ -0,0 +1,220 @@
+//! `sp uninstall`.
+
+use super::AgentTargets;
+use super::context::ResolveContext;
+use super::targets::{collect_agents, require_agents, validate_agent_selection};
+use crate::api::uninstall_from;
+use crate::config::{Config, is_default_sink_path, load_config, resolve_sink_path};
+use crate::index::sync_index;
+use crate::install::{forget_pack, plan_uninstall, prune_empty_dir};
+use crate::output::{Output, UninstallView};
+use crate::pack::resolve_pack_path;
+use crate::patterns::name_glob;
+use crate::state::{StateFile, find_record_index, load_state, write_state};
+use crate::util::{installed_before, make_absolute};
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub(super) struct UninstallOptions {
+    pub(super) if_installed: bool,
+    pub(super) prune_empty: bool,
+    pub(super) dry_run: bool,
+    pub(super) older_than: Option<Duration>,
+    pub(super) keep_files: bool,
+}
+
+/// Uninstalls `pack_arg` from each selected sink, or every recorded pack there when
+/// it is `None` (`--all`, or only packs older than `--older-than`).
+pub(super) fn uninstall_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: Option<&str>,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    options: &UninstallOptions,
+    output: &Output,
+) -> Result<()> {
+    let pack_name = match pack_arg {
+        Some(pack_arg) if Path::new(pack_arg).exists() || pack_arg.ends_with(".yaml") => {
+            let pack_path = make_absolute(&resolve_pack_path(
+                repo_root,
+                pack_arg,
+                ctx.options.no_bundled,
+            )?)?;
+            Some(ctx.load_pack(&pack_path)?.name)
+        }
+        Some(pack_arg) => Some(pack_arg.to_string()),
+        None => None,
+    };
+    // `team-*` (or `team-?`, `team-[ab]`) selects every recorded pack whose name matches.
+    let pattern = match &pack_name {
+        Some(name) => name_glob(name)?,
+        None => None,
+    };
+    let config = load_config()?;
+    let mut state = load_state()?;
+    let sinks = uninstall_sinks(
+        &config,
+        &state,
+        pack_name.as_deref().filter(|_| pattern.is_none()),
+        targets,
+        path_override,
+    )?;
+
+    let mut matched = false;
+    for (agent, sink_path) in sinks {
+        let sink_key = sink_path.display().to_string();
+        let packs = match (&pack_name, &pattern) {
+            (Some(_), Some(pattern)) => {
+                let mut packs: Vec<String> = state
+                    .installs
+                    .iter()
+                    .filter(|record| record.sink_path == sink_key && pattern.is_match(&record.pack))
+                    .map(|record| record.pack.clone())
+                    .collect();
+                packs.sort();
+                matched |= !packs.is_empty();
+                packs
+            }
+            (Some(pack_name), None) => vec![pack_name.clone()],
+            (None, _) => {
+                let mut packs: Vec<String> = state
+                    .installs
+                    .iter()
+                    .filter(|record| record.sink_path == sink_key)
+                    .filter(|record| {
+                        options
+                            .older_than
+                            .is_none_or(|age| installed_before(&record.installed_at, age))
+                    })
+                    .map(|record| record.pack.clone())
+                    .collect();
+                packs.sort();
+                packs
+            }
+        };
+        for pack_name in packs {
+            uninstall_one(&mut state, &agent, &sink_path, &pack_name, options, output)?;
+        }
+    }
+    if let Some(name) = &pack_name
+        && pattern.is_some()
+        && !matched
+        && !options.if_installed
+    {
+        return Err(
+            eyre!("no installed pack matches {name} in the selected sinks")
+                .suggestion("Run sp installed to list installed packs"),
+        );
+    }
+    Ok(())
+}
+
+fn uninstall_one(
+    state: &mut StateFile,
+    agent: &str,
+    sink_path: &Path,
+    pack_name: &str,
+    options: &UninstallOptions,
+    output: &Output,
+) -> Result<()> {
+    if options.if_installed && find_record_index(state, sink_path, pack_name).is_none() {
+        output.print_uninstall(&UninstallView {
+            pack: pack_name.to_string(),
+            sink: agent.to_string(),
+            sink_path: sink_path.display().to_string(),
+            removed: 0,
+            unmanaged: 0,
+            missing: 0,
+            paths: Vec::new(),
+            was_installed: false,
+            pruned_sink: false,
+            dry_run: options.dry_run,
+        })?;
+        return Ok(());
+    }
+    // Counted before removal, since afterwards every recorded path is gone.
+    let missing = find_record_index(state, sink_path, pack_name)
+        .map(|index| {
+            state.installs[index]
+                .installed_paths
+                .iter()
+                .filter(|path| !Path::new(path).exists())
+                .count()
+        })
+        .unwrap_or(0);
+    let (record, pruned_sink) = if options.dry_run {
+        (plan_uninstall(state, sink_path, pack_name)?.0, false)
+    } else {
+        let record = if options.keep_files {
+            forget_pack(state, sink_path, pack_name)?
+        } else {
+            uninstall_from(state, sink_path, pack_name)?
+        };
+        write_state(state)?;
+        sync_index(state)?;
+        // With --all this only succeeds after the sink's last pack is gone.
+        // Built-in agent folders stay, even empty, since the agent owns them.
+        let prune = options.prune_empty && !is_default_sink_path(sink_path)?;
+        (record, prune && prune_empty_dir(sink_path)?)
+    };
+
+    let view = UninstallView {
+        pack: pack_name.to_string(),
+        sink: agent.to_string(),
+        sink_path: sink_path.display().to_string(),
+        // Paths already gone count only as missing.
+        removed: if options.keep_files {
+            0
+        } else {
+            record.installed_paths.len() - missing
+        },
+        unmanaged: if options.keep_files {
+            record.installed_paths.len() - missing
+        } else {
+            0
+        },
+        missing,
+        paths: record.installed_paths,
+        was_installed: true,
+        pruned_sink,
+        dry_run: options.dry_run,
+    };
+    output.print_uninstall(&view)?;
+    Ok(())
+}
+
+/// Sinks to uninstall from. `--path` without an agent flag selects the sink by path,
+/// labelled with the agent recorded in state for that pack (any pack with `--all`).
+fn uninstall_sinks(
+    config: &Config,
+    state: &StateFile,
+    pack: Option<&str>,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+) -> Result<Vec<(String, PathBuf)>> {
+    if let Some(path) = path_override
+        && collect_agents(targets).is_empty()
+    {
+        let sink_path = make_absolute(path)?;
+        let sink_key = sink_path.display().to_string();
+        let agent = state
+            .installs
+            .iter()
+            .find(|record| record.sink_path == sink_key && pack.is_none_or(|p| record.pack == p))
+            .map(|record| record.sink.clone())
+            .unwrap_or_else(|| "custom".to_string());
+        return Ok(vec![(agent, sink_path)]);
+    }
+    let agents = require_agents(targets)?;
+    validate_agent_selection(&agents, path_override)?;
+    agents
+        .into_iter()
+        .map(|agent| {
+            let sink_path = resolve_sink_path(config, &agent, path_override)?;
+            Ok((agent, sink_path))
+        })
+        .collect()
+}
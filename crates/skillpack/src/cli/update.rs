@@ -0,0 +1,291 @@
+//! `sp switch`, `sp update` and `sp reinstall`: change packs that are already installed.
+
+use super::AgentTargets;
+use super::context::ResolveContext;
+use super::targets::{install_agents, require_agents, sink_filters, validate_agent_selection};
+use crate::api::InstallOptions;
+use crate::api::{install_into, recorded_pack_root, uninstall_from};
+use crate::config::{load_config, resolve_sink_path};
+use crate::index::sync_index;
+use crate::output::{
+    Output, ReinstallView, SwitchSinkView, SwitchView, UpdateItem, UpdateStatus, UpdateView,
+};
+use crate::pack::{STDIN_PACK_FILE, split_remote_pack};
+use crate::state::{InstallRecord, StateFile, find_record_index, load_state, write_state};
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info_span};
+
+pub(super) fn switch_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_args: &[String],
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let agents = require_agents(targets)?;
+    validate_agent_selection(&agents, path_override)?;
+
+    // Pre-resolve all packs to fail early if any pack is invalid
+    let mut resolved_packs = Vec::new();
+    for pack_arg in pack_args {
+        let (pack_path, pack_root) = ctx.locate(repo_root, pack_arg)?;
+        let resolved = ctx.resolve(&pack_root, &pack_path)?;
+        resolved_packs.push((pack_path, resolved));
+    }
+
+    let mut state = load_state()?;
+    let mut sink_views = Vec::new();
+
+    for agent in &agents {
+        let sink_path = resolve_sink_path(&config, agent, path_override)?;
+        let sink_path_str = sink_path.display().to_string();
+
+        // Find all packs currently installed to this sink
+        let installed_packs: Vec<String> = state
+            .installs
+            .iter()
+            .filter(|r| r.sink_path == sink_path_str)
+            .map(|r| r.pack.clone())
+            .collect();
+
+        // Uninstall all existing packs from this sink
+        let mut uninstalled = Vec::new();
+        for pack_name in &installed_packs {
+            uninstall_from(&mut state, &sink_path, pack_name)?;
+            uninstalled.push(pack_name.clone());
+        }
+
+        // Install new packs
+        let mut installed = Vec::new();
+        for (_pack_path, resolved) in &resolved_packs {
+            ctx.timed("copy", || {
+                install_into(
+                    &mut state,
+                    resolved,
+                    agent,
+                    &sink_path,
+                    &InstallOptions::default(),
+                )
+            })?;
+            installed.push(resolved.pack.name.clone());
+        }
+
+        ctx.timed("state write", || write_state(&state))?;
+        sync_index(&state)?;
+
+        sink_views.push(SwitchSinkView {
+            sink: agent.to_string(),
+            sink_path: sink_path_str,
+            uninstalled,
+            installed,
+        });
+    }
+
+    let view = SwitchView { sinks: sink_views };
+    output.print_switch(&view)?;
+    Ok(())
+}
+
+pub(super) fn update_cmd(
+    ctx: &ResolveContext,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    force: bool,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let sink_filters = sink_filters(&config, targets, path_override)?;
+    let mut state = load_state()?;
+    let records: Vec<InstallRecord> = state
+        .installs
+        .iter()
+        .filter(|record| {
+            sink_filters
+                .as_ref()
+                .is_none_or(|filters| filters.contains(&record.sink_path))
+        })
+        .cloned()
+        .collect();
+
+    let mut updates = Vec::new();
+    let mut failed = 0;
+    for record in records {
+        // A record that fails to update is reported with the rest, not allowed
+        // to stop the records after it.
+        let item = match update_record(ctx, &mut state, &record, force) {
+            Ok(item) => item,
+            Err(err) => {
+                failed += 1;
+                UpdateItem {
+                    sink: record.sink,
+                    pack: record.pack,
+                    status: UpdateStatus::Failed,
+                    reason: Some(format!("{err:#}")),
+                    skill_count: record.installed_paths.len(),
+                    changed: 0,
+                    sink_path: record.sink_path,
+                }
+            }
+        };
+        updates.push(item);
+    }
+    updates.sort_by(|a, b| {
+        (a.sink.as_str(), a.pack.as_str()).cmp(&(b.sink.as_str(), b.pack.as_str()))
+    });
+    let total = updates.len();
+    output.print_update(&UpdateView { updates })?;
+    if failed > 0 {
+        return Err(eyre!("{failed} of {total} install(s) failed to update")
+            .suggestion("See the failed entries above; the others were updated"));
+    }
+    Ok(())
+}
+
+/// Updates one recorded install and writes state; the record is left as it
+/// was when resolving or copying fails.
+fn update_record(
+    ctx: &ResolveContext,
+    state: &mut StateFile,
+    record: &InstallRecord,
+    force: bool,
+) -> Result<UpdateItem> {
+    let skip_reason = if record.frozen && !force {
+        Some("frozen")
+    } else if record.pack_file == STDIN_PACK_FILE {
+        Some("installed from stdin")
+    } else {
+        None
+    };
+    if let Some(reason) = skip_reason {
+        debug!(pack = %record.pack, sink = %record.sink, reason, "skip install");
+        return Ok(UpdateItem {
+            sink: record.sink.clone(),
+            pack: record.pack.clone(),
+            status: UpdateStatus::Skipped,
+            reason: Some(reason.to_string()),
+            skill_count: record.installed_paths.len(),
+            changed: 0,
+            sink_path: record.sink_path.clone(),
+        });
+    }
+    // Remote packs are fetched again, so a new commit of the pack file counts.
+    let (pack_path, pack_root) = match record.remote.as_deref().and_then(split_remote_pack) {
+        Some((repo, pack)) => ctx.fetch_remote_pack(repo, pack)?,
+        None => {
+            let pack_path = PathBuf::from(&record.pack_file);
+            let pack_root = recorded_pack_root(&pack_path, &ctx.options)?;
+            (pack_path, pack_root)
+        }
+    };
+    let resolved = ctx.resolve_recorded(&pack_root, &pack_path, record)?;
+    let options = InstallOptions {
+        freeze: record.frozen,
+        skip_unchanged: true,
+        ..InstallOptions::default()
+    };
+    let installed = ctx.timed("copy", || {
+        install_into(
+            state,
+            &resolved,
+            &record.sink,
+            Path::new(&record.sink_path),
+            &options,
+        )
+    })?;
+    ctx.timed("state write", || write_state(state))?;
+    // Added, recopied, or dropped folders; the rest kept their recorded hash.
+    let changed = installed
+        .installed_paths
+        .iter()
+        .filter(|path| record.hashes.get(*path) != installed.hashes.get(*path))
+        .count()
+        + record
+            .installed_paths
+            .iter()
+            .filter(|path| !installed.installed_paths.contains(path))
+            .count();
+    let advanced = record
+        .imports
+        .iter()
+        .map(|import| &import.commit)
+        .ne(installed.imports.iter().map(|import| &import.commit));
+    let (status, reason) = match (changed, advanced) {
+        (0, true) => (UpdateStatus::Unchanged, Some("commit advanced")),
+        (0, false) => (UpdateStatus::Unchanged, None),
+        _ => (UpdateStatus::Updated, None),
+    };
+    Ok(UpdateItem {
+        sink: installed.sink,
+        pack: installed.pack,
+        status,
+        reason: reason.map(str::to_string),
+        skill_count: installed.installed_paths.len(),
+        changed,
+        sink_path: installed.sink_path,
+    })
+}
+
+/// `sp reinstall`: per sink, copies the pack over its recorded install with
+/// preserved files discarded, reconciling folders it no longer selects, then
+/// writes state. Nothing is removed up front, so a failed copy leaves the
+/// previous install and its record in place. The pack is resolved first so a
+/// bad pack leaves every sink untouched.
+pub(super) fn reinstall_cmd(
+    repo_root: &Path,
+    ctx: &ResolveContext,
+    pack_arg: &str,
+    targets: &AgentTargets,
+    path_override: Option<&Path>,
+    output: &Output,
+) -> Result<()> {
+    let config = load_config()?;
+    let (pack_path, pack_root) = ctx.locate(repo_root, pack_arg)?;
+    let resolved = ctx.resolve(&pack_root, &pack_path)?;
+    let agents = install_agents(&config, targets, &resolved.pack)?;
+    validate_agent_selection(&agents, path_override)?;
+
+    let mut state = load_state()?;
+    let mut sinks = Vec::with_capacity(agents.len());
+    for agent in &agents {
+        let sink_path = resolve_sink_path(&config, agent, path_override)?;
+        let _span = info_span!("reinstall", pack = %resolved.pack.name, agent = %agent).entered();
+        // Keep how the pack was installed here: its namespace, skill deps and freeze.
+        let record = find_record_index(&state, &sink_path, &resolved.pack.name)
+            .map(|index| state.installs[index].clone());
+        let mut sink_pack = match &record {
+            // Resolved again (and its warnings reported again) only when this
+            // install followed `depends_on:` and the run does not.
+            Some(record) if record.with_skill_deps && !resolved.options.with_skill_deps => {
+                ctx.resolve_recorded(&pack_root, &pack_path, record)?
+            }
+            _ => resolved.clone(),
+        };
+        sink_pack.options.namespace = record.as_ref().and_then(|record| record.namespace.clone());
+        let options = InstallOptions {
+            freeze: record.is_some_and(|record| record.frozen),
+            discard_preserved: true,
+            ..InstallOptions::default()
+        };
+        ctx.timed("copy", || {
+            install_into(&mut state, &sink_pack, agent, &sink_path, &options)
+        })?;
+        ctx.timed("state write", || write_state(&state))?;
+        sinks.push(agent.clone());
+    }
+    sync_index(&state)?;
+
+    output.print_reinstall(&ReinstallView {
+        pack: resolved.pack.name.clone(),
+        skills: resolved
+            .final_skills
+            .iter()
+            .map(|skill| skill.id.clone())
+            .collect(),
+        sinks,
+    })?;
+    Ok(())
+}
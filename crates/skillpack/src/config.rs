@@ -1,13 +1,31 @@
+mod check;
+
+pub use check::{ConfigFinding, Severity, check_config};
+
+use crate::pack::PrefixMode;
 use crate::util::make_absolute;
 use color_eyre::Section as _;
-use color_eyre::eyre::{Result, eyre};
+use color_eyre::eyre::{Result, WrapErr, eyre};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use tracing::debug;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Default)]
 pub struct ConfigFile {
+    #[serde(default)]
     pub sinks: BTreeMap<String, String>,
+    #[serde(default)]
+    pub defaults: PackDefaults,
+}
+
+/// `defaults:` in `config.yaml`: install settings for packs that leave them out.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct PackDefaults {
+    /// Used when a pack has no `install.sep`.
+    pub sep: Option<String>,
+    /// Used when a pack sets neither `install.prefix` nor `install.prefix_mode`.
+    pub prefix_style: Option<PrefixMode>,
 }
 
 #[derive(Debug, Clone)]
@@ -20,9 +38,30 @@ pub struct ConfigDetail {
     pub path: PathBuf,
     pub defaults: BTreeMap<String, PathBuf>,
     pub overrides: BTreeMap<String, PathBuf>,
+    /// Override names that came from `SKILLPACK_SINK_<NAME>` rather than the config file.
+    pub env_overrides: BTreeSet<String>,
     pub effective: BTreeMap<String, PathBuf>,
 }
 
+impl ConfigDetail {
+    /// Where the effective path for `name` came from: `env`, `config`, or `default`.
+    pub fn source(&self, name: &str) -> &'static str {
+        if self.env_overrides.contains(name) {
+            "env"
+        } else if self.overrides.contains_key(name) {
+            "config"
+        } else {
+            "default"
+        }
+    }
+}
+
+const SINK_ENV_PREFIX: &str = "SKILLPACK_SINK_";
+
+/// Opt-in switch for XDG base directories; off by default so existing
+/// `~/.skillpack` setups keep working.
+pub const XDG_ENV: &str = "SKILLPACK_XDG";
+
 pub fn config_dir() -> Result<PathBuf> {
     config_dir_with(|key| std::env::var(key).ok(), dirs::home_dir)
 }
@@ -35,16 +74,55 @@ where
     if let Some(path) = get_var("SKILLPACK_HOME") {
         return Ok(PathBuf::from(path));
     }
+    if let Some(dir) = xdg_dir(&get_var, "XDG_CONFIG_HOME") {
+        return Ok(dir);
+    }
     let home = home_dir().ok_or_else(|| eyre!("missing home dir").suggestion("Set HOME"))?;
     Ok(home.join(".skillpack"))
 }
 
+/// Directory holding `state.json`: `$XDG_STATE_HOME/skillpack` when XDG is enabled
+/// and set, otherwise the config dir.
+pub fn state_dir() -> Result<PathBuf> {
+    state_dir_with(|key| std::env::var(key).ok(), dirs::home_dir)
+}
+
+fn state_dir_with<F, G>(get_var: F, home_dir: G) -> Result<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+    G: Fn() -> Option<PathBuf>,
+{
+    if get_var("SKILLPACK_HOME").is_none()
+        && let Some(dir) = xdg_dir(&get_var, "XDG_STATE_HOME")
+    {
+        return Ok(dir);
+    }
+    config_dir_with(get_var, home_dir)
+}
+
+fn xdg_dir<F>(get_var: &F, base_var: &str) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let enabled = get_var(XDG_ENV).is_some_and(|value| !value.is_empty() && value != "0");
+    let base = get_var(base_var).filter(|base| !base.is_empty())?;
+    // The XDG spec ignores relative base directories.
+    let base = PathBuf::from(base);
+    (enabled && base.is_absolute()).then(|| base.join("skillpack"))
+}
+
 pub fn config_path() -> Result<PathBuf> {
     Ok(config_dir()?.join("config.yaml"))
 }
 
 pub fn state_path() -> Result<PathBuf> {
-    Ok(config_dir()?.join("state.json"))
+    Ok(state_dir()?.join("state.json"))
+}
+
+/// Whether `path` is a built-in agent sink (`~/.codex/skills`, ...), a folder the
+/// agent itself expects to exist rather than one made for skillpack.
+pub fn is_default_sink_path(path: &Path) -> Result<bool> {
+    Ok(default_sinks()?.values().any(|default| default == path))
 }
 
 fn default_sinks() -> Result<BTreeMap<String, PathBuf>> {
@@ -75,12 +153,14 @@ pub fn load_config_detail() -> Result<ConfigDetail> {
     let path = config_path()?;
     let mut overrides = BTreeMap::new();
     if path.exists() {
-        let content = std::fs::read_to_string(&path)?;
-        let parsed: ConfigFile = serde_yaml::from_str(&content)?;
+        let parsed = read_config_file(&path)?;
         for (name, raw_path) in parsed.sinks {
             overrides.insert(name, expand_path(&raw_path)?);
         }
     }
+    let env = env_sink_overrides(std::env::vars())?;
+    let env_overrides = env.keys().cloned().collect();
+    overrides.extend(env);
     let mut effective = defaults.clone();
     for (name, path) in &overrides {
         effective.insert(name.clone(), path.clone());
@@ -89,10 +169,58 @@ pub fn load_config_detail() -> Result<ConfigDetail> {
         path,
         defaults,
         overrides,
+        env_overrides,
         effective,
     })
 }
 
+/// An empty or comment-only file (like the `--edit` template) is an empty config.
+fn parse_config_file(content: &str) -> Result<ConfigFile> {
+    Ok(serde_yaml::from_str::<Option<ConfigFile>>(content)?.unwrap_or_default())
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read config file: {}", path.display()))?;
+    parse_config_file(&content)
+        .wrap_err_with(|| format!("failed to parse config file: {}", path.display()))
+        .suggestion("Run sp config --check, or fix it with sp config --edit")
+}
+
+/// Pack defaults from `config.yaml`; none when the file does not exist. Read once
+/// per run and passed to [`crate::pack::load_pack`].
+pub fn load_pack_defaults() -> Result<PackDefaults> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(PackDefaults::default());
+    }
+    Ok(read_config_file(&path)?.defaults)
+}
+
+fn env_sink_overrides<I>(vars: I) -> Result<BTreeMap<String, PathBuf>>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    env_sink_values(vars)
+        .into_iter()
+        .map(|(name, value)| Ok((name, expand_path(&value)?)))
+        .collect()
+}
+
+/// `SKILLPACK_SINK_<NAME>` values as set, keyed by lowercased name; empty names
+/// and values are ignored.
+fn env_sink_values<I>(vars: I) -> BTreeMap<String, String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    vars.into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(SINK_ENV_PREFIX)?;
+            (!name.is_empty() && !value.is_empty()).then(|| (name.to_lowercase(), value))
+        })
+        .collect()
+}
+
 pub fn resolve_sink_path(
     config: &Config,
     sink: &str,
@@ -112,6 +240,89 @@ pub fn resolve_sink_path(
     })
 }
 
+/// Commented-out `config.yaml` listing the default sinks, written by `sp config --edit`.
+pub fn config_template() -> Result<String> {
+    let home = dirs::home_dir().ok_or_else(|| eyre!("missing home dir").suggestion("Set HOME"))?;
+    let mut out = String::from(
+        "# skillpack config: where each agent's skills are installed.\n\
+# Uncomment a sink to change its folder; sinks left out keep the default shown.\n\
+#\n\
+# sinks:\n",
+    );
+    for (name, path) in default_sinks()? {
+        let path = match path.strip_prefix(&home) {
+            Ok(rest) => format!("~/{}", rest.display()),
+            Err(_) => path.display().to_string(),
+        };
+        out.push_str(&format!("#   {name}: {path}\n"));
+    }
+    out.push_str("#\n# defaults:\n#   sep: \"__\"\n");
+    Ok(out)
+}
+
+/// `version` in the config dir: the skillpack release that last wrote state or config.
+pub fn version_path() -> Result<PathBuf> {
+    Ok(config_dir()?.join("version"))
+}
+
+/// Marks the running release as the last writer of state and config. A marker
+/// naming a newer release is kept, so an older binary never hides that the
+/// files may be in a newer format.
+pub fn write_version_marker() -> Result<()> {
+    ensure_config_dir()?;
+    let path = version_path()?;
+    let current = env!("CARGO_PKG_VERSION");
+    if let Ok(content) = std::fs::read_to_string(&path)
+        && release_key(content.trim()) > release_key(current)
+    {
+        debug!(marker = %content.trim(), "keep newer version marker");
+        return Ok(());
+    }
+    std::fs::write(&path, format!("{current}\n"))
+        .wrap_err_with(|| format!("failed to write {}", path.display()))
+}
+
+/// A warning when the version marker names a release this binary may not read
+/// correctly: a newer major version (or, before 1.0, a newer minor one).
+pub fn newer_version_warning() -> Result<Option<String>> {
+    let path = version_path()?;
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let written = content.trim();
+    let current = env!("CARGO_PKG_VERSION");
+    let newer = match (compat_key(written), compat_key(current)) {
+        (Some(written), Some(current)) => written > current,
+        _ => false,
+    };
+    Ok(newer.then(|| {
+        format!(
+            "state and config were last written by skillpack {written}, newer than this {current}; \
+they may use a format this version does not understand"
+        )
+    }))
+}
+
+/// `(major, minor)` as far as compatibility goes: the minor only counts before 1.0.
+fn compat_key(version: &str) -> Option<(u64, u64)> {
+    let (major, minor, _) = release_key(version)?;
+    Some(if major == 0 { (0, minor) } else { (major, 0) })
+}
+
+/// `(major, minor, patch)`, ignoring any pre-release or build suffix; missing
+/// parts count as 0.
+fn release_key(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.split('.').map(|part| {
+        part.split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|digits| digits.parse::<u64>().ok())
+    });
+    let major = parts.next()??;
+    let minor = parts.next().flatten().unwrap_or(0);
+    let patch = parts.next().flatten().unwrap_or(0);
+    Some((major, minor, patch))
+}
+
 pub fn ensure_config_dir() -> Result<()> {
     let dir = config_dir()?;
     std::fs::create_dir_all(&dir)?;
@@ -128,7 +339,7 @@ pub fn effective_sinks(config: &Config) -> BTreeMap<String, String> {
 
 #[cfg(test)]
 mod tests {
-    use super::config_dir_with;
+    use super::{compat_key, config_dir_with, env_sink_overrides, state_dir_with};
     use std::path::PathBuf;
 
     #[test]
@@ -146,4 +357,80 @@ mod tests {
         .unwrap();
         assert_eq!(dir.to_string_lossy(), "/tmp/skillpack-test");
     }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let pairs: Vec<(String, String)> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn xdg_dirs_used_when_enabled_and_set() {
+        let get_var = vars(&[
+            ("SKILLPACK_XDG", "1"),
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_STATE_HOME", "/xdg/state"),
+        ]);
+        let home = || Some(PathBuf::from("/home/demo"));
+        assert_eq!(
+            config_dir_with(&get_var, home).unwrap(),
+            PathBuf::from("/xdg/config/skillpack")
+        );
+        assert_eq!(
+            state_dir_with(&get_var, home).unwrap(),
+            PathBuf::from("/xdg/state/skillpack")
+        );
+    }
+
+    #[test]
+    fn xdg_unset_or_disabled_falls_back_to_home() {
+        let home = || Some(PathBuf::from("/home/demo"));
+        let unset = vars(&[("SKILLPACK_XDG", "1")]);
+        assert_eq!(
+            state_dir_with(&unset, home).unwrap(),
+            PathBuf::from("/home/demo/.skillpack")
+        );
+        let disabled = vars(&[
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_STATE_HOME", "/xdg/state"),
+        ]);
+        assert_eq!(
+            config_dir_with(&disabled, home).unwrap(),
+            PathBuf::from("/home/demo/.skillpack")
+        );
+        let pinned = vars(&[
+            ("SKILLPACK_HOME", "/pinned"),
+            ("SKILLPACK_XDG", "1"),
+            ("XDG_STATE_HOME", "/xdg/state"),
+        ]);
+        assert_eq!(
+            state_dir_with(&pinned, home).unwrap(),
+            PathBuf::from("/pinned")
+        );
+    }
+
+    #[test]
+    fn env_sink_overrides_lowercase_names() {
+        let overrides = env_sink_overrides(vec![
+            ("SKILLPACK_SINK_CODEX".to_string(), "/tmp/codex".to_string()),
+            ("SKILLPACK_SINK_".to_string(), "/tmp/ignored".to_string()),
+            ("SKILLPACK_HOME".to_string(), "/tmp/home".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides["codex"], PathBuf::from("/tmp/codex"));
+    }
+
+    #[test]
+    fn compat_key_counts_minor_only_before_one_point_oh() {
+        assert_eq!(compat_key("0.2.3"), Some((0, 2)));
+        assert_eq!(compat_key("0.3.0-beta.1"), Some((0, 3)));
+        assert_eq!(compat_key("1.4.2"), Some((1, 0)));
+        assert_eq!(compat_key("2"), Some((2, 0)));
+        assert_eq!(compat_key("garbage"), None);
+        assert!(compat_key("0.3.0") > compat_key("0.2.9"));
+        assert_eq!(compat_key("1.9.0"), compat_key("1.0.0"));
+    }
 }
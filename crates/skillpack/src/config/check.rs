@@ -0,0 +1,265 @@
+//! `sp config --check`: problems in the configured sinks and `defaults:`.
+
+use super::{
+    ConfigFile, PackDefaults, SINK_ENV_PREFIX, config_path, default_sinks, env_sink_values,
+    read_config_file,
+};
+use crate::pack::validate_install_component;
+use color_eyre::eyre::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A problem found by `sp config --check`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigFinding {
+    pub severity: Severity,
+    pub sink: String,
+    pub message: String,
+}
+
+/// Checks the effective sinks as written (before path expansion): those in the
+/// config file, with `SKILLPACK_SINK_<NAME>` values replacing them, then
+/// `defaults:`. With `fail_fast`, stops at the first error instead of reporting
+/// every problem.
+pub fn check_config(fail_fast: bool) -> Result<Vec<ConfigFinding>> {
+    let path = config_path()?;
+    let parsed = if path.exists() {
+        read_config_file(&path)?
+    } else {
+        ConfigFile::default()
+    };
+    let env = env_sink_values(std::env::vars());
+    let mut sinks = parsed.sinks;
+    sinks.extend(env.clone());
+    let known: BTreeSet<String> = default_sinks()?.into_keys().collect();
+    let mut findings = Findings {
+        items: Vec::new(),
+        fail_fast,
+    };
+    if collect_sink_findings(&sinks, &known, &mut findings).is_continue() {
+        let _ = collect_default_findings(&parsed.defaults, &mut findings);
+    }
+    let mut findings = findings.items;
+    for finding in &mut findings {
+        if env.contains_key(&finding.sink) {
+            let var = format!("{SINK_ENV_PREFIX}{}", finding.sink.to_uppercase());
+            finding.message.push_str(&format!(" (from {var})"));
+        }
+    }
+    Ok(findings)
+}
+
+/// Collected findings; with `fail_fast`, collection stops at the first error.
+struct Findings {
+    items: Vec<ConfigFinding>,
+    fail_fast: bool,
+}
+
+impl Findings {
+    fn push(&mut self, severity: Severity, sink: &str, message: String) -> ControlFlow<()> {
+        self.items.push(ConfigFinding {
+            severity,
+            sink: sink.to_string(),
+            message,
+        });
+        if self.fail_fast && severity == Severity::Error {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+fn collect_sink_findings(
+    sinks: &BTreeMap<String, String>,
+    known: &BTreeSet<String>,
+    findings: &mut Findings,
+) -> ControlFlow<()> {
+    let mut lowercase: BTreeMap<String, &str> = BTreeMap::new();
+    let mut paths: BTreeMap<PathBuf, &str> = BTreeMap::new();
+    for (name, raw_path) in sinks {
+        if let Some(other) = lowercase.insert(name.to_lowercase(), name) {
+            findings.push(
+                Severity::Error,
+                name,
+                format!("duplicate sink name (also defined as {other})"),
+            )?;
+        }
+        if !known.contains(name) {
+            findings.push(
+                Severity::Warning,
+                name,
+                format!(
+                    "unknown sink; no agent flag targets it (known: {})",
+                    known.iter().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            )?;
+        }
+        let path = PathBuf::from(shellexpand::tilde(raw_path).as_ref());
+        if !path.is_absolute() {
+            findings.push(
+                Severity::Error,
+                name,
+                format!("path is not absolute: {raw_path}"),
+            )?;
+            continue;
+        }
+        if path.parent().is_some_and(|parent| !parent.exists()) {
+            findings.push(
+                Severity::Warning,
+                name,
+                format!("parent directory does not exist: {}", path.display()),
+            )?;
+        }
+        if let Some(other) = paths.insert(path.clone(), name) {
+            findings.push(
+                Severity::Warning,
+                name,
+                format!("same path as sink {other}: {}", path.display()),
+            )?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Checks `defaults:` the way a pack's own `install.sep` is checked; reported
+/// under the name `defaults`.
+fn collect_default_findings(defaults: &PackDefaults, findings: &mut Findings) -> ControlFlow<()> {
+    if let Some(sep) = &defaults.sep {
+        if let Err(err) = validate_install_component("sep", sep) {
+            findings.push(Severity::Error, "defaults", err.to_string())?;
+        } else if sep.is_empty() {
+            findings.push(
+                Severity::Warning,
+                "defaults",
+                "sep is empty; packs without install.sep must set install.flatten: true"
+                    .to_string(),
+            )?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConfigFinding, Findings, PackDefaults, Severity, collect_default_findings,
+        collect_sink_findings,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn check_sinks(
+        sinks: &BTreeMap<String, String>,
+        known: &BTreeSet<String>,
+        fail_fast: bool,
+    ) -> Vec<ConfigFinding> {
+        let mut findings = Findings {
+            items: Vec::new(),
+            fail_fast,
+        };
+        let _ = collect_sink_findings(sinks, known, &mut findings);
+        findings.items
+    }
+
+    #[test]
+    fn check_sinks_reports_each_problem() {
+        let known: BTreeSet<String> = ["codex".to_string()].into();
+        let sinks: BTreeMap<String, String> = [
+            ("Codex", "/tmp/skillpack-check/a"),
+            ("codex", "relative/skills"),
+            ("extra", "/tmp/skillpack-check-missing/b"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let findings = check_sinks(&sinks, &known, false);
+        let summary: Vec<(Severity, &str)> = findings
+            .iter()
+            .map(|f| (f.severity, f.sink.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Warning, "Codex"),
+                (Severity::Warning, "Codex"),
+                (Severity::Error, "codex"),
+                (Severity::Error, "codex"),
+                (Severity::Warning, "extra"),
+                (Severity::Warning, "extra"),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_sinks_fail_fast_stops_at_first_error() {
+        let known: BTreeSet<String> = ["codex".to_string()].into();
+        let sinks: BTreeMap<String, String> = [
+            ("a-extra", "/tmp/a"),
+            ("b-relative", "relative/one"),
+            ("c-relative", "relative/two"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let all = check_sinks(&sinks, &known, false);
+        assert_eq!(
+            all.iter().filter(|f| f.severity == Severity::Error).count(),
+            2
+        );
+
+        let fast = check_sinks(&sinks, &known, true);
+        let summary: Vec<(Severity, &str)> =
+            fast.iter().map(|f| (f.severity, f.sink.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Warning, "a-extra"),
+                (Severity::Warning, "b-relative"),
+                (Severity::Error, "b-relative"),
+            ]
+        );
+    }
+
+    #[test]
+    fn default_findings_share_the_fail_fast_collector() {
+        let defaults = PackDefaults {
+            sep: Some("a/b".to_string()),
+            ..PackDefaults::default()
+        };
+        let mut findings = Findings {
+            items: Vec::new(),
+            fail_fast: true,
+        };
+        assert!(collect_default_findings(&defaults, &mut findings).is_break());
+        assert_eq!(findings.items.len(), 1);
+        assert_eq!(findings.items[0].severity, Severity::Error);
+        assert_eq!(findings.items[0].sink, "defaults");
+
+        let empty = PackDefaults {
+            sep: Some(String::new()),
+            ..PackDefaults::default()
+        };
+        assert!(collect_default_findings(&empty, &mut findings).is_continue());
+        assert_eq!(findings.items[1].severity, Severity::Warning);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::repo_config::{REPO_CONFIG_FILE, load_repo_config};
 use crate::util::path_to_id;
 use color_eyre::Section as _;
-use color_eyre::eyre::{Result, eyre};
-use std::collections::HashSet;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
@@ -11,25 +13,83 @@ pub struct Skill {
     pub dir: PathBuf,
 }
 
-pub fn discover_local_skills(repo_root: &Path) -> Result<Vec<Skill>> {
-    let skills_root = repo_root.join("skills");
-    if !skills_root.exists() {
-        return Err(
-            eyre!("skills directory not found: {}", skills_root.display()).suggestion(
-                "Auto-discovery checks current/parent dirs for skills/ or packs/. \
+/// `name`, `description` and `depends_on` from a `SKILL.md` YAML frontmatter block.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SkillMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Skill ids from the same source this skill needs; `--with-skill-deps` pulls them in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+/// Reads the frontmatter of `skill_dir/SKILL.md`: the YAML between a leading
+/// `---` line and the next one. `None` when the file has no frontmatter.
+pub fn read_skill_metadata(skill_dir: &Path) -> Result<Option<SkillMetadata>> {
+    let path = skill_dir.join("SKILL.md");
+    let content = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    let mut lines = content.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return Ok(None);
+    }
+    let mut yaml = String::new();
+    for line in lines {
+        if line.trim_end() == "---" {
+            let metadata = serde_yaml::from_str::<Option<SkillMetadata>>(&yaml)
+                .wrap_err_with(|| format!("invalid frontmatter in {}", path.display()))?;
+            return Ok(Some(metadata.unwrap_or_default()));
+        }
+        yaml.push_str(line);
+        yaml.push('\n');
+    }
+    Ok(None)
+}
+
+/// Skills under each local skills root (`skills/` unless `.skillpack.yaml` sets
+/// `skills_roots`). A `SKILL.md` that cannot be read (permissions, invalid UTF-8)
+/// skips its skill and adds a message to `warnings`.
+pub fn discover_local_skills(repo_root: &Path, warnings: &mut Vec<String>) -> Result<Vec<Skill>> {
+    let mut skills: Vec<Skill> = Vec::new();
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+    for root in load_repo_config(repo_root)?.skills_roots() {
+        let skills_root = repo_root.join(&root);
+        if !skills_root.exists() {
+            return Err(
+                eyre!("skills directory not found: {}", skills_root.display()).suggestion(
+                    "Auto-discovery checks current/parent dirs for skills/ or packs/. \
 Use --root <repo> to override",
-            ),
-        );
+                ),
+            );
+        }
+        for skill in discover_skills(&skills_root, true, warnings)? {
+            if let Some(other) = seen.get(&skill.id) {
+                return Err(eyre!(
+                    "skill id {} is defined in two skills roots: {} and {}",
+                    skill.id,
+                    other.display(),
+                    skill.dir.display()
+                )
+                .suggestion(format!(
+                    "Rename one of the folders or drop a root from skills_roots in {REPO_CONFIG_FILE}"
+                )));
+            }
+            seen.insert(skill.id.clone(), skill.dir.clone());
+            skills.push(skill);
+        }
     }
-    discover_skills(&skills_root, true)
+    Ok(skills)
 }
 
-pub fn discover_remote_skills(repo_root: &Path) -> Result<Vec<Skill>> {
-    discover_skills(repo_root, false)
+pub fn discover_remote_skills(repo_root: &Path, warnings: &mut Vec<String>) -> Result<Vec<Skill>> {
+    discover_skills(repo_root, false, warnings)
 }
 
-fn discover_skills(root: &Path, is_local: bool) -> Result<Vec<Skill>> {
+fn discover_skills(root: &Path, is_local: bool, warnings: &mut Vec<String>) -> Result<Vec<Skill>> {
     let mut skill_dirs: Vec<PathBuf> = Vec::new();
+    let mut empty = HashSet::new();
     for entry in WalkDir::new(root).follow_links(true) {
         let entry = entry?;
         if entry.file_name() != "SKILL.md" {
@@ -40,7 +100,16 @@ fn discover_skills(root: &Path, is_local: bool) -> Result<Vec<Skill>> {
         if !metadata.is_file() {
             continue;
         }
-        std::fs::read_to_string(entry.path())?;
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(content) => content,
+            Err(err) => {
+                warnings.push(format!(
+                    "skipped skill with unreadable SKILL.md: {}: {err}",
+                    entry.path().display()
+                ));
+                continue;
+            }
+        };
         let Some(parent) = entry.path().parent() else {
             continue;
         };
@@ -62,6 +131,9 @@ fn discover_skills(root: &Path, is_local: bool) -> Result<Vec<Skill>> {
         if rel.as_os_str().is_empty() {
             continue;
         }
+        if content.trim().is_empty() {
+            empty.insert(rel.to_path_buf());
+        }
         skill_dirs.push(rel.to_path_buf());
     }
 
@@ -81,6 +153,9 @@ fn discover_skills(root: &Path, is_local: bool) -> Result<Vec<Skill>> {
             continue;
         }
         let id = path_to_id(&rel);
+        if empty.contains(&rel) {
+            warnings.push(format!("skill {id} has an empty SKILL.md"));
+        }
         let dir = root.join(&rel);
         if !dir.is_dir() {
             return Err(eyre!("skill dir is not a directory: {}", dir.display())
@@ -97,7 +172,7 @@ fn dir_is_symlink(path: &Path) -> Result<bool> {
 
 #[cfg(test)]
 mod tests {
-    use super::discover_skills;
+    use super::{SkillMetadata, discover_local_skills, discover_skills, read_skill_metadata};
     use assert_fs::prelude::*;
 
     #[test]
@@ -108,7 +183,7 @@ mod tests {
         skills.child("a/SKILL.md").write_str("x").unwrap();
         skills.child("a/b/SKILL.md").write_str("y").unwrap();
 
-        let found = discover_skills(skills.path(), true).unwrap();
+        let found = discover_skills(skills.path(), true, &mut Vec::new()).unwrap();
         assert_eq!(found.len(), 1);
         assert_eq!(found[0].id, "a/b");
     }
@@ -120,10 +195,90 @@ mod tests {
         skills.create_dir_all().unwrap();
         skills.child("SKILL.md").write_str("x").unwrap();
 
-        let err = discover_skills(skills.path(), true).unwrap_err();
+        let err = discover_skills(skills.path(), true, &mut Vec::new()).unwrap_err();
         assert!(err.to_string().contains("skills/SKILL.md"));
     }
 
+    #[test]
+    fn local_skills_merge_configured_roots() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child(".skillpack.yaml")
+            .write_str("skills_roots:\n  - skills\n  - shared/skills\n")
+            .unwrap();
+        temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+        temp.child("shared/skills/tools/beta/SKILL.md")
+            .write_str("x")
+            .unwrap();
+
+        let found = discover_local_skills(temp.path(), &mut Vec::new()).unwrap();
+        let ids: Vec<&str> = found.iter().map(|skill| skill.id.as_str()).collect();
+        assert_eq!(ids, vec!["alpha", "tools/beta"]);
+
+        temp.child("shared/skills/alpha/SKILL.md")
+            .write_str("x")
+            .unwrap();
+        let err = discover_local_skills(temp.path(), &mut Vec::new()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("skill id alpha is defined in two skills roots"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unreadable_skill_md_is_skipped_with_warning() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let skills = temp.child("skills");
+        skills.child("good/SKILL.md").write_str("x").unwrap();
+        skills
+            .child("bad/SKILL.md")
+            .write_binary(&[0xff, 0xfe, 0x00, 0x80])
+            .unwrap();
+
+        let mut warnings = Vec::new();
+        let found = discover_skills(skills.path(), true, &mut warnings).unwrap();
+        let ids: Vec<&str> = found.iter().map(|skill| skill.id.as_str()).collect();
+        assert_eq!(ids, vec!["good"]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("bad"), "{warnings:?}");
+    }
+
+    #[test]
+    fn skill_metadata_reads_frontmatter() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("a/SKILL.md")
+            .write_str(
+                "---\nname: writing\ndescription: Edits prose\nlicense: MIT\n---\n# Writing\n",
+            )
+            .unwrap();
+        temp.child("b/SKILL.md")
+            .write_str("# No frontmatter\n")
+            .unwrap();
+
+        assert_eq!(
+            read_skill_metadata(temp.child("a").path()).unwrap(),
+            Some(SkillMetadata {
+                name: Some("writing".to_string()),
+                description: Some("Edits prose".to_string()),
+                depends_on: Vec::new(),
+            })
+        );
+        assert_eq!(read_skill_metadata(temp.child("b").path()).unwrap(), None);
+    }
+
+    #[test]
+    fn empty_skill_md_is_kept_with_warning() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let skills = temp.child("skills");
+        skills.child("blank/SKILL.md").write_str(" \n\t\n").unwrap();
+        skills.child("ok/SKILL.md").write_str("x").unwrap();
+
+        let mut warnings = Vec::new();
+        let found = discover_skills(skills.path(), true, &mut warnings).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(warnings, vec!["skill blank has an empty SKILL.md"]);
+    }
+
     #[cfg(unix)]
     #[test]
     fn skill_md_symlink_requires_symlinked_folder() {
@@ -144,7 +299,7 @@ mod tests {
         )
         .unwrap();
 
-        let err = discover_skills(skills.path(), true).unwrap_err();
+        let err = discover_skills(skills.path(), true, &mut Vec::new()).unwrap_err();
         assert!(err.to_string().contains("SKILL.md is a symlink"));
     }
 }
@@ -0,0 +1,105 @@
+//! `index.json`: every installed skill across sinks, keyed by install name.
+//!
+//! Written by `sp install --link-manifest` and kept in sync by later uninstalls.
+//! It is a denormalized view for agents and tools; `state.json` stays authoritative.
+
+use crate::config::state_dir;
+use crate::resolve::ResolvedPack;
+use crate::state::{StateFile, write_json_atomic};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub sink: String,
+    pub sink_path: String,
+    pub pack: String,
+    /// `local` or the import repo the skill came from.
+    pub source: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallIndex {
+    pub version: u32,
+    /// Install name → entries; one per sink the name is installed into.
+    pub skills: BTreeMap<String, Vec<IndexEntry>>,
+}
+
+impl Default for InstallIndex {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            skills: BTreeMap::new(),
+        }
+    }
+}
+
+impl InstallIndex {
+    /// Replaces the entries of `resolved` in `sink_path` with its current skills.
+    pub fn record_install(&mut self, resolved: &ResolvedPack, sink: &str, sink_path: &Path) {
+        let sink_path_str = sink_path.display().to_string();
+        self.retain(|entry| {
+            !(entry.sink_path == sink_path_str && entry.pack == resolved.pack.name)
+        });
+        for skill in &resolved.final_skills {
+            let name = resolved.install_name(&skill.id);
+            let entry = IndexEntry {
+                sink: sink.to_string(),
+                sink_path: sink_path_str.clone(),
+                pack: resolved.pack.name.clone(),
+                source: skill.source.to_string(),
+                path: sink_path.join(&name).display().to_string(),
+            };
+            self.skills.entry(name).or_default().push(entry);
+        }
+    }
+
+    /// Drops entries whose `(sink_path, pack)` no longer has an install record.
+    pub fn prune(&mut self, state: &StateFile) {
+        let installed: HashSet<(&str, &str)> = state
+            .installs
+            .iter()
+            .map(|record| (record.sink_path.as_str(), record.pack.as_str()))
+            .collect();
+        self.retain(|entry| installed.contains(&(entry.sink_path.as_str(), entry.pack.as_str())));
+    }
+
+    fn retain(&mut self, keep: impl Fn(&IndexEntry) -> bool) {
+        for entries in self.skills.values_mut() {
+            entries.retain(&keep);
+        }
+        self.skills.retain(|_, entries| !entries.is_empty());
+    }
+}
+
+pub fn index_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("index.json"))
+}
+
+/// Loads the index, or None if it has never been written.
+pub fn load_index() -> Result<Option<InstallIndex>> {
+    let path = index_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let index = serde_json::from_str(&content)
+        .wrap_err_with(|| format!("failed to parse install index: {}", path.display()))?;
+    Ok(Some(index))
+}
+
+pub fn write_index(index: &InstallIndex) -> Result<()> {
+    write_json_atomic(index, &index_path()?)
+}
+
+/// Prunes an existing index against `state`; does nothing when no index exists.
+pub fn sync_index(state: &StateFile) -> Result<()> {
+    if let Some(mut index) = load_index()? {
+        index.prune(state);
+        write_index(&index)?;
+    }
+    Ok(())
+}
@@ -1,19 +1,119 @@
-use crate::resolve::{ResolvedPack, ResolvedSkill};
+mod copy;
+
+pub use copy::hash_skill_dir;
+use copy::{clear_dir, copy_skill_dir};
+
+use crate::output::{InstallView, PackInfo};
+use crate::patterns::PatternSet;
+use crate::resolve::ResolvedPack;
 use crate::state::{ImportRecord, InstallRecord, StateFile, find_record_index, record_owned_path};
-use crate::util::{ensure_child_path, install_name, now_rfc3339};
+use crate::util::{ensure_child_path, long_path, now_rfc3339, validate_folder_name};
 use color_eyre::Section as _;
 use color_eyre::eyre::{Result, eyre};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
-use tracing::debug;
-use walkdir::WalkDir;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Pin the install so `sp update` leaves it alone.
+    pub freeze: bool,
+    /// Take over destination folders not owned by the pack.
+    pub force: bool,
+    /// Leave previously recorded folders that are no longer selected on disk.
+    pub no_reconcile: bool,
+    /// Called with each skill id and the bytes written, as its folder is copied.
+    pub progress: Option<fn(&str, u64)>,
+    /// Leave owned folders alone when the source hashes the same as last install.
+    pub skip_unchanged: bool,
+    /// Clear `install.preserve` files too, so owned folders end up exactly as
+    /// the source (`sp reinstall`).
+    pub discard_preserved: bool,
+}
+
+/// Installs into one sink and summarizes what changed relative to the previous record.
+pub fn install_to_sink(
+    resolved: &ResolvedPack,
+    pack_path: &Path,
+    sink: &str,
+    sink_path: &Path,
+    state: &mut StateFile,
+    options: &InstallOptions,
+) -> Result<InstallView> {
+    let old_paths = find_record_index(state, sink_path, &resolved.pack.name)
+        .map(|idx| state.installs[idx].installed_paths.clone())
+        .unwrap_or_default();
+    let record = install_pack(resolved, sink, sink_path, state, options)?;
+
+    let old_set: HashSet<&str> = old_paths.iter().map(String::as_str).collect();
+    let new_set: HashSet<&str> = record.installed_paths.iter().map(String::as_str).collect();
+    let added_names = folder_names(new_set.difference(&old_set));
+    // Without reconciling, dropped folders stay on disk, so nothing was removed.
+    let removed_names = if options.no_reconcile {
+        Vec::new()
+    } else {
+        folder_names(old_set.difference(&new_set))
+    };
+    let updated_names = folder_names(new_set.intersection(&old_set));
+    let (added, updated, removed) = (added_names.len(), updated_names.len(), removed_names.len());
+    debug!(sink, added, updated, removed, "install summary");
+    for path in &record.installed_paths {
+        debug!(sink, path = %path, "installed path");
+    }
+
+    Ok(InstallView {
+        pack: PackInfo {
+            name: resolved.pack.name.clone(),
+            file: pack_path.display().to_string(),
+            prefix: resolved.pack.install_prefix.clone(),
+            sep: resolved.pack.install_sep.clone(),
+            flatten: resolved.pack.install_flatten,
+            prefix_mode: resolved.pack.install_prefix_mode,
+        },
+        sink: sink.to_string(),
+        sink_path: sink_path.display().to_string(),
+        added,
+        updated,
+        removed,
+        added_names,
+        updated_names,
+        removed_names,
+        installed_paths: record.installed_paths,
+    })
+}
+
+fn folder_names<'a>(paths: impl Iterator<Item = &'a &'a str>) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .map(|path| {
+            Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
 
 pub fn install_pack(
     resolved: &ResolvedPack,
     sink: &str,
     sink_path: &Path,
     state: &mut StateFile,
+    options: &InstallOptions,
 ) -> Result<InstallRecord> {
+    let scoped;
+    let resolved = if resolved.agent_only.is_empty() {
+        resolved
+    } else {
+        scoped = resolved.for_agent(sink);
+        &scoped
+    };
+    // Fail before touching the sink, even creating it, if the platform cannot
+    // create a folder name.
+    for skill in &resolved.final_skills {
+        validate_folder_name(&resolved.install_name(&skill.id))?;
+    }
     std::fs::create_dir_all(sink_path)?;
     debug!(
         pack = %resolved.pack.name,
@@ -24,15 +124,23 @@ pub fn install_pack(
     let install_prefix = &resolved.pack.install_prefix;
     let install_sep = &resolved.pack.install_sep;
     let install_flatten = resolved.pack.install_flatten;
-    let new_paths = build_install_paths(
-        &resolved.final_skills,
-        sink_path,
-        install_prefix,
-        install_sep,
-        install_flatten,
-    );
+    // Skill folders go under `install.subdir` when set; `sink_path` stays the
+    // record key and the bound every removal is checked against.
+    let dest_root = match &resolved.pack.install_subdir {
+        Some(subdir) => sink_path.join(subdir),
+        None => sink_path.to_path_buf(),
+    };
+    ensure_child_path(sink_path, &dest_root)?;
+    let preserve = if options.discard_preserved {
+        PatternSet::new(&[])?
+    } else {
+        PatternSet::new(&resolved.pack.install_preserve)?
+    };
+    let new_paths = build_install_paths(resolved, &dest_root);
 
-    if let Some(index) = find_record_index(state, sink_path, &resolved.pack.name) {
+    if !options.no_reconcile
+        && let Some(index) = find_record_index(state, sink_path, &resolved.pack.name)
+    {
         let record = &state.installs[index];
         let new_set: HashSet<_> = new_paths.iter().cloned().collect();
         for old in &record.installed_paths {
@@ -41,37 +149,65 @@ pub fn install_pack(
                 ensure_child_path(sink_path, &path)?;
                 if path.exists() {
                     debug!(path = %path.display(), "remove stale");
-                    std::fs::remove_dir_all(&path)?;
+                    if clear_dir(&path, &preserve)? {
+                        prune_empty_parents(sink_path, &path)?;
+                    }
                 }
             }
         }
     }
 
+    let previous_hashes = find_record_index(state, sink_path, &resolved.pack.name)
+        .map(|index| state.installs[index].hashes.clone())
+        .unwrap_or_default();
+    let mut hashes = BTreeMap::new();
+    std::fs::create_dir_all(&dest_root)?;
     for skill in &resolved.final_skills {
-        let dest = sink_path.join(install_name(
-            install_prefix,
-            install_sep,
-            &skill.id,
-            install_flatten,
-        ));
+        let dest = dest_root.join(resolved.install_name(&skill.id));
+        let dest_key = dest.display().to_string();
+        // Hashing ahead of the copy reads every source file twice, so only runs
+        // when it can save the copy; otherwise the copy hashes what it reads.
+        let hash = options
+            .skip_unchanged
+            .then(|| hash_skill_dir(&skill.dir))
+            .transpose()?;
+        if let Some(hash) = hash
+            && dest.exists()
+            && previous_hashes.get(&dest_key) == Some(&hash)
+            && record_owned_path(state, sink_path, &resolved.pack.name, &dest)
+        {
+            debug!(path = %dest.display(), "unchanged, skip copy");
+            hashes.insert(dest_key, hash);
+            continue;
+        }
         if dest.exists() {
             if !record_owned_path(state, sink_path, &resolved.pack.name, &dest) {
-                return Err(eyre!(
-                    "destination exists but is not owned by pack: {}",
-                    dest.display()
-                )
-                .suggestion("Change install prefix/sep or uninstall the other pack"));
+                if !options.force {
+                    return Err(eyre!(
+                        "destination exists but is not owned by pack: {}",
+                        dest.display()
+                    )
+                    .suggestion(
+                        "Change install prefix/sep, uninstall the other pack, or use --force",
+                    ));
+                }
+                warn!(path = %dest.display(), "taking over unowned destination");
             }
             ensure_child_path(sink_path, &dest)?;
             debug!(path = %dest.display(), "remove existing");
-            std::fs::remove_dir_all(&dest)?;
+            clear_dir(&long_path(&dest), &preserve)?;
         }
         debug!(
             src = %skill.dir.display(),
             dest = %dest.display(),
             "copy skill"
         );
-        copy_skill_dir(&skill.dir, &dest)?;
+        let (bytes, hash) = copy_skill_dir(&skill.dir, &long_path(&dest), &preserve)?;
+        debug!(skill = %skill.id, bytes, "copied skill");
+        if let Some(progress) = options.progress {
+            progress(&skill.id, bytes);
+        }
+        hashes.insert(dest_key, hash);
     }
 
     let record = InstallRecord {
@@ -82,6 +218,7 @@ pub fn install_pack(
         prefix: install_prefix.clone(),
         sep: install_sep.clone(),
         flatten: install_flatten,
+        frozen: options.freeze,
         imports: resolved
             .imports
             .iter()
@@ -93,6 +230,10 @@ pub fn install_pack(
             .collect(),
         installed_paths: new_paths,
         installed_at: now_rfc3339()?,
+        hashes,
+        namespace: resolved.options.namespace.clone(),
+        with_skill_deps: resolved.options.with_skill_deps,
+        remote: resolved.options.remote_source.clone(),
     };
 
     if let Some(index) = find_record_index(state, sink_path, &resolved.pack.name) {
@@ -109,56 +250,130 @@ pub fn uninstall_pack(
     sink_path: &Path,
     pack: &str,
 ) -> Result<InstallRecord> {
+    let (record, remove) = plan_uninstall(state, sink_path, pack)?;
+    state
+        .installs
+        .retain(|r| !(r.sink_path == record.sink_path && r.pack == record.pack));
+    for dest in remove {
+        debug!(path = %dest.display(), "remove");
+        std::fs::remove_dir_all(&dest)?;
+        prune_empty_parents(sink_path, &dest)?;
+    }
+    Ok(record)
+}
+
+/// Drops the record for `pack` in `sink_path` but leaves its folders on disk
+/// (`sp uninstall --keep-files`).
+pub fn forget_pack(state: &mut StateFile, sink_path: &Path, pack: &str) -> Result<InstallRecord> {
     let index = find_record_index(state, sink_path, pack).ok_or_else(|| {
         eyre!("pack not installed").suggestion("Run sp installed to list installed packs")
     })?;
-    let record = state.installs.remove(index);
+    Ok(state.installs.remove(index))
+}
+
+/// Computes what `uninstall_pack` would do without touching the filesystem or state:
+/// the record to drop and the recorded folders that still exist.
+pub fn plan_uninstall(
+    state: &StateFile,
+    sink_path: &Path,
+    pack: &str,
+) -> Result<(InstallRecord, Vec<PathBuf>)> {
+    let index = find_record_index(state, sink_path, pack).ok_or_else(|| {
+        eyre!("pack not installed").suggestion("Run sp installed to list installed packs")
+    })?;
+    let record = state.installs[index].clone();
+    let mut remove = Vec::new();
     for path in &record.installed_paths {
         let dest = PathBuf::from(path);
         ensure_child_path(sink_path, &dest)?;
         if dest.exists() {
-            debug!(path = %dest.display(), "remove");
-            std::fs::remove_dir_all(dest)?;
+            remove.push(dest);
         }
     }
-    Ok(record)
+    Ok((record, remove))
 }
 
-fn build_install_paths(
-    skills: &[ResolvedSkill],
-    sink_path: &Path,
-    prefix: &str,
-    sep: &str,
-    flatten: bool,
-) -> Vec<String> {
-    let mut out: Vec<String> = skills
-        .iter()
-        .map(|skill| sink_path.join(install_name(prefix, sep, &skill.id, flatten)))
-        .map(|path| path.display().to_string())
-        .collect();
-    out.sort();
-    out
+/// Removes `dir` if it exists and is empty; returns whether it was removed.
+pub fn prune_empty_dir(dir: &Path) -> Result<bool> {
+    if !dir.is_dir() || std::fs::read_dir(dir)?.next().is_some() {
+        return Ok(false);
+    }
+    debug!(path = %dir.display(), "remove empty dir");
+    std::fs::remove_dir(dir)?;
+    Ok(true)
+}
+
+/// Removes the folders between `sink_path` and a removed `dest` (an
+/// `install.subdir`) that are now empty, innermost first. The sink itself stays.
+fn prune_empty_parents(sink_path: &Path, dest: &Path) -> Result<()> {
+    for dir in dest.ancestors().skip(1) {
+        if dir == sink_path || !dir.starts_with(sink_path) || !prune_empty_dir(dir)? {
+            break;
+        }
+    }
+    Ok(())
 }
 
-fn copy_skill_dir(src: &Path, dest: &Path) -> Result<()> {
-    std::fs::create_dir_all(dest)?;
-    for entry in WalkDir::new(src).follow_links(true) {
-        let entry = entry?;
-        if entry.depth() == 0 {
+/// Lists sink folders that no install record owns. Hidden folders are skipped, as
+/// are folders holding owned paths (an `install.subdir`).
+pub fn find_orphans(state: &StateFile, sink_path: &Path) -> Result<Vec<PathBuf>> {
+    if !sink_path.is_dir() {
+        return Ok(Vec::new());
+    }
+    let owned: HashSet<&str> = state
+        .installs
+        .iter()
+        .flat_map(|record| record.installed_paths.iter().map(String::as_str))
+        .collect();
+    let mut orphans = Vec::new();
+    for entry in std::fs::read_dir(sink_path)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden || !path.is_dir() {
             continue;
         }
-        let rel = entry.path().strip_prefix(src)?;
-        let dest_path = dest.join(rel);
-        if entry.file_type().is_dir() {
-            std::fs::create_dir_all(&dest_path)?;
-        } else if entry.file_type().is_file() {
-            if let Some(parent) = dest_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            std::fs::copy(entry.path(), &dest_path)?;
+        if !owned
+            .iter()
+            .any(|owned| Path::new(owned).starts_with(&path))
+        {
+            orphans.push(path);
         }
     }
-    Ok(())
+    orphans.sort();
+    Ok(orphans)
+}
+
+/// Names of everything already in a sink that has no install records, such as a
+/// `.git` folder or hand-written files. Empty once skillpack has installed there.
+pub fn foreign_entries(state: &StateFile, sink_path: &Path) -> Result<Vec<String>> {
+    let sink_key = sink_path.display().to_string();
+    if !sink_path.is_dir()
+        || state
+            .installs
+            .iter()
+            .any(|record| record.sink_path == sink_key)
+    {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(sink_path)? {
+        names.push(entry?.file_name().to_string_lossy().to_string());
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn build_install_paths(resolved: &ResolvedPack, dest_root: &Path) -> Vec<String> {
+    let mut out: Vec<String> = resolved
+        .final_skills
+        .iter()
+        .map(|skill| dest_root.join(resolved.install_name(&skill.id)))
+        .map(|path| path.display().to_string())
+        .collect();
+    out.sort();
+    out
 }
 
 #[cfg(test)]
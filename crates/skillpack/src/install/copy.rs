@@ -0,0 +1,136 @@
+//! Copying skill folders into sinks and hashing their contents.
+
+use crate::patterns::PatternSet;
+use crate::util::path_to_id;
+use color_eyre::eyre::Result;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Content hash of a skill folder: every file's relative path and bytes, in path order.
+pub fn hash_skill_dir(dir: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    for entry in WalkDir::new(dir).follow_links(true).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = path_to_id(entry.path().strip_prefix(dir)?);
+        hash_file(&mut hasher, &rel, &std::fs::read(entry.path())?)?;
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+fn hash_file(hasher: &mut blake3::Hasher, rel: &str, content: &[u8]) -> Result<()> {
+    hasher.update(rel.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&u64::try_from(content.len())?.to_le_bytes());
+    hasher.update(content);
+    Ok(())
+}
+
+/// Deletes `dir`, except for paths under it matching `preserve`; returns whether
+/// `dir` itself was removed.
+pub(super) fn clear_dir(dir: &Path, preserve: &PatternSet) -> Result<bool> {
+    if !kept_paths(dir, preserve) {
+        std::fs::remove_dir_all(dir)?;
+        return Ok(true);
+    }
+    for entry in WalkDir::new(dir).contents_first(true) {
+        let entry = entry?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        let rel = path_to_id(entry.path().strip_prefix(dir)?);
+        if preserve.is_match(&rel) {
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            // Still holds preserved files.
+            if std::fs::read_dir(entry.path())?.next().is_none() {
+                std::fs::remove_dir(entry.path())?;
+            }
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(false)
+}
+
+/// Whether anything under `dir` matches `preserve`.
+fn kept_paths(dir: &Path, preserve: &PatternSet) -> bool {
+    if !dir.is_dir() {
+        return false;
+    }
+    WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| {
+            entry
+                .path()
+                .strip_prefix(dir)
+                .is_ok_and(|rel| preserve.is_match(&path_to_id(rel)))
+        })
+}
+
+/// Copies `src` into `dest` and returns the number of bytes written with the
+/// [`hash_skill_dir`] hash of `src`, computed from the bytes read for the copy;
+/// files already in `dest` matching `preserve` are kept rather than overwritten.
+pub(super) fn copy_skill_dir(
+    src: &Path,
+    dest: &Path,
+    preserve: &PatternSet,
+) -> Result<(u64, String)> {
+    std::fs::create_dir_all(dest)?;
+    let mut bytes = 0;
+    let mut hasher = blake3::Hasher::new();
+    for entry in WalkDir::new(src).follow_links(true).sort_by_file_name() {
+        let entry = entry?;
+        if entry.depth() == 0 {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(src)?;
+        let dest_path = dest.join(rel);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest_path)?;
+        } else if entry.file_type().is_file() {
+            let content = std::fs::read(entry.path())?;
+            hash_file(&mut hasher, &path_to_id(rel), &content)?;
+            if dest_path.exists() && preserve.is_match(&path_to_id(rel)) {
+                continue;
+            }
+            if let Some(parent) = dest_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&dest_path, &content)?;
+            // Keep the executable bit on skill scripts, as fs::copy would.
+            std::fs::set_permissions(&dest_path, entry.metadata()?.permissions())?;
+            bytes += u64::try_from(content.len())?;
+        }
+    }
+    Ok((bytes, hasher.finalize().to_hex().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_skill_dir, hash_skill_dir};
+    use crate::patterns::PatternSet;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn copy_hashes_the_source_like_hash_skill_dir() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let src = temp.child("src");
+        src.child("SKILL.md").write_str("# skill").unwrap();
+        src.child("notes/local.md").write_str("source").unwrap();
+        let dest = temp.child("dest");
+        dest.child("notes/local.md").write_str("edited").unwrap();
+
+        // A preserved file is left alone but still counts toward the source hash.
+        let preserve = PatternSet::new(&["notes/**".to_string()]).unwrap();
+        let (bytes, hash) = copy_skill_dir(src.path(), dest.path(), &preserve).unwrap();
+        assert_eq!(bytes, 7);
+        assert_eq!(hash, hash_skill_dir(src.path()).unwrap());
+        dest.child("notes/local.md").assert("edited");
+    }
+}
@@ -1,12 +1,18 @@
+pub mod api;
+mod archive;
 pub mod bundled;
 pub mod cli;
 pub mod config;
 pub mod discover;
 pub mod git;
+mod index;
 pub mod install;
 pub mod output;
 pub mod pack;
 pub mod patterns;
+pub mod repo_config;
 pub mod resolve;
+pub mod sources;
 pub mod state;
 pub mod util;
+mod watch;
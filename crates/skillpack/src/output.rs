@@ -1,10 +1,18 @@
 mod helpers;
+mod pager;
 mod printer;
 mod styles;
 mod types;
+mod warnings;
 
+pub use pager::DEFAULT_PAGER;
 pub use printer::Output;
 pub use types::{
-    ConfigView, ImportView, InstallView, InstalledItem, InstalledView, OutputFormat, PackInfo,
-    PackSummary, ShowView, SinkView, SwitchSinkView, SwitchView, UninstallView,
+    AgentsView, CacheListView, CachePathView, CacheRepoItem, CacheSizeView, ColorChoice,
+    ConfigCheckView, ConfigView, DoctorFinding, DoctorView, ExplainSource, ExplainView, GroupBy,
+    ImportView, InstallView, InstalledGroupsView, InstalledItem, InstalledView, MigrateItem,
+    MigrateStateView, OrphanItem, OrphansView, OutputFormat, PackDiffView, PackInfo, PackSummary,
+    PhaseTiming, ReinstallView, ShowExplainView, ShowView, SinkView, StatusSinkItem, StatusView,
+    SwitchSinkView, SwitchView, TimingsView, UninstallView, UpdateItem, UpdateStatus, UpdateView,
 };
+pub use warnings::WarningSink;
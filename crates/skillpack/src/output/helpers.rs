@@ -20,9 +20,24 @@ pub(crate) fn short_hash(hash: &str) -> String {
     hash[..end].to_string()
 }
 
+/// Human-readable byte count using binary units (e.g. `1.5 MiB`).
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 #[cfg(test)]
 mod tests {
-    use super::abbreviate_path;
+    use super::{abbreviate_path, format_bytes};
     use std::path::MAIN_SEPARATOR;
 
     #[test]
@@ -35,6 +50,13 @@ mod tests {
         assert_eq!(abbreviate_path(&sibling), sibling);
     }
 
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
     #[test]
     fn abbreviate_path_abbreviates_home_and_child() {
         let Some(home) = dirs::home_dir() else {
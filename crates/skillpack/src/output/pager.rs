@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Used when `--pager` is given and `$PAGER` is unset; `-R` keeps ANSI colors.
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Pipes pretty output through a pager. The pager starts with the first write, so
+/// commands that fail before printing anything never open it.
+#[derive(Debug)]
+pub struct Pager {
+    command: String,
+    child: Mutex<Option<Child>>,
+}
+
+impl Pager {
+    pub fn new(command: String) -> Self {
+        Self {
+            command,
+            child: Mutex::new(None),
+        }
+    }
+
+    pub fn write(&self, text: &str) -> io::Result<()> {
+        let mut child = self.child.lock().unwrap_or_else(|err| err.into_inner());
+        if child.is_none() {
+            *child = Some(self.spawn()?);
+        }
+        let Some(stdin) = child.as_mut().and_then(|child| child.stdin.as_mut()) else {
+            return Ok(());
+        };
+        match stdin.write_all(text.as_bytes()) {
+            // The pager was closed before reading everything (e.g. `q` in less).
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Closes the pager's input and waits for it to exit.
+    pub fn finish(&self) -> io::Result<()> {
+        let child = self
+            .child
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(mut child) = child {
+            drop(child.stdin.take());
+            child.wait()?;
+        }
+        Ok(())
+    }
+
+    fn spawn(&self) -> io::Result<Child> {
+        let mut parts = self.command.split_whitespace();
+        let program = parts.next().unwrap_or("less");
+        debug!(pager = %self.command, "spawn pager");
+        let mut command = Command::new(program);
+        command.args(parts).stdin(Stdio::piped());
+        // Same defaults as git: quit if one screen, keep colors, no screen clear.
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        command.spawn().map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("failed to start pager {}: {err}", self.command),
+            )
+        })
+    }
+}
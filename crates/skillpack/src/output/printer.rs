@@ -1,517 +1,200 @@
-use super::helpers::{abbreviate_path, short_hash};
+mod config;
+mod install;
+mod listing;
+mod show;
+mod state;
+
+use super::pager::Pager;
 use super::styles::Styles;
-use super::types::{
-    ConfigView, InstallView, InstalledView, OutputFormat, PackSummary, ShowView, SwitchView,
-    UninstallView,
-};
+use super::types::{ColorChoice, OutputFormat};
+use super::warnings::WarningSink;
 use owo_colors::OwoColorize;
 use serde::Serialize;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+
+/// The text formats an `Output::emit` renderer is asked for.
+#[derive(Clone, Copy)]
+enum TextFormat {
+    Plain,
+    Pretty,
+}
 
 pub struct Output {
     format: OutputFormat,
     styles: Styles,
+    print0: bool,
+    verbose: bool,
+    warnings: WarningSink,
+    select: Option<String>,
+    pager: Option<Pager>,
 }
 
 impl Output {
-    pub fn new(format: OutputFormat, no_color: bool) -> Self {
+    pub fn new(format: OutputFormat, color: ColorChoice) -> Self {
         Self {
             format,
-            styles: Styles::new(no_color),
+            styles: Styles::new(color),
+            print0: false,
+            verbose: false,
+            warnings: WarningSink::new(format == OutputFormat::Plain),
+            select: None,
+            pager: None,
         }
     }
 
-    pub fn print_skills(&self, skills: &[String]) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(&serde_json::json!({
-                "count": skills.len(),
-                "skills": skills,
-            })),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                for id in skills {
-                    out.push_str(id);
-                    out.push('\n');
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-                out.push_str(&format!("{}\n\n", "Skills".style(self.styles.header())));
-                if skills.is_empty() {
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "No skills found".style(self.styles.path())
-                    ));
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "Create skills/ directory with SKILL.md files to get started"
-                            .style(self.styles.path())
-                    ));
-                } else {
-                    for skill in skills {
-                        out.push_str(&format!("  {}\n", skill.style(self.styles.name())));
-                    }
-                }
-                out.push('\n');
-                self.write_stdout(&out)
-            }
-        }
+    /// Page pretty output through `command` (`--pager`). Ignored for plain and
+    /// JSON output and when stdout is not a terminal.
+    pub fn with_pager(mut self, command: Option<String>) -> Self {
+        self.pager = command
+            .filter(|_| self.format == OutputFormat::Pretty && io::stdout().is_terminal())
+            .map(Pager::new);
+        self
     }
 
-    pub fn print_packs(&self, packs: &[PackSummary]) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(&serde_json::json!({
-                "count": packs.len(),
-                "packs": packs,
-            })),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                for pack in packs {
-                    out.push_str(&pack.name);
-                    out.push('\n');
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-                out.push_str(&format!("{}\n\n", "Packs".style(self.styles.header())));
-                if packs.is_empty() {
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "No packs found".style(self.styles.path())
-                    ));
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "Create packs/*.yaml files to define skill collections"
-                            .style(self.styles.path())
-                    ));
-                } else {
-                    for pack in packs {
-                        out.push_str(&format!(
-                            "  {}  {}\n",
-                            pack.name.style(self.styles.name()),
-                            abbreviate_path(&pack.path).style(self.styles.path())
-                        ));
-                    }
-                }
-                out.push('\n');
-                self.write_stdout(&out)
-            }
+    /// Waits for the pager, if one was started, so the prompt returns after it,
+    /// then writes any warnings no document carried (the command failed, or
+    /// warned after its last print) to stderr.
+    pub fn finish(&self) -> io::Result<()> {
+        if let Some(pager) = &self.pager {
+            pager.finish()?;
         }
-    }
-
-    pub fn print_show(&self, view: &ShowView) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                out.push_str("local\n");
-                for id in &view.local {
-                    out.push_str(id);
-                    out.push('\n');
-                }
-                for import in &view.imports {
-                    out.push_str("import ");
-                    out.push_str(&import.repo);
-                    out.push('\n');
-                    for id in &import.skills {
-                        out.push_str(id);
-                        out.push('\n');
-                    }
-                }
-                out.push_str("final\n");
-                for name in &view.final_install_names {
-                    out.push_str(name);
-                    out.push('\n');
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-
-                // Pack header
-                out.push_str(&format!(
-                    "{}\n\n",
-                    view.pack.name.style(self.styles.header())
-                ));
-
-                // Pack info
-                out.push_str(&format!(
-                    "  {} {}\n",
-                    "source".style(self.styles.label()),
-                    abbreviate_path(&view.pack.file).style(self.styles.path())
-                ));
-                let flatten = if view.pack.flatten {
-                    format!(" flatten={}", "leaf".style(self.styles.name()))
-                } else {
-                    String::new()
-                };
-                out.push_str(&format!(
-                    "  {} prefix={} sep={}{}\n",
-                    "install".style(self.styles.label()),
-                    view.pack.prefix.style(self.styles.name()),
-                    view.pack.sep.style(self.styles.name()),
-                    flatten
-                ));
-                out.push('\n');
-
-                // Local skills (only if non-empty)
-                if !view.local.is_empty() {
-                    out.push_str(&format!(
-                        "  {} {}\n",
-                        "Local".style(self.styles.header()),
-                        format!("({})", view.local.len()).style(self.styles.count())
-                    ));
-                    for (i, skill) in view.local.iter().enumerate() {
-                        let prefix = if i == view.local.len() - 1 {
-                            "└─"
-                        } else {
-                            "├─"
-                        };
-                        out.push_str(&format!(
-                            "  {} {}\n",
-                            prefix.style(self.styles.tree()),
-                            skill.style(self.styles.name())
-                        ));
-                    }
-                    out.push('\n');
-                }
-
-                // Imports
-                if !view.imports.is_empty() {
-                    out.push_str(&format!(
-                        "  {} {}\n",
-                        "Imports".style(self.styles.header()),
-                        format!("({})", view.imports.len()).style(self.styles.count())
-                    ));
-                    for (i, import) in view.imports.iter().enumerate() {
-                        let is_last_import = i == view.imports.len() - 1;
-                        let prefix = if is_last_import { "└─" } else { "├─" };
-                        let ref_str = import.reference.as_deref().unwrap_or("default");
-                        out.push_str(&format!(
-                            "  {} {} {} {}\n",
-                            prefix.style(self.styles.tree()),
-                            import.repo.style(self.styles.name()),
-                            format!("@{}", ref_str).style(self.styles.path()),
-                            format!("({})", short_hash(&import.commit)).style(self.styles.path())
-                        ));
-                        // Skills under this import
-                        for (j, skill) in import.skills.iter().enumerate() {
-                            let skill_prefix = if j == import.skills.len() - 1 {
-                                if is_last_import {
-                                    "   └─"
-                                } else {
-                                    "│  └─"
-                                }
-                            } else if is_last_import {
-                                "   ├─"
-                            } else {
-                                "│  ├─"
-                            };
-                            out.push_str(&format!(
-                                "  {} {}\n",
-                                skill_prefix.style(self.styles.tree()),
-                                skill.style(self.styles.path())
-                            ));
-                        }
-                    }
-                    out.push('\n');
-                }
-
-                // Final install names
-                if !view.final_install_names.is_empty() {
-                    out.push_str(&format!(
-                        "  {} {}\n",
-                        "Installs as".style(self.styles.header()),
-                        format!("({})", view.final_install_names.len()).style(self.styles.count())
-                    ));
-                    for name in &view.final_install_names {
-                        out.push_str(&format!(
-                            "  {} {}\n",
-                            "→".style(self.styles.tree()),
-                            name.style(self.styles.success())
-                        ));
-                    }
-                    out.push('\n');
-                }
-
-                self.write_stdout(&out)
-            }
+        let warnings = self.warnings.take();
+        // Plain output already logged each warning as it was pushed.
+        if self.format == OutputFormat::Plain {
+            return Ok(());
         }
+        self.write_stderr_warnings(&warnings)
     }
 
-    pub fn print_install(&self, view: &InstallView) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                out.push_str("installed ");
-                out.push_str(&view.installed_paths.len().to_string());
-                out.push_str(" skills to ");
-                out.push_str(&view.sink_path);
-                out.push('\n');
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-
-                // Success header
-                out.push_str(&format!(
-                    "{} Installed {} to {}\n\n",
-                    "✓".style(self.styles.success()),
-                    view.pack.name.style(self.styles.name()),
-                    view.sink.style(self.styles.name())
-                ));
-
-                // Details
-                out.push_str(&format!(
-                    "  {} {}\n",
-                    "path".style(self.styles.label()),
-                    abbreviate_path(&view.sink_path).style(self.styles.path())
-                ));
-                out.push_str(&format!(
-                    "  {} {}\n",
-                    "skills".style(self.styles.label()),
-                    view.installed_paths
-                        .len()
-                        .to_string()
-                        .style(self.styles.count())
-                ));
-
-                // Change summary
-                let mut changes = Vec::new();
-                if view.added > 0 {
-                    changes.push(format!(
-                        "{} added",
-                        view.added.to_string().style(self.styles.success())
-                    ));
-                }
-                if view.updated > 0 {
-                    changes.push(format!(
-                        "{} updated",
-                        view.updated.to_string().style(self.styles.count())
-                    ));
-                }
-                if view.removed > 0 {
-                    changes.push(format!(
-                        "{} removed",
-                        view.removed.to_string().style(self.styles.path())
-                    ));
-                }
-                if !changes.is_empty() {
-                    out.push_str(&format!(
-                        "  {} {}\n",
-                        "changes".style(self.styles.label()),
-                        changes.join(", ")
-                    ));
-                }
-                out.push('\n');
-                self.write_stdout(&out)
-            }
+    fn write_stderr_warnings(&self, warnings: &[String]) -> io::Result<()> {
+        let mut err = io::stderr().lock();
+        for message in warnings {
+            writeln!(
+                err,
+                "{} {}",
+                "warning:".style(self.styles.warning()),
+                message.style(self.styles.warning())
+            )?;
         }
+        Ok(())
     }
 
-    pub fn print_uninstall(&self, view: &UninstallView) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                out.push_str("uninstalled ");
-                out.push_str(&view.pack);
-                out.push_str(" from ");
-                out.push_str(&view.sink_path);
-                out.push('\n');
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-
-                // Success header
-                out.push_str(&format!(
-                    "{} Uninstalled {} from {}\n\n",
-                    "✓".style(self.styles.success()),
-                    view.pack.style(self.styles.name()),
-                    view.sink.style(self.styles.name())
-                ));
+    /// Print only the JSON node at `path` (`pack.name`, or a JSON pointer like
+    /// `/pack/name`) instead of the whole document.
+    pub fn with_select(mut self, path: Option<String>) -> Self {
+        self.select = path;
+        self
+    }
 
-                // Details
-                out.push_str(&format!(
-                    "  {} {}\n",
-                    "path".style(self.styles.label()),
-                    abbreviate_path(&view.sink_path).style(self.styles.path())
-                ));
-                out.push_str(&format!(
-                    "  {} {} skills\n",
-                    "removed".style(self.styles.label()),
-                    view.removed.to_string().style(self.styles.count())
-                ));
-                out.push('\n');
-                self.write_stdout(&out)
-            }
-        }
+    /// End plain listing records with NUL instead of newline (for `xargs -0`).
+    pub fn with_print0(mut self, print0: bool) -> Self {
+        self.print0 = print0;
+        self
     }
 
-    pub fn print_installed(&self, view: &InstalledView) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                for record in &view.installs {
-                    out.push_str(&format!(
-                        "{} {} {} {} {}\n",
-                        record.sink,
-                        record.pack,
-                        record.skill_count,
-                        record.installed_at,
-                        record.sink_path
-                    ));
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-                out.push_str(&format!("{}\n\n", "Installed".style(self.styles.header())));
+    /// Show per-item detail (e.g. which skills an install added/updated/removed).
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
 
-                if view.installs.is_empty() {
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "No packs installed".style(self.styles.path())
-                    ));
-                    out.push_str(&format!(
-                        "  {}\n",
-                        "Run: sp install <pack> --codex".style(self.styles.path())
-                    ));
-                } else {
-                    for record in &view.installs {
-                        out.push_str(&format!(
-                            "  {} {} {} {}\n",
-                            record.pack.style(self.styles.name()),
-                            format!("→ {}", record.sink).style(self.styles.path()),
-                            format!("({} skills)", record.skill_count).style(self.styles.count()),
-                            record.installed_at.as_str().style(self.styles.path())
-                        ));
-                        out.push_str(&format!(
-                            "    {}\n",
-                            abbreviate_path(&record.sink_path).style(self.styles.path())
-                        ));
-                    }
-                }
-                out.push('\n');
-                self.write_stdout(&out)
-            }
-        }
+    /// Whether stdout carries a JSON document, so side channels like progress stay quiet.
+    pub fn is_json(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::CompactJson)
     }
 
-    pub fn print_config(&self, view: &ConfigView) -> io::Result<()> {
-        match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                for sink in &view.effective {
-                    out.push_str(&sink.name);
-                    out.push(' ');
-                    out.push_str(&sink.path);
-                    out.push('\n');
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-                out.push_str(&format!("{}\n\n", "Config".style(self.styles.header())));
+    /// Where commands report soft problems so they show up in the output.
+    pub fn warnings(&self) -> &WarningSink {
+        &self.warnings
+    }
 
-                out.push_str(&format!(
-                    "  {} {}\n\n",
-                    "file".style(self.styles.label()),
-                    abbreviate_path(&view.config_path).style(self.styles.path())
-                ));
+    fn record_end(&self) -> char {
+        if self.print0 { '\0' } else { '\n' }
+    }
 
-                // Show effective sinks (the ones that matter)
-                out.push_str(&format!(
-                    "  {} {}\n",
-                    "Sinks".style(self.styles.header()),
-                    format!("({})", view.effective.len()).style(self.styles.count())
-                ));
-                for sink in &view.effective {
-                    let is_override = view.overrides.iter().any(|o| o.name == sink.name);
-                    let marker = if is_override { " (override)" } else { "" };
-                    out.push_str(&format!(
-                        "  {} {}{}\n",
-                        sink.name.style(self.styles.name()),
-                        abbreviate_path(&sink.path).style(self.styles.path()),
-                        marker.style(self.styles.path())
-                    ));
-                }
-                out.push('\n');
-                self.write_stdout(&out)
-            }
+    fn write_stdout(&self, text: &str) -> io::Result<()> {
+        if self.format != OutputFormat::Pretty {
+            return io::stdout().lock().write_all(text.as_bytes());
+        }
+        let mut out = text.to_string();
+        let warnings = self.warnings.take();
+        for message in &warnings {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "!".style(self.styles.warning()),
+                message.style(self.styles.warning())
+            ));
+        }
+        if !warnings.is_empty() {
+            out.push('\n');
+        }
+        match &self.pager {
+            Some(pager) => pager.write(&out),
+            None => io::stdout().lock().write_all(out.as_bytes()),
         }
     }
 
-    pub fn print_switch(&self, view: &SwitchView) -> io::Result<()> {
+    /// Prints `view` as JSON under the JSON formats, otherwise the text `render`
+    /// builds for plain or pretty output.
+    fn emit<T: Serialize>(
+        &self,
+        view: &T,
+        render: impl FnOnce(TextFormat) -> String,
+    ) -> io::Result<()> {
         match self.format {
-            OutputFormat::Json => self.print_json(view),
-            OutputFormat::Plain => {
-                let mut out = String::new();
-                for sink_view in &view.sinks {
-                    out.push_str("switched ");
-                    out.push_str(&sink_view.sink);
-                    out.push_str(" uninstalled ");
-                    out.push_str(&sink_view.uninstalled.len().to_string());
-                    out.push_str(" installed ");
-                    out.push_str(&sink_view.installed.len().to_string());
-                    out.push('\n');
-                }
-                self.write_stdout(&out)
-            }
-            OutputFormat::Pretty => {
-                let mut out = String::new();
-
-                for sink_view in &view.sinks {
-                    out.push_str(&format!(
-                        "{} Switched {}\n\n",
-                        "✓".style(self.styles.success()),
-                        sink_view.sink.style(self.styles.name())
-                    ));
-
-                    out.push_str(&format!(
-                        "  {} {}\n",
-                        "path".style(self.styles.label()),
-                        abbreviate_path(&sink_view.sink_path).style(self.styles.path())
-                    ));
-
-                    if !sink_view.uninstalled.is_empty() {
-                        out.push_str(&format!(
-                            "  {} {}\n",
-                            "uninstalled".style(self.styles.label()),
-                            sink_view.uninstalled.join(", ").style(self.styles.path())
-                        ));
-                    }
-
-                    if !sink_view.installed.is_empty() {
-                        out.push_str(&format!(
-                            "  {} {}\n",
-                            "installed".style(self.styles.label()),
-                            sink_view.installed.join(", ").style(self.styles.success())
-                        ));
-                    }
-
-                    out.push('\n');
-                }
-
-                self.write_stdout(&out)
-            }
+            OutputFormat::Json | OutputFormat::CompactJson => self.print_json(view),
+            OutputFormat::Plain => self.write_stdout(&render(TextFormat::Plain)),
+            OutputFormat::Pretty => self.write_stdout(&render(TextFormat::Pretty)),
         }
     }
 
-    fn write_stdout(&self, text: &str) -> io::Result<()> {
-        let mut stdout = io::stdout().lock();
-        stdout.write_all(text.as_bytes())
+    fn print_json<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let document = WithWarnings {
+            view: value,
+            warnings: self.warnings.take(),
+        };
+        let Some(path) = &self.select else {
+            return self.write_json(&document);
+        };
+        // The selected node rarely includes `warnings`, so they go to stderr.
+        self.write_stderr_warnings(&document.warnings)?;
+        let document = serde_json::to_value(&document).map_err(io::Error::other)?;
+        let node = document.pointer(&json_pointer(path)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("--select path not found in output: {path}"),
+            )
+        })?;
+        self.write_json(node)
     }
 
-    fn print_json<T: Serialize>(&self, value: &T) -> io::Result<()> {
-        let mut out = serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string());
+    fn write_json<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let out = if self.format == OutputFormat::CompactJson {
+            serde_json::to_string(value)
+        } else {
+            serde_json::to_string_pretty(value)
+        };
+        let mut out = out.unwrap_or_else(|_| "{}".to_string());
         out.push('\n');
         self.write_stdout(&out)
     }
 }
+
+/// `path` as a JSON pointer: returned as is when it starts with `/`, otherwise
+/// split on `.` (`imports.0.repo` → `/imports/0/repo`).
+fn json_pointer(path: &str) -> String {
+    if path.starts_with('/') {
+        return path.to_string();
+    }
+    path.split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// A JSON document with the command's collected warnings appended.
+#[derive(Serialize)]
+struct WithWarnings<'a, T> {
+    #[serde(flatten)]
+    view: &'a T,
+    warnings: Vec<String>,
+}
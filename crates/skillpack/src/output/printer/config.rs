@@ -0,0 +1,194 @@
+use super::{Output, TextFormat};
+use crate::config::Severity;
+use crate::output::helpers::{abbreviate_path, format_bytes, short_hash};
+use crate::output::types::{
+    AgentsView, CacheListView, CachePathView, CacheSizeView, ConfigCheckView, ConfigView,
+    OutputFormat,
+};
+use owo_colors::OwoColorize;
+use std::io;
+
+impl Output {
+    pub fn print_cache_path(&self, view: &CachePathView) -> io::Result<()> {
+        self.emit(view, |_| format!("{}\n", view.path))
+    }
+
+    pub fn print_cache_size(&self, view: &CacheSizeView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => format!("{}\n", view.bytes),
+            TextFormat::Pretty => format!(
+                "{} {}\n",
+                format_bytes(view.bytes).style(self.styles.name()),
+                format!("in {}", abbreviate_path(&view.path)).style(self.styles.path())
+            ),
+        })
+    }
+
+    pub fn print_cache_list(&self, view: &CacheListView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for repo in &view.repos {
+                    out.push_str(&format!(
+                        "{} {} {}\n",
+                        repo.path,
+                        repo.bytes,
+                        repo.origin.as_deref().unwrap_or("-")
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!(
+                    "{} {}\n\n",
+                    "Cache".style(self.styles.header()),
+                    abbreviate_path(&view.path).style(self.styles.path())
+                ));
+
+                if view.repos.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No cached repos".style(self.styles.path())
+                    ));
+                } else {
+                    for repo in &view.repos {
+                        let name = std::path::Path::new(&repo.path)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_else(|| repo.path.clone());
+                        out.push_str(&format!(
+                            "  {} {} {}\n",
+                            short_hash(&name).style(self.styles.name()),
+                            repo.origin.as_deref().unwrap_or("(no origin)"),
+                            format!("({})", format_bytes(repo.bytes)).style(self.styles.path())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_config(&self, view: &ConfigView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for sink in &view.effective {
+                    out.push_str(&sink.name);
+                    out.push(' ');
+                    out.push_str(&sink.path);
+                    out.push('\n');
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Config".style(self.styles.header())));
+
+                out.push_str(&format!(
+                    "  {} {}\n\n",
+                    "file".style(self.styles.label()),
+                    abbreviate_path(&view.config_path).style(self.styles.path())
+                ));
+
+                // Show effective sinks (the ones that matter)
+                out.push_str(&format!(
+                    "  {} {}\n",
+                    "Sinks".style(self.styles.header()),
+                    format!("({})", view.effective.len()).style(self.styles.count())
+                ));
+                for sink in &view.effective {
+                    let marker = match sink.source.as_str() {
+                        "config" => " (override)",
+                        "env" => " (env)",
+                        _ => "",
+                    };
+                    out.push_str(&format!(
+                        "  {} {}{}\n",
+                        sink.name.style(self.styles.name()),
+                        abbreviate_path(&sink.path).style(self.styles.path()),
+                        marker.style(self.styles.path())
+                    ));
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_agents(&self, view: &AgentsView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for agent in &view.agents {
+                    out.push_str(&agent.name);
+                    out.push(self.record_end());
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Agents".style(self.styles.header())));
+                for agent in &view.agents {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        agent.name.style(self.styles.name()),
+                        abbreviate_path(&agent.path).style(self.styles.path())
+                    ));
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_config_check(&self, view: &ConfigCheckView) -> io::Result<()> {
+        match self.format {
+            // Its `warnings` field already counts the checker's own findings.
+            OutputFormat::Json | OutputFormat::CompactJson => self.write_json(view),
+            OutputFormat::Plain => {
+                let mut out = String::new();
+                for finding in &view.findings {
+                    out.push_str(&format!(
+                        "{} {} {}\n",
+                        finding.severity.as_str(),
+                        finding.sink,
+                        finding.message
+                    ));
+                }
+                self.write_stdout(&out)
+            }
+            OutputFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!(
+                    "{} {}\n\n",
+                    "Config check".style(self.styles.header()),
+                    abbreviate_path(&view.config_path).style(self.styles.path())
+                ));
+                if view.findings.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "✓".style(self.styles.success()),
+                        "No problems found".style(self.styles.path())
+                    ));
+                }
+                for finding in &view.findings {
+                    let (marker, style) = match finding.severity {
+                        Severity::Error => ("✗", self.styles.error()),
+                        Severity::Warning => ("!", self.styles.warning()),
+                    };
+                    out.push_str(&format!(
+                        "  {} {} {}\n",
+                        marker.style(style),
+                        finding.sink.style(self.styles.name()),
+                        finding.message
+                    ));
+                }
+                out.push('\n');
+                self.write_stdout(&out)
+            }
+        }
+    }
+}
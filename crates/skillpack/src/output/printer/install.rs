@@ -0,0 +1,397 @@
+use super::{Output, TextFormat};
+use crate::output::helpers::abbreviate_path;
+use crate::output::types::{
+    InstallView, OutputFormat, ReinstallView, SwitchView, TimingsView, UninstallView, UpdateView,
+};
+use owo_colors::OwoColorize;
+use std::io::{self, Write};
+
+impl Output {
+    pub fn print_install(&self, view: &InstallView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                out.push_str("installed ");
+                out.push_str(&view.installed_paths.len().to_string());
+                out.push_str(" skills to ");
+                out.push_str(&view.sink_path);
+                out.push('\n');
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+
+                // Success header
+                out.push_str(&format!(
+                    "{} Installed {} to {}\n\n",
+                    "✓".style(self.styles.success()),
+                    view.pack.name.style(self.styles.name()),
+                    view.sink.style(self.styles.name())
+                ));
+
+                // Details
+                out.push_str(&format!(
+                    "  {} {}\n",
+                    "path".style(self.styles.label()),
+                    abbreviate_path(&view.sink_path).style(self.styles.path())
+                ));
+                out.push_str(&format!(
+                    "  {} {}\n",
+                    "skills".style(self.styles.label()),
+                    view.installed_paths
+                        .len()
+                        .to_string()
+                        .style(self.styles.count())
+                ));
+
+                // Change summary
+                let mut changes = Vec::new();
+                if view.added > 0 {
+                    changes.push(format!(
+                        "{} added",
+                        view.added.to_string().style(self.styles.success())
+                    ));
+                }
+                if view.updated > 0 {
+                    changes.push(format!(
+                        "{} updated",
+                        view.updated.to_string().style(self.styles.count())
+                    ));
+                }
+                if view.removed > 0 {
+                    changes.push(format!(
+                        "{} removed",
+                        view.removed.to_string().style(self.styles.path())
+                    ));
+                }
+                if !changes.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "changes".style(self.styles.label()),
+                        changes.join(", ")
+                    ));
+                }
+                if self.verbose {
+                    for name in &view.added_names {
+                        out.push_str(&format!(
+                            "    {}\n",
+                            format!("+ {name}").style(self.styles.success())
+                        ));
+                    }
+                    for name in &view.updated_names {
+                        out.push_str(&format!(
+                            "    {}\n",
+                            format!("~ {name}").style(self.styles.count())
+                        ));
+                    }
+                    for name in &view.removed_names {
+                        out.push_str(&format!(
+                            "    {}\n",
+                            format!("- {name}").style(self.styles.path())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_reinstall(&self, view: &ReinstallView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => format!("reinstalled {} {}\n", view.pack, view.skills.join(" ")),
+            TextFormat::Pretty => format!(
+                "{} Reinstalled {} {}\n",
+                "↻".style(self.styles.success()),
+                view.skills.join(", ").style(self.styles.name()),
+                format!("→ {}", view.sinks.join(", ")).style(self.styles.path())
+            ),
+        })
+    }
+
+    fn uninstall_missing_line(&self, missing: usize) -> String {
+        format!(
+            "  {} {} skills (already deleted; state was out of date)\n",
+            "missing".style(self.styles.label()),
+            missing.to_string().style(self.styles.count())
+        )
+    }
+
+    /// Writes to stderr so `--timings` never mixes into the command's own output.
+    pub fn print_timings(&self, view: &TimingsView) -> io::Result<()> {
+        let out = match self.format {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(view).map_err(io::Error::other)? + "\n"
+            }
+            OutputFormat::CompactJson => {
+                serde_json::to_string(view).map_err(io::Error::other)? + "\n"
+            }
+            OutputFormat::Plain => view
+                .phases
+                .iter()
+                .map(|timing| format!("{}\t{:.1}\n", timing.phase, timing.ms))
+                .collect(),
+            OutputFormat::Pretty => {
+                let mut out = format!("{}\n", "Timings".style(self.styles.header()));
+                for timing in &view.phases {
+                    out.push_str(&format!(
+                        "  {:<16} {}\n",
+                        timing.phase,
+                        format!("{:.1}ms", timing.ms).style(self.styles.name())
+                    ));
+                }
+                out
+            }
+        };
+        io::stderr().lock().write_all(out.as_bytes())
+    }
+
+    pub fn print_uninstall(&self, view: &UninstallView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                if !view.was_installed {
+                    out.push_str("not installed ");
+                    out.push_str(&view.pack);
+                    out.push_str(" in ");
+                    out.push_str(&view.sink_path);
+                    out.push('\n');
+                    return out;
+                }
+                if view.dry_run {
+                    out.push_str("would uninstall ");
+                    out.push_str(&view.pack);
+                    out.push_str(" from ");
+                    out.push_str(&view.sink_path);
+                    out.push('\n');
+                    let action = if view.unmanaged > 0 {
+                        "keep "
+                    } else {
+                        "remove "
+                    };
+                    for path in &view.paths {
+                        out.push_str(action);
+                        out.push_str(path);
+                        out.push('\n');
+                    }
+                    if view.missing > 0 {
+                        out.push_str(&format!("missing {}\n", view.missing));
+                    }
+                    return out;
+                }
+                out.push_str("uninstalled ");
+                out.push_str(&view.pack);
+                out.push_str(" from ");
+                out.push_str(&view.sink_path);
+                out.push('\n');
+                for path in view.paths.iter().filter(|_| view.unmanaged > 0) {
+                    out.push_str("keep ");
+                    out.push_str(path);
+                    out.push('\n');
+                }
+                if view.missing > 0 {
+                    out.push_str(&format!("missing {}\n", view.missing));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+
+                if !view.was_installed {
+                    out.push_str(&format!(
+                        "{} {} is not installed in {}, nothing to do\n\n",
+                        "•".style(self.styles.label()),
+                        view.pack.style(self.styles.name()),
+                        view.sink.style(self.styles.name())
+                    ));
+                    return out;
+                }
+
+                if view.dry_run {
+                    out.push_str(&format!(
+                        "{} Would uninstall {} from {} {}\n\n",
+                        "•".style(self.styles.label()),
+                        view.pack.style(self.styles.name()),
+                        view.sink.style(self.styles.name()),
+                        "(dry run)".style(self.styles.path())
+                    ));
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "path".style(self.styles.label()),
+                        abbreviate_path(&view.sink_path).style(self.styles.path())
+                    ));
+                    out.push_str(&format!(
+                        "  {} {} skills\n",
+                        "would remove".style(self.styles.label()),
+                        view.removed.to_string().style(self.styles.count())
+                    ));
+                    if view.unmanaged > 0 {
+                        out.push_str(&format!(
+                            "  {} {} skills\n",
+                            "would keep".style(self.styles.label()),
+                            view.unmanaged.to_string().style(self.styles.count())
+                        ));
+                    }
+                    if view.missing > 0 {
+                        out.push_str(&self.uninstall_missing_line(view.missing));
+                    }
+                    for path in &view.paths {
+                        out.push_str(&format!(
+                            "    {} {}\n",
+                            "-".style(self.styles.tree()),
+                            abbreviate_path(path).style(self.styles.path())
+                        ));
+                    }
+                    out.push('\n');
+                    return out;
+                }
+
+                // Success header
+                out.push_str(&format!(
+                    "{} Uninstalled {} from {}\n\n",
+                    "✓".style(self.styles.success()),
+                    view.pack.style(self.styles.name()),
+                    view.sink.style(self.styles.name())
+                ));
+
+                // Details
+                out.push_str(&format!(
+                    "  {} {}\n",
+                    "path".style(self.styles.label()),
+                    abbreviate_path(&view.sink_path).style(self.styles.path())
+                ));
+                out.push_str(&format!(
+                    "  {} {} skills\n",
+                    "removed".style(self.styles.label()),
+                    view.removed.to_string().style(self.styles.count())
+                ));
+                if view.unmanaged > 0 {
+                    out.push_str(&format!(
+                        "  {} {} skills (left in place, no longer managed)\n",
+                        "unmanaged".style(self.styles.label()),
+                        view.unmanaged.to_string().style(self.styles.count())
+                    ));
+                }
+                if view.missing > 0 {
+                    out.push_str(&self.uninstall_missing_line(view.missing));
+                }
+                if view.pruned_sink {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "pruned".style(self.styles.label()),
+                        "empty sink directory".style(self.styles.path())
+                    ));
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_update(&self, view: &UpdateView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for item in &view.updates {
+                    out.push_str(&format!(
+                        "{} {} {} {} {}\n",
+                        item.status.as_str(),
+                        item.sink,
+                        item.pack,
+                        item.skill_count,
+                        item.sink_path
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Update".style(self.styles.header())));
+
+                if view.updates.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No packs installed".style(self.styles.path())
+                    ));
+                } else {
+                    for item in &view.updates {
+                        let status = match item.reason {
+                            Some(ref reason) => format!("{} ({reason})", item.status.as_str())
+                                .style(self.styles.label())
+                                .to_string(),
+                            None => item
+                                .status
+                                .as_str()
+                                .style(self.styles.success())
+                                .to_string(),
+                        };
+                        out.push_str(&format!(
+                            "  {} {} {} {}\n",
+                            item.pack.style(self.styles.name()),
+                            format!("→ {}", item.sink).style(self.styles.path()),
+                            format!("({} skills)", item.skill_count).style(self.styles.count()),
+                            status
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_switch(&self, view: &SwitchView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for sink_view in &view.sinks {
+                    out.push_str("switched ");
+                    out.push_str(&sink_view.sink);
+                    out.push_str(" uninstalled ");
+                    out.push_str(&sink_view.uninstalled.len().to_string());
+                    out.push_str(" installed ");
+                    out.push_str(&sink_view.installed.len().to_string());
+                    out.push('\n');
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+
+                for sink_view in &view.sinks {
+                    out.push_str(&format!(
+                        "{} Switched {}\n\n",
+                        "✓".style(self.styles.success()),
+                        sink_view.sink.style(self.styles.name())
+                    ));
+
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "path".style(self.styles.label()),
+                        abbreviate_path(&sink_view.sink_path).style(self.styles.path())
+                    ));
+
+                    if !sink_view.uninstalled.is_empty() {
+                        out.push_str(&format!(
+                            "  {} {}\n",
+                            "uninstalled".style(self.styles.label()),
+                            sink_view.uninstalled.join(", ").style(self.styles.path())
+                        ));
+                    }
+
+                    if !sink_view.installed.is_empty() {
+                        out.push_str(&format!(
+                            "  {} {}\n",
+                            "installed".style(self.styles.label()),
+                            sink_view.installed.join(", ").style(self.styles.success())
+                        ));
+                    }
+
+                    out.push('\n');
+                }
+
+                out
+            }
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use super::{Output, TextFormat};
+use crate::output::helpers::abbreviate_path;
+use crate::output::types::PackSummary;
+use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+use std::io;
+
+impl Output {
+    /// A bare total (`sp show --count`): the number alone, or `{"count": N}`.
+    pub fn print_count(&self, count: usize) -> io::Result<()> {
+        self.emit(&serde_json::json!({ "count": count }), |_| {
+            format!("{count}\n")
+        })
+    }
+
+    /// `sources` maps each id to where it was found (`repo` or `bundled`); it is only
+    /// printed in JSON.
+    pub fn print_skills(
+        &self,
+        skills: &[String],
+        sources: Option<&BTreeMap<String, &str>>,
+    ) -> io::Result<()> {
+        let mut view = serde_json::json!({
+            "count": skills.len(),
+            "skills": skills,
+        });
+        if let Some(sources) = sources {
+            view["sources"] = serde_json::json!(sources);
+        }
+        self.emit(&view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for id in skills {
+                    out.push_str(id);
+                    out.push(self.record_end());
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Skills".style(self.styles.header())));
+                if skills.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No skills found".style(self.styles.path())
+                    ));
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "Create skills/ directory with SKILL.md files to get started"
+                            .style(self.styles.path())
+                    ));
+                } else {
+                    for skill in skills {
+                        out.push_str(&format!("  {}\n", skill.style(self.styles.name())));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_packs(&self, packs: &[PackSummary]) -> io::Result<()> {
+        let view = serde_json::json!({
+            "count": packs.len(),
+            "packs": packs,
+        });
+        self.emit(&view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for pack in packs {
+                    out.push_str(&pack.name);
+                    out.push(self.record_end());
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Packs".style(self.styles.header())));
+                if packs.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No packs found".style(self.styles.path())
+                    ));
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "Create packs/*.yaml files to define skill collections"
+                            .style(self.styles.path())
+                    ));
+                } else {
+                    for pack in packs {
+                        out.push_str(&format!(
+                            "  {}  {}\n",
+                            pack.name.style(self.styles.name()),
+                            abbreviate_path(&pack.path).style(self.styles.path())
+                        ));
+                        let mut rows: Vec<String> = pack.skills.clone().unwrap_or_default();
+                        match pack.imports {
+                            Some(1) => rows.push("1 import".to_string()),
+                            Some(n) if n > 1 => rows.push(format!("{n} imports")),
+                            _ => {}
+                        }
+                        for (i, row) in rows.iter().enumerate() {
+                            let prefix = if i == rows.len() - 1 {
+                                "└─"
+                            } else {
+                                "├─"
+                            };
+                            out.push_str(&format!(
+                                "    {} {}\n",
+                                prefix.style(self.styles.tree()),
+                                row.style(self.styles.path())
+                            ));
+                        }
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+}
@@ -0,0 +1,472 @@
+use super::{Output, TextFormat};
+use crate::output::helpers::{abbreviate_path, short_hash};
+use crate::output::types::{ExplainView, PackDiffView, ShowExplainView, ShowView};
+use crate::pack::{NormalizedPack, PrefixMode};
+use owo_colors::OwoColorize;
+use std::io;
+
+impl Output {
+    pub fn print_show(&self, view: &ShowView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                let label = |out: &mut String, label: &str| {
+                    if !view.bare {
+                        out.push_str(label);
+                        out.push('\n');
+                    }
+                };
+                label(&mut out, "local");
+                let skill_line = |id: &str| match show_summary(view, id) {
+                    Some(summary) => format!("{id}\t{summary}\n"),
+                    None => format!("{id}\n"),
+                };
+                for id in &view.local {
+                    out.push_str(&skill_line(id));
+                }
+                for import in &view.imports {
+                    label(&mut out, &format!("import {}", import.repo));
+                    for id in &import.skills {
+                        out.push_str(&skill_line(id));
+                    }
+                }
+                if !view.bare {
+                    for (from, to) in &view.renames {
+                        out.push_str(&format!("rename {from} {to}\n"));
+                    }
+                    for (pattern, ids) in &view.excluded {
+                        out.push_str(&format!("exclude {pattern} {}\n", ids.join(" ")));
+                    }
+                }
+                label(&mut out, "final");
+                for name in &view.final_install_names {
+                    out.push_str(name);
+                    out.push('\n');
+                }
+                if !view.bare {
+                    for (name, ids) in &view.collisions {
+                        out.push_str("collision ");
+                        out.push_str(name);
+                        for id in ids {
+                            out.push(' ');
+                            out.push_str(id);
+                        }
+                        out.push('\n');
+                    }
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+
+                // Pack header
+                out.push_str(&format!(
+                    "{}\n\n",
+                    view.pack.name.style(self.styles.header())
+                ));
+
+                // Pack info
+                out.push_str(&format!(
+                    "  {} {}\n",
+                    "source".style(self.styles.label()),
+                    abbreviate_path(&view.pack.file).style(self.styles.path())
+                ));
+                let flatten = if view.pack.flatten {
+                    format!(" flatten={}", "leaf".style(self.styles.name()))
+                } else {
+                    String::new()
+                };
+                let prefix = match view.pack.prefix_mode {
+                    PrefixMode::Single => view.pack.prefix.as_str(),
+                    PrefixMode::FirstSegment => "<first segment>",
+                };
+                out.push_str(&format!(
+                    "  {} prefix={} sep={}{}\n",
+                    "install".style(self.styles.label()),
+                    prefix.style(self.styles.name()),
+                    view.pack.sep.style(self.styles.name()),
+                    flatten
+                ));
+                out.push('\n');
+
+                // Local skills (only if non-empty)
+                if !view.local.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Local".style(self.styles.header()),
+                        format!("({})", view.local.len()).style(self.styles.count())
+                    ));
+                    for (i, skill) in view.local.iter().enumerate() {
+                        let prefix = if i == view.local.len() - 1 {
+                            "└─"
+                        } else {
+                            "├─"
+                        };
+                        out.push_str(&format!(
+                            "  {} {}{}\n",
+                            prefix.style(self.styles.tree()),
+                            skill.style(self.styles.name()),
+                            self.show_summary_suffix(view, skill)
+                        ));
+                    }
+                    out.push('\n');
+                }
+
+                // Imports
+                if !view.imports.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Imports".style(self.styles.header()),
+                        format!("({})", view.imports.len()).style(self.styles.count())
+                    ));
+                    for (i, import) in view.imports.iter().enumerate() {
+                        let is_last_import = i == view.imports.len() - 1;
+                        let prefix = if is_last_import { "└─" } else { "├─" };
+                        let ref_str = import
+                            .resolved_ref
+                            .as_deref()
+                            .or(import.reference.as_deref())
+                            .unwrap_or("default");
+                        let overridden = if import.ref_overridden {
+                            format!(" {}", "(--repo-ref)".style(self.styles.warning()))
+                        } else {
+                            String::new()
+                        };
+                        out.push_str(&format!(
+                            "  {} {} {} {}{}\n",
+                            prefix.style(self.styles.tree()),
+                            import.repo.style(self.styles.name()),
+                            format!("@{}", ref_str).style(self.styles.path()),
+                            format!("({})", short_hash(&import.commit)).style(self.styles.path()),
+                            overridden
+                        ));
+                        // Skills under this import
+                        let limit = view.max_skills.unwrap_or(usize::MAX);
+                        let hidden = import.skills.len().saturating_sub(limit);
+                        let mut rows: Vec<String> = import
+                            .skills
+                            .iter()
+                            .take(limit)
+                            .map(|skill| {
+                                format!(
+                                    "{}{}",
+                                    skill.style(self.styles.path()),
+                                    self.show_summary_suffix(view, skill)
+                                )
+                            })
+                            .collect();
+                        if hidden > 0 {
+                            rows.push(
+                                format!("... and {hidden} more")
+                                    .style(self.styles.path())
+                                    .to_string(),
+                            );
+                        }
+                        for (j, skill) in rows.iter().enumerate() {
+                            let skill_prefix = if j == rows.len() - 1 {
+                                if is_last_import {
+                                    "   └─"
+                                } else {
+                                    "│  └─"
+                                }
+                            } else if is_last_import {
+                                "   ├─"
+                            } else {
+                                "│  ├─"
+                            };
+                            out.push_str(&format!(
+                                "  {} {}\n",
+                                skill_prefix.style(self.styles.tree()),
+                                skill
+                            ));
+                        }
+                    }
+                    out.push('\n');
+                }
+
+                if !view.renames.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Renames".style(self.styles.header()),
+                        format!("({})", view.renames.len()).style(self.styles.count())
+                    ));
+                    for (from, to) in &view.renames {
+                        out.push_str(&format!(
+                            "  {} {} {}\n",
+                            "→".style(self.styles.tree()),
+                            from.style(self.styles.path()),
+                            format!("as {to}").style(self.styles.name())
+                        ));
+                    }
+                    out.push('\n');
+                }
+
+                if !view.excluded.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Excluded".style(self.styles.header()),
+                        format!("({})", view.excluded.len()).style(self.styles.count())
+                    ));
+                    for (pattern, ids) in &view.excluded {
+                        out.push_str(&format!(
+                            "  {} {} {}\n",
+                            "✗".style(self.styles.tree()),
+                            pattern.style(self.styles.name()),
+                            format!("removed {}", ids.join(", ")).style(self.styles.path())
+                        ));
+                    }
+                    out.push('\n');
+                }
+
+                // Final install names
+                if !view.final_install_names.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Installs as".style(self.styles.header()),
+                        format!("({})", view.final_install_names.len()).style(self.styles.count())
+                    ));
+                    for name in &view.final_install_names {
+                        out.push_str(&format!(
+                            "  {} {}\n",
+                            "→".style(self.styles.tree()),
+                            name.style(self.styles.success())
+                        ));
+                    }
+                    out.push('\n');
+                }
+
+                if !view.collisions.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "Collisions".style(self.styles.error()),
+                        format!("({})", view.collisions.len()).style(self.styles.count())
+                    ));
+                    for (name, ids) in &view.collisions {
+                        out.push_str(&format!(
+                            "  {} {} {}\n",
+                            "✗".style(self.styles.error()),
+                            name.style(self.styles.error()),
+                            format!("← {}", ids.join(", ")).style(self.styles.path())
+                        ));
+                    }
+                    out.push('\n');
+                }
+
+                out
+            }
+        })
+    }
+
+    /// Pack YAML for plain and pretty output, warnings as leading comments so the
+    /// text stays valid YAML.
+    pub fn print_normalized(&self, pack: &NormalizedPack) -> io::Result<()> {
+        let yaml = serde_yaml::to_string(pack).map_err(io::Error::other)?;
+        self.emit(pack, |_| {
+            let mut out = String::new();
+            for message in self.warnings.take() {
+                out.push_str(&format!("# warning: {message}\n"));
+            }
+            out.push_str(&yaml);
+            out
+        })
+    }
+
+    pub fn print_explain(&self, view: &ExplainView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => explain_plain(view),
+            TextFormat::Pretty => self.explain_pretty(view),
+        })
+    }
+
+    /// Plain lines carry the skill id first, since records for many skills follow each other.
+    pub fn print_show_explain(&self, view: &ShowExplainView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for skill in &view.skills {
+                    for line in explain_plain(skill).lines() {
+                        out.push_str(&format!("{} {line}\n", skill.skill));
+                    }
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let out: String = view
+                    .skills
+                    .iter()
+                    .map(|skill| self.explain_pretty(skill))
+                    .collect();
+                out
+            }
+        })
+    }
+
+    fn explain_pretty(&self, view: &ExplainView) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{} {}\n\n",
+            view.skill.style(self.styles.header()),
+            format!("in {}", view.pack).style(self.styles.path())
+        ));
+        for source in &view.sources {
+            let (marker, style) = if source.selected {
+                ("✓", self.styles.success())
+            } else {
+                ("·", self.styles.path())
+            };
+            out.push_str(&format!(
+                "  {} {}\n",
+                marker.style(style),
+                source.source.style(self.styles.name())
+            ));
+            if source.include.is_empty() {
+                out.push_str(&format!(
+                    "    {} {}\n",
+                    "include".style(self.styles.label()),
+                    "no pattern matches".style(self.styles.path())
+                ));
+            }
+            for pat in &source.include {
+                out.push_str(&format!(
+                    "    {} {}\n",
+                    "include".style(self.styles.label()),
+                    pat
+                ));
+            }
+            for pat in &source.exclude {
+                out.push_str(&format!(
+                    "    {} {}\n",
+                    "exclude".style(self.styles.label()),
+                    pat.style(self.styles.error())
+                ));
+            }
+        }
+        for pat in &view.exclude {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "pack exclude".style(self.styles.label()),
+                pat.style(self.styles.error())
+            ));
+        }
+        if let Some(to) = &view.renamed_to {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "renamed".style(self.styles.label()),
+                to.style(self.styles.name())
+            ));
+        }
+        if !view.collides_with.is_empty() {
+            out.push_str(&format!(
+                "  {} {}\n",
+                "collides with".style(self.styles.label()),
+                view.collides_with.join(", ").style(self.styles.error())
+            ));
+        }
+        out.push('\n');
+        match (&view.install_name, &view.reason) {
+            (Some(name), _) => out.push_str(&format!(
+                "  {} {}\n",
+                "→".style(self.styles.tree()),
+                name.style(self.styles.success())
+            )),
+            (None, Some(reason)) => out.push_str(&format!(
+                "  {} {}\n",
+                "✗".style(self.styles.error()),
+                reason
+            )),
+            (None, None) => {}
+        }
+        out.push('\n');
+        out
+    }
+
+    pub fn print_pack_diff(&self, view: &PackDiffView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for (mark, ids) in [
+                    ("-", &view.only_in_pack),
+                    ("+", &view.only_in_other),
+                    ("=", &view.in_both),
+                ] {
+                    for id in ids {
+                        out.push_str(&format!("{mark} {id}\n"));
+                    }
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = format!(
+                    "{} {} {}\n\n",
+                    view.pack.style(self.styles.header()),
+                    "→".style(self.styles.tree()),
+                    view.other.style(self.styles.header())
+                );
+                let sections = [
+                    (format!("Only in {}", view.pack), &view.only_in_pack, "-"),
+                    (format!("Only in {}", view.other), &view.only_in_other, "+"),
+                    ("In both".to_string(), &view.in_both, " "),
+                ];
+                for (title, ids, mark) in sections {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        title.style(self.styles.header()),
+                        format!("({})", ids.len()).style(self.styles.count())
+                    ));
+                    for id in ids {
+                        let line = format!("{mark} {id}");
+                        let line = match mark {
+                            "-" => line.style(self.styles.error()).to_string(),
+                            "+" => line.style(self.styles.success()).to_string(),
+                            _ => line.style(self.styles.path()).to_string(),
+                        };
+                        out.push_str(&format!("  {line}\n"));
+                    }
+                    out.push('\n');
+                }
+                out
+            }
+        })
+    }
+
+    /// ` — <description>` dimmed, for skills `sp show --with-metadata` found frontmatter for.
+    fn show_summary_suffix(&self, view: &ShowView, id: &str) -> String {
+        match show_summary(view, id) {
+            Some(summary) => format!(" {}", format!("— {summary}").style(self.styles.path())),
+            None => String::new(),
+        }
+    }
+}
+
+fn explain_plain(view: &ExplainView) -> String {
+    let mut out = String::new();
+    for source in &view.sources {
+        for pat in &source.include {
+            out.push_str(&format!("include {} {pat}\n", source.source));
+        }
+        for pat in &source.exclude {
+            out.push_str(&format!("exclude {} {pat}\n", source.source));
+        }
+    }
+    for pat in &view.exclude {
+        out.push_str(&format!("exclude pack {pat}\n"));
+    }
+    if let Some(to) = &view.renamed_to {
+        out.push_str(&format!("rename {} {to}\n", view.skill));
+    }
+    if !view.collides_with.is_empty() {
+        out.push_str(&format!("collides {}\n", view.collides_with.join(" ")));
+    }
+    match (&view.install_name, &view.reason) {
+        (Some(name), _) => out.push_str(&format!("installs {name}\n")),
+        (None, Some(reason)) => out.push_str(&format!("absent {reason}\n")),
+        (None, None) => {}
+    }
+    out
+}
+
+/// A skill's frontmatter description, or its name when there is no description.
+fn show_summary<'a>(view: &'a ShowView, id: &str) -> Option<&'a str> {
+    let metadata = view.metadata.get(id)?;
+    metadata.description.as_deref().or(metadata.name.as_deref())
+}
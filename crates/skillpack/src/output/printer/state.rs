@@ -0,0 +1,319 @@
+use super::{Output, TextFormat};
+use crate::output::helpers::abbreviate_path;
+use crate::output::types::{
+    DoctorView, GroupBy, InstalledGroupsView, InstalledView, MigrateStateView, OrphansView,
+    StatusView,
+};
+use owo_colors::OwoColorize;
+use std::io;
+
+impl Output {
+    pub fn print_installed(&self, view: &InstalledView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for record in &view.installs {
+                    out.push_str(&format!(
+                        "{} {} {} {} {}{}",
+                        record.sink,
+                        record.pack,
+                        record.skill_count,
+                        record.installed_at,
+                        record.sink_path,
+                        self.record_end()
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Installed".style(self.styles.header())));
+
+                if view.installs.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No packs installed".style(self.styles.path())
+                    ));
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "Run: sp install <pack> --codex".style(self.styles.path())
+                    ));
+                } else {
+                    for record in &view.installs {
+                        let frozen = if record.frozen { " frozen" } else { "" };
+                        out.push_str(&format!(
+                            "  {} {} {} {}{}\n",
+                            record.pack.style(self.styles.name()),
+                            format!("→ {}", record.sink).style(self.styles.path()),
+                            format!("({} skills)", record.skill_count).style(self.styles.count()),
+                            record.installed_at.as_str().style(self.styles.path()),
+                            frozen.style(self.styles.label())
+                        ));
+                        out.push_str(&format!(
+                            "    {}\n",
+                            abbreviate_path(&record.sink_path).style(self.styles.path())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_installed_groups(&self, view: &InstalledGroupsView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for (key, items) in &view.installs {
+                    for record in items {
+                        out.push_str(&format!(
+                            "{} {} {} {} {} {}{}",
+                            key,
+                            record.sink,
+                            record.pack,
+                            record.skill_count,
+                            record.installed_at,
+                            record.sink_path,
+                            self.record_end()
+                        ));
+                    }
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Installed".style(self.styles.header())));
+                if view.installs.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n\n",
+                        "No packs installed".style(self.styles.path())
+                    ));
+                    return out;
+                }
+                for (key, items) in &view.installs {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        key.style(self.styles.header()),
+                        format!("({})", items.len()).style(self.styles.count())
+                    ));
+                    for record in items {
+                        // The group key already names one side; show the other.
+                        let other = match view.group_by {
+                            GroupBy::Sink => record.pack.style(self.styles.name()).to_string(),
+                            GroupBy::Pack => format!("→ {}", record.sink)
+                                .style(self.styles.path())
+                                .to_string(),
+                        };
+                        let frozen = if record.frozen { " frozen" } else { "" };
+                        out.push_str(&format!(
+                            "    {} {} {}{}\n",
+                            other,
+                            format!("({} skills)", record.skill_count).style(self.styles.count()),
+                            abbreviate_path(&record.sink_path).style(self.styles.path()),
+                            frozen.style(self.styles.label())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_status(&self, view: &StatusView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for item in &view.sinks {
+                    let stale = item
+                        .stale
+                        .as_ref()
+                        .map_or_else(|| "-".to_string(), |packs| packs.len().to_string());
+                    out.push_str(&format!(
+                        "{} {} {} {} {} {}{}",
+                        item.sink,
+                        item.packs,
+                        item.skills,
+                        item.missing.len(),
+                        stale,
+                        item.sink_path,
+                        self.record_end()
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Status".style(self.styles.header())));
+                for item in &view.sinks {
+                    out.push_str(&format!(
+                        "  {} {} {}\n",
+                        item.sink.style(self.styles.name()),
+                        format!("({} packs, {} skills)", item.packs, item.skills)
+                            .style(self.styles.count()),
+                        abbreviate_path(&item.sink_path).style(self.styles.path())
+                    ));
+                    for path in &item.missing {
+                        out.push_str(&format!(
+                            "    {} {} {}\n",
+                            "✗".style(self.styles.error()),
+                            "missing".style(self.styles.error()),
+                            abbreviate_path(path).style(self.styles.path())
+                        ));
+                    }
+                    for pack in item.stale.iter().flatten() {
+                        out.push_str(&format!(
+                            "    {} {} {}\n",
+                            "!".style(self.styles.warning()),
+                            pack.style(self.styles.name()),
+                            "has newer commits (run sp update)".style(self.styles.path())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_migrate_state(&self, view: &MigrateStateView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for item in &view.records {
+                    out.push_str(&format!(
+                        "{} {} {} {} {}{}",
+                        item.sink,
+                        item.pack,
+                        item.paths,
+                        item.old_sink_path,
+                        item.sink_path,
+                        self.record_end()
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                let title = if view.dry_run {
+                    "Would migrate state"
+                } else {
+                    "Migrated state"
+                };
+                out.push_str(&format!(
+                    "{} {} → {}\n\n",
+                    title.style(self.styles.header()),
+                    abbreviate_path(&view.from_prefix).style(self.styles.path()),
+                    abbreviate_path(&view.to_prefix).style(self.styles.path())
+                ));
+                if view.records.is_empty() {
+                    out.push_str(&format!(
+                        "  {} no recorded paths under {}\n\n",
+                        "•".style(self.styles.label()),
+                        abbreviate_path(&view.from_prefix).style(self.styles.path())
+                    ));
+                    return out;
+                }
+                let verb = if view.moved { "moved" } else { "rewritten" };
+                for item in &view.records {
+                    out.push_str(&format!(
+                        "  {} {} {} {}\n",
+                        item.pack.style(self.styles.name()),
+                        format!("({})", item.sink).style(self.styles.label()),
+                        abbreviate_path(&item.sink_path).style(self.styles.path()),
+                        format!("{} paths {verb}", item.paths).style(self.styles.count())
+                    ));
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_orphans(&self, view: &OrphansView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for orphan in &view.orphans {
+                    out.push_str(&orphan.path);
+                    out.push('\n');
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Orphans".style(self.styles.header())));
+
+                if view.orphans.is_empty() {
+                    out.push_str(&format!(
+                        "  {}\n",
+                        "No orphaned folders".style(self.styles.path())
+                    ));
+                } else {
+                    for orphan in &view.orphans {
+                        out.push_str(&format!(
+                            "  {} {}\n",
+                            abbreviate_path(&orphan.path).style(self.styles.name()),
+                            format!("→ {}", orphan.sink).style(self.styles.path())
+                        ));
+                    }
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+
+    pub fn print_doctor(&self, view: &DoctorView) -> io::Result<()> {
+        self.emit(view, |format| match format {
+            TextFormat::Plain => {
+                let mut out = String::new();
+                for finding in &view.findings {
+                    out.push_str(&format!(
+                        "{} {} {}\n",
+                        finding.kind,
+                        finding.path,
+                        finding.fix.as_deref().unwrap_or("-")
+                    ));
+                }
+                out
+            }
+            TextFormat::Pretty => {
+                let mut out = String::new();
+                out.push_str(&format!("{}\n\n", "Doctor".style(self.styles.header())));
+                if view.findings.is_empty() {
+                    out.push_str(&format!(
+                        "  {} {}\n",
+                        "✓".style(self.styles.success()),
+                        "No problems found".style(self.styles.path())
+                    ));
+                }
+                for finding in &view.findings {
+                    let mark = if finding.fix.as_deref() == Some("fixed") {
+                        "✓".style(self.styles.success()).to_string()
+                    } else {
+                        "!".style(self.styles.warning()).to_string()
+                    };
+                    out.push_str(&format!(
+                        "  {mark} {} {}\n",
+                        finding.message,
+                        abbreviate_path(&finding.path).style(self.styles.path())
+                    ));
+                    if let Some(fix) = &finding.fix {
+                        out.push_str(&format!("    {}\n", fix.style(self.styles.label())));
+                    }
+                }
+                if !view.fix && !view.findings.is_empty() {
+                    out.push_str(&format!(
+                        "\n  {}\n",
+                        "Run sp doctor --fix to repair (add --yes to delete cached repos)"
+                            .style(self.styles.path())
+                    ));
+                }
+                out.push('\n');
+                out
+            }
+        })
+    }
+}
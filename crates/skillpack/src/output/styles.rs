@@ -1,3 +1,4 @@
+use super::types::ColorChoice;
 use owo_colors::Style;
 use std::io::IsTerminal;
 
@@ -7,9 +8,14 @@ pub(crate) struct Styles {
 }
 
 impl Styles {
-    pub(crate) fn new(no_color: bool) -> Self {
-        let use_color =
-            !no_color && std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    pub(crate) fn new(color: ColorChoice) -> Self {
+        let use_color = match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+            }
+        };
         Self { use_color }
     }
 
@@ -68,4 +74,20 @@ impl Styles {
             Style::new()
         }
     }
+
+    pub(crate) fn error(&self) -> Style {
+        if self.use_color {
+            Style::new().red()
+        } else {
+            Style::new()
+        }
+    }
+
+    pub(crate) fn warning(&self) -> Style {
+        if self.use_color {
+            Style::new().yellow().bold()
+        } else {
+            Style::new()
+        }
+    }
 }
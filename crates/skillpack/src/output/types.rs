@@ -1,17 +1,52 @@
+//! Serializable views behind `--format json`.
+//!
+//! Output must be byte-stable across runs: use `BTreeMap` (never `HashMap`) for maps
+//! and sort lists before building a view.
+
+use crate::config::ConfigFinding;
+use crate::discover::SkillMetadata;
+use crate::pack::PrefixMode;
 use clap::ValueEnum;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum)]
 pub enum OutputFormat {
     Pretty,
     Plain,
     Json,
+    /// Same documents as `json`, each on a single line.
+    CompactJson,
+}
+
+/// When to emit ANSI colors.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How `sp installed --group-by` keys its listing.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Sink,
+    Pack,
 }
 
 #[derive(Debug, Serialize)]
 pub struct PackSummary {
     pub name: String,
     pub path: String,
+    /// Local skill ids; only filled by `sp packs --with-skills`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skills: Option<Vec<String>>,
+    /// Number of imports (not resolved); only filled by `sp packs --with-skills`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imports: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -21,14 +56,20 @@ pub struct PackInfo {
     pub prefix: String,
     pub sep: String,
     pub flatten: bool,
+    pub prefix_mode: PrefixMode,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ImportView {
     pub repo: String,
     pub reference: Option<String>,
+    /// Branch or tag at `commit` when `reference` is a commit hash.
+    pub resolved_ref: Option<String>,
     pub commit: String,
     pub skills: Vec<String>,
+    /// `reference` came from `--repo-ref` instead of the pack.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ref_overridden: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,6 +78,54 @@ pub struct ShowView {
     pub local: Vec<String>,
     pub imports: Vec<ImportView>,
     pub final_install_names: Vec<String>,
+    /// `[original id, renamed id]` pairs from the pack's `rename:` map.
+    pub renames: Vec<(String, String)>,
+    /// `[install_name, [skill ids]]` groups; only populated with `--allow-collisions`.
+    pub collisions: Vec<(String, Vec<String>)>,
+    /// `[pattern, [removed ids]]` per exclude; only populated with `--explain-excludes`.
+    pub excluded: Vec<(String, Vec<String>)>,
+    /// Skill id → frontmatter; only populated with `--with-metadata`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, SkillMetadata>,
+    /// Per-import skill limit for pretty output; JSON always has the full lists.
+    #[serde(skip)]
+    pub max_skills: Option<usize>,
+    /// Plain output without section labels or rename/exclude/collision lines.
+    #[serde(skip)]
+    pub bare: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainSource {
+    pub source: String,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub selected: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainView {
+    pub pack: String,
+    pub skill: String,
+    pub included: bool,
+    pub sources: Vec<ExplainSource>,
+    pub exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_name: Option<String>,
+    /// Other final skill ids that map to the same install name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub collides_with: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// `sp show --explain`: one decision record per skill any include matched, sorted by id.
+#[derive(Debug, Serialize)]
+pub struct ShowExplainView {
+    pub pack: String,
+    pub skills: Vec<ExplainView>,
 }
 
 #[derive(Debug, Serialize)]
@@ -47,6 +136,9 @@ pub struct InstallView {
     pub added: usize,
     pub updated: usize,
     pub removed: usize,
+    pub added_names: Vec<String>,
+    pub updated_names: Vec<String>,
+    pub removed_names: Vec<String>,
     pub installed_paths: Vec<String>,
 }
 
@@ -56,6 +148,15 @@ pub struct UninstallView {
     pub sink: String,
     pub sink_path: String,
     pub removed: usize,
+    /// Recorded folders left on disk but no longer tracked (`--keep-files`).
+    pub unmanaged: usize,
+    /// Recorded folders that were already gone, e.g. deleted by hand.
+    pub missing: usize,
+    /// Recorded skill folders removed or left unmanaged (or, under `dry_run`, to be).
+    pub paths: Vec<String>,
+    pub was_installed: bool,
+    pub pruned_sink: bool,
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -65,6 +166,7 @@ pub struct InstalledItem {
     pub skill_count: usize,
     pub installed_at: String,
     pub sink_path: String,
+    pub frozen: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,10 +174,156 @@ pub struct InstalledView {
     pub installs: Vec<InstalledItem>,
 }
 
+/// `sp installed --group-by`: the same items keyed by sink or pack name.
+#[derive(Debug, Serialize)]
+pub struct InstalledGroupsView {
+    pub group_by: GroupBy,
+    pub installs: BTreeMap<String, Vec<InstalledItem>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusSinkItem {
+    pub sink: String,
+    pub sink_path: String,
+    pub packs: usize,
+    pub skills: usize,
+    /// Recorded install paths that no longer exist on disk.
+    pub missing: Vec<String>,
+    /// Packs whose imports have newer commits; only set with `--check-updates`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusView {
+    pub sinks: Vec<StatusSinkItem>,
+}
+
+/// One record `sp migrate-state` rewrote; `paths` counts its rewritten skill folders.
+#[derive(Debug, Serialize)]
+pub struct MigrateItem {
+    pub sink: String,
+    pub pack: String,
+    pub old_sink_path: String,
+    pub sink_path: String,
+    pub paths: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrateStateView {
+    pub from_prefix: String,
+    pub to_prefix: String,
+    /// Whether skill folders were (or, under `dry_run`, would be) moved as well.
+    pub moved: bool,
+    pub dry_run: bool,
+    pub records: Vec<MigrateItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigCheckView {
+    pub config_path: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub findings: Vec<ConfigFinding>,
+}
+
+/// `sp show <pack> --diff-against <other>`: final skill ids split by which pack has them.
+#[derive(Debug, Serialize)]
+pub struct PackDiffView {
+    pub pack: String,
+    pub other: String,
+    pub only_in_pack: Vec<String>,
+    pub only_in_other: Vec<String>,
+    pub in_both: Vec<String>,
+}
+
+/// One `install --watch` round (the changed skills that triggered it) or one
+/// `sp reinstall` (every skill in the pack), and the sinks reinstalled.
+#[derive(Debug, Serialize)]
+pub struct ReinstallView {
+    pub pack: String,
+    pub skills: Vec<String>,
+    pub sinks: Vec<String>,
+}
+
+/// One problem `sp doctor` found and, under `--fix`, what happened to it.
+#[derive(Debug, Serialize)]
+pub struct DoctorFinding {
+    /// `dangling-record`, `orphaned-cache`, or `missing-dir`.
+    pub kind: String,
+    pub path: String,
+    pub message: String,
+    /// `fixed`, or `needs --yes` for a fix that deletes files; unset without `--fix`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DoctorView {
+    pub fix: bool,
+    pub findings: Vec<DoctorFinding>,
+}
+
+/// One `--timings` phase, summed across every pack and sink the command touched.
+#[derive(Debug, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub ms: f64,
+}
+
+/// `--timings`: phase durations in the order each phase first ran.
+#[derive(Debug, Serialize)]
+pub struct TimingsView {
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CachePathView {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheSizeView {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheRepoItem {
+    pub path: String,
+    pub origin: Option<String>,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheListView {
+    pub path: String,
+    pub repos: Vec<CacheRepoItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphanItem {
+    pub sink: String,
+    pub sink_path: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphansView {
+    pub orphans: Vec<OrphanItem>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SinkView {
     pub name: String,
     pub path: String,
+    /// Where the path came from: `default`, `config`, or `env`.
+    pub source: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AgentsView {
+    pub agents: Vec<SinkView>,
 }
 
 #[derive(Debug, Serialize)]
@@ -98,3 +346,41 @@ pub struct SwitchSinkView {
 pub struct SwitchView {
     pub sinks: Vec<SwitchSinkView>,
 }
+
+/// What `sp update` did with one recorded install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateStatus {
+    Updated,
+    Unchanged,
+    Skipped,
+    Failed,
+}
+
+impl UpdateStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateStatus::Updated => "updated",
+            UpdateStatus::Unchanged => "unchanged",
+            UpdateStatus::Skipped => "skipped",
+            UpdateStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateItem {
+    pub sink: String,
+    pub pack: String,
+    pub status: UpdateStatus,
+    pub reason: Option<String>,
+    pub skill_count: usize,
+    /// Skill folders added, recopied, or removed by this update.
+    pub changed: usize,
+    pub sink_path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpdateView {
+    pub updates: Vec<UpdateItem>,
+}
@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Soft problems noticed while a command runs (zero-match excludes, dropped
+/// duplicates, ...). JSON documents carry them as a `warnings` array and pretty
+/// output lists them after the view; plain output leaves them to the log.
+#[derive(Debug, Default)]
+pub struct WarningSink {
+    messages: Mutex<Vec<String>>,
+    log: bool,
+}
+
+impl WarningSink {
+    pub fn new(log: bool) -> Self {
+        Self {
+            messages: Mutex::new(Vec::new()),
+            log,
+        }
+    }
+
+    pub fn push(&self, message: String) {
+        if self.log {
+            warn!("{message}");
+        }
+        self.messages.lock().unwrap().push(message);
+    }
+
+    /// Drains the collected messages, so each is reported with one document.
+    pub fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.messages.lock().unwrap())
+    }
+}
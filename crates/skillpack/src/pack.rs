@@ -1,26 +1,57 @@
+mod includes;
+mod validate;
+
+pub(crate) use validate::validate_install_component;
+
+use includes::{normalize_patterns, read_include_list, split_includes};
+use validate::{validate_pack, validate_rename, validate_subdir};
+
 use color_eyre::Section as _;
 use color_eyre::eyre::{Result, WrapErr, eyre};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use crate::bundled::bundled_pack_path;
+use crate::config::PackDefaults;
+use crate::util::install_name;
 
 #[derive(Debug, Deserialize)]
 struct PackFile {
     name: String,
     #[serde(default)]
-    include: Vec<String>,
+    include: Vec<IncludeEntry>,
+    include_from: Option<String>,
     exclude: Option<Vec<String>>,
     imports: Option<Vec<ImportSpec>>,
     install: Option<InstallSpec>,
+    #[serde(default)]
+    rename: BTreeMap<String, String>,
+    #[serde(default)]
+    agents: Vec<String>,
+    #[serde(default)]
+    requires: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// An `include:` entry: a plain pattern, or `{ pattern, agents }` to select the
+/// matching skills only when installing for those agents.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum IncludeEntry {
+    Pattern(String),
+    Scoped {
+        pattern: String,
+        agents: Vec<String>,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ImportSpec {
     pub repo: String,
-    #[serde(rename = "ref")]
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
     pub ref_name: Option<String>,
     pub include: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub exclude: Option<Vec<String>>,
 }
 
@@ -29,20 +60,142 @@ pub struct InstallSpec {
     pub prefix: Option<String>,
     pub sep: Option<String>,
     pub flatten: Option<bool>,
+    pub subdir: Option<String>,
+    pub prefix_mode: Option<PrefixMode>,
+    pub preserve_on_reconcile: Option<Vec<String>>,
+}
+
+/// Where an install name's prefix comes from.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrefixMode {
+    /// `install.prefix` (default: the pack name) for every skill.
+    #[default]
+    Single,
+    /// Each skill id's first segment, so `coding/dotnet/efcore` installs as
+    /// `coding__dotnet__efcore`.
+    FirstSegment,
 }
 
+/// Pattern or skill id → the agents it is limited to.
+pub type AgentScopes = BTreeMap<String, Vec<String>>;
+
 #[derive(Debug, Clone)]
 pub struct Pack {
     pub name: String,
+    /// Every local include pattern, agent-scoped ones included.
     pub include: Vec<String>,
+    /// Include pattern → the only agents its skills install for.
+    pub include_agents: AgentScopes,
     pub exclude: Vec<String>,
     pub imports: Vec<ImportSpec>,
     pub install_prefix: String,
     pub install_sep: String,
     pub install_flatten: bool,
+    pub install_prefix_mode: PrefixMode,
+    /// Relative folder under the sink that skill folders go into (`packs/demo`).
+    pub install_subdir: Option<String>,
+    /// Globs for paths inside an installed folder (`.env`, `local/**`) that
+    /// reinstalls and reconcile leave in place.
+    pub install_preserve: Vec<String>,
+    /// Skill id → id to install it under.
+    pub rename: BTreeMap<String, String>,
+    /// Agents `sp install` targets when no agent flag is given.
+    pub agents: Vec<String>,
+    /// Packs that must already be installed in a sink before this one.
+    pub requires: Vec<String>,
+    /// Problems noticed while loading that are not fatal (e.g. duplicate patterns).
+    pub warnings: Vec<String>,
+}
+
+impl Pack {
+    /// Folder name `id` installs under, following the pack's `install:` settings.
+    pub fn install_name(&self, id: &str) -> String {
+        match self.install_prefix_mode {
+            PrefixMode::Single => install_name(
+                &self.install_prefix,
+                &self.install_sep,
+                id,
+                self.install_flatten,
+            ),
+            PrefixMode::FirstSegment => match id.split_once('/') {
+                Some((first, rest)) => {
+                    install_name(first, &self.install_sep, rest, self.install_flatten)
+                }
+                // No category to group by, so fall back to the pack's own prefix.
+                None => install_name(
+                    &self.install_prefix,
+                    &self.install_sep,
+                    id,
+                    self.install_flatten,
+                ),
+            },
+        }
+    }
+}
+
+/// A loaded pack written back as pack YAML, with every default filled in
+/// (`sp normalize`).
+#[derive(Debug, Serialize)]
+pub struct NormalizedPack {
+    pub name: String,
+    pub include: Vec<IncludeEntry>,
+    pub exclude: Vec<String>,
+    pub imports: Vec<ImportSpec>,
+    pub install: NormalizedInstall,
+    pub rename: BTreeMap<String, String>,
+    pub agents: Vec<String>,
+    pub requires: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizedInstall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    pub sep: String,
+    pub flatten: bool,
+    pub prefix_mode: PrefixMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preserve_on_reconcile: Vec<String>,
+}
+
+impl From<&Pack> for NormalizedPack {
+    fn from(pack: &Pack) -> Self {
+        Self {
+            name: pack.name.clone(),
+            include: pack
+                .include
+                .iter()
+                .map(|pattern| match pack.include_agents.get(pattern) {
+                    Some(agents) => IncludeEntry::Scoped {
+                        pattern: pattern.clone(),
+                        agents: agents.clone(),
+                    },
+                    None => IncludeEntry::Pattern(pattern.clone()),
+                })
+                .collect(),
+            exclude: pack.exclude.clone(),
+            imports: pack.imports.clone(),
+            install: NormalizedInstall {
+                // A prefix is an error alongside first-segment, so leave it out there.
+                prefix: (pack.install_prefix_mode == PrefixMode::Single)
+                    .then(|| pack.install_prefix.clone()),
+                sep: pack.install_sep.clone(),
+                flatten: pack.install_flatten,
+                prefix_mode: pack.install_prefix_mode,
+                subdir: pack.install_subdir.clone(),
+                preserve_on_reconcile: pack.install_preserve.clone(),
+            },
+            rename: pack.rename.clone(),
+            agents: pack.agents.clone(),
+            requires: pack.requires.clone(),
+        }
+    }
 }
 
-pub fn resolve_pack_path(repo_root: &Path, pack_arg: &str) -> Result<PathBuf> {
+pub fn resolve_pack_path(repo_root: &Path, pack_arg: &str, no_bundled: bool) -> Result<PathBuf> {
     let candidate = Path::new(pack_arg);
     if candidate.exists() {
         return Ok(candidate.to_path_buf());
@@ -59,7 +212,7 @@ pub fn resolve_pack_path(repo_root: &Path, pack_arg: &str) -> Result<PathBuf> {
     }
     let pack_path = repo_root.join("packs").join(format!("{pack_arg}.yaml"));
     if !pack_path.exists() {
-        if let Some(path) = bundled_pack_path(pack_arg)? {
+        if let Some(path) = bundled_pack_path(pack_arg, no_bundled)? {
             return Ok(path);
         }
         return Err(eyre!("pack not found: {pack_arg}").suggestion(format!(
@@ -70,11 +223,64 @@ pub fn resolve_pack_path(repo_root: &Path, pack_arg: &str) -> Result<PathBuf> {
     Ok(pack_path)
 }
 
-pub fn load_pack(pack_path: &Path) -> Result<Pack> {
+/// Splits a `<repo>#<pack>` argument naming a pack in a remote git repo. Callers
+/// try the argument as a local pack first, so a local file with `#` in its name
+/// still works.
+pub fn split_remote_pack(pack_arg: &str) -> Option<(&str, &str)> {
+    let (repo, pack) = pack_arg.rsplit_once('#')?;
+    (!repo.is_empty() && !pack.is_empty()).then_some((repo, pack))
+}
+
+/// Recorded as `pack_file` for packs read from stdin (`sp install -`).
+pub const STDIN_PACK_FILE: &str = "<stdin>";
+
+/// Loads a pack file; settings it leaves out fall back to `defaults`
+/// (see [`crate::config::load_pack_defaults`]).
+pub fn load_pack(pack_path: &Path, defaults: &PackDefaults) -> Result<Pack> {
     let content = std::fs::read_to_string(pack_path)
         .wrap_err_with(|| format!("failed to read pack file: {}", pack_path.display()))?;
-    let parsed: PackFile = serde_yaml::from_str(&content)
-        .wrap_err_with(|| format!("failed to parse pack file: {}", pack_path.display()))?;
+    let base_dir = pack_path.parent().unwrap_or(Path::new("."));
+    parse_pack(
+        &content,
+        &pack_path.display().to_string(),
+        base_dir,
+        defaults,
+    )
+}
+
+/// Parses pack YAML; `source` names the origin in error messages. An
+/// `include_from:` file is read relative to the current directory.
+pub fn load_pack_str(content: &str, source: &str, defaults: &PackDefaults) -> Result<Pack> {
+    parse_pack(content, source, &std::env::current_dir()?, defaults)
+}
+
+/// Settings a pack leaves out fall back to `defaults` from `config.yaml`, then to
+/// the built-in defaults.
+fn parse_pack(
+    content: &str,
+    source: &str,
+    base_dir: &Path,
+    defaults: &PackDefaults,
+) -> Result<Pack> {
+    let mut parsed: PackFile = serde_yaml::from_str(content)
+        .wrap_err_with(|| format!("failed to parse pack file: {source}"))?;
+    let (mut include, include_agents) = split_includes(&parsed.include)?;
+    if let Some(list) = &parsed.include_from {
+        include.extend(read_include_list(base_dir, list)?);
+    }
+    let mut warnings = Vec::new();
+    normalize_patterns("include", &mut include, &mut warnings);
+    if let Some(exclude) = parsed.exclude.as_mut() {
+        normalize_patterns("exclude", exclude, &mut warnings);
+    }
+    for import in parsed.imports.iter_mut().flatten() {
+        let field = format!("import {} include", import.repo);
+        normalize_patterns(&field, &mut import.include, &mut warnings);
+        if let Some(exclude) = import.exclude.as_mut() {
+            let field = format!("import {} exclude", import.repo);
+            normalize_patterns(&field, exclude, &mut warnings);
+        }
+    }
     validate_pack(&parsed)?;
     let install_prefix = parsed
         .install
@@ -85,59 +291,81 @@ pub fn load_pack(pack_path: &Path) -> Result<Pack> {
         .install
         .as_ref()
         .and_then(|i| i.sep.clone())
+        .or_else(|| defaults.sep.clone())
         .unwrap_or_else(|| "__".to_string());
     let install_flatten = parsed
         .install
         .as_ref()
         .and_then(|i| i.flatten)
         .unwrap_or(false);
+    let pack_prefix = parsed.install.as_ref().and_then(|i| i.prefix.as_ref());
+    let install_prefix_mode = parsed
+        .install
+        .as_ref()
+        .and_then(|i| i.prefix_mode)
+        // An explicit install.prefix means this pack wants a single prefix.
+        .or_else(|| defaults.prefix_style.filter(|_| pack_prefix.is_none()))
+        .unwrap_or_default();
+    if install_prefix_mode == PrefixMode::FirstSegment && pack_prefix.is_some() {
+        return Err(
+            eyre!("install.prefix has no effect with install.prefix_mode: first-segment")
+                .suggestion("Drop one of install.prefix and install.prefix_mode"),
+        );
+    }
+    let install_subdir = parsed
+        .install
+        .as_ref()
+        .and_then(|i| i.subdir.as_deref())
+        .map(|subdir| subdir.trim().trim_matches('/').to_string());
+    validate_install_component("install.prefix", &install_prefix)?;
+    validate_install_component("install.sep", &install_sep)?;
+    if install_sep.is_empty() && !install_flatten {
+        return Err(
+            eyre!("install.sep is empty, so skill id segments would run together").suggestion(
+                "Set a non-empty install.sep, or install.flatten: true to keep only leaf names",
+            ),
+        );
+    }
+    if let Some(subdir) = &install_subdir {
+        validate_subdir(subdir)?;
+    }
+    let mut install_preserve = parsed
+        .install
+        .as_ref()
+        .and_then(|i| i.preserve_on_reconcile.clone())
+        .unwrap_or_default();
+    normalize_patterns(
+        "install.preserve_on_reconcile",
+        &mut install_preserve,
+        &mut warnings,
+    );
+    for (from, to) in &parsed.rename {
+        validate_rename(from, to)?;
+    }
 
     Ok(Pack {
         name: parsed.name,
-        include: parsed.include,
+        include,
+        include_agents,
         exclude: parsed.exclude.unwrap_or_default(),
         imports: parsed.imports.unwrap_or_default(),
         install_prefix,
         install_sep,
         install_flatten,
+        install_prefix_mode,
+        install_subdir,
+        install_preserve,
+        rename: parsed.rename,
+        agents: parsed.agents,
+        requires: parsed.requires,
+        warnings,
     })
 }
 
-fn validate_pack(pack: &PackFile) -> Result<()> {
-    if pack.name.trim().is_empty() {
-        return Err(
-            eyre!("pack name is required").suggestion("Set name: <pack-name> in the pack file")
-        );
-    }
-    let has_local = !pack.include.is_empty();
-    let has_imports = pack
-        .imports
-        .as_ref()
-        .map(|imports| !imports.is_empty())
-        .unwrap_or(false);
-    if !has_local && !has_imports {
-        return Err(eyre!("pack must include local skills or imports")
-            .suggestion("Add include: or imports: to the pack file"));
-    }
-    if let Some(imports) = &pack.imports {
-        for import in imports {
-            if import.repo.trim().is_empty() {
-                return Err(
-                    eyre!("import repo is required").suggestion("Set repo: <git-url> in imports")
-                );
-            }
-            if import.include.is_empty() {
-                return Err(eyre!("import include must be non-empty")
-                    .suggestion("Add include: patterns under the import"));
-            }
-        }
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
-    use super::load_pack;
+    use super::{PrefixMode, load_pack, load_pack_str, parse_pack};
+    use crate::config::PackDefaults;
     use assert_fs::prelude::*;
 
     #[test]
@@ -147,12 +375,53 @@ mod tests {
         pack.write_str("name: demo\ninclude:\n  - general/**\n")
             .unwrap();
 
-        let loaded = load_pack(pack.path()).unwrap();
+        let loaded = load_pack(pack.path(), &PackDefaults::default()).unwrap();
         assert_eq!(loaded.install_prefix, "demo");
         assert_eq!(loaded.install_sep, "__");
         assert!(!loaded.install_flatten);
     }
 
+    #[test]
+    fn config_defaults_fill_in_omitted_install_settings() {
+        let defaults = PackDefaults {
+            sep: Some(".".to_string()),
+            prefix_style: Some(PrefixMode::FirstSegment),
+        };
+        let parse = |yaml: &str| parse_pack(yaml, "test", std::path::Path::new("."), &defaults);
+
+        let loaded = parse("name: demo\ninclude:\n  - general/**\n").unwrap();
+        assert_eq!(loaded.install_sep, ".");
+        assert_eq!(loaded.install_prefix_mode, PrefixMode::FirstSegment);
+
+        let loaded =
+            parse("name: demo\ninclude:\n  - general/**\ninstall:\n  sep: \"-\"\n  prefix: x\n")
+                .unwrap();
+        assert_eq!(loaded.install_sep, "-");
+        assert_eq!(loaded.install_prefix_mode, PrefixMode::Single);
+        assert_eq!(loaded.install_name("general/writing"), "x-general-writing");
+    }
+
+    #[test]
+    fn load_pack_merges_include_from_file() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("packs/skills.txt")
+            .write_str("# generated\ncoding/**\n\n  tools/lint  \ngeneral/**\n")
+            .unwrap();
+        let pack = temp.child("packs/demo.yaml");
+        pack.write_str("name: demo\ninclude:\n  - general/**\ninclude_from: skills.txt\n")
+            .unwrap();
+
+        let loaded = load_pack(pack.path(), &PackDefaults::default()).unwrap();
+        assert_eq!(
+            loaded.include,
+            vec!["general/**", "coding/**", "tools/lint"]
+        );
+
+        std::fs::remove_file(temp.child("packs/skills.txt").path()).unwrap();
+        let err = load_pack(pack.path(), &PackDefaults::default()).unwrap_err();
+        assert!(err.to_string().contains("include_from"));
+    }
+
     #[test]
     fn load_pack_flatten_true() {
         let temp = assert_fs::TempDir::new().unwrap();
@@ -160,7 +429,91 @@ mod tests {
         pack.write_str("name: demo\ninclude:\n  - general/**\ninstall:\n  flatten: true\n")
             .unwrap();
 
-        let loaded = load_pack(pack.path()).unwrap();
+        let loaded = load_pack(pack.path(), &PackDefaults::default()).unwrap();
         assert!(loaded.install_flatten);
     }
+
+    #[test]
+    fn load_pack_collapses_duplicate_patterns() {
+        let loaded = load_pack_str(
+            "name: demo\ninclude:\n  - general/**\n  - \" general/** \"\n  - tools/\n  - tools\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap();
+        assert_eq!(loaded.include, vec!["general/**", "tools"]);
+        assert_eq!(
+            loaded.warnings,
+            vec![
+                "duplicate include pattern dropped: general/**",
+                "duplicate include pattern dropped: tools",
+            ]
+        );
+    }
+
+    #[test]
+    fn load_pack_rejects_rename_escaping_sink() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let pack = temp.child("pack.yaml");
+        pack.write_str("name: demo\ninclude:\n  - general/**\nrename:\n  general/a: ../a\n")
+            .unwrap();
+
+        let err = load_pack(pack.path(), &PackDefaults::default()).unwrap_err();
+        assert!(err.to_string().contains("not a valid skill id"));
+    }
+
+    #[test]
+    fn load_pack_rejects_unsafe_sep() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let pack = temp.child("pack.yaml");
+        pack.write_str("name: demo\ninclude:\n  - general/**\ninstall:\n  sep: \"/\"\n")
+            .unwrap();
+
+        let err = load_pack(pack.path(), &PackDefaults::default()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("install.sep contains invalid character")
+        );
+    }
+
+    #[test]
+    fn load_pack_rejects_subdir_escaping_sink() {
+        let err = load_pack_str(
+            "name: demo\ninclude:\n  - general/**\ninstall:\n  subdir: packs/../..\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("install.subdir"));
+    }
+
+    #[test]
+    fn load_pack_rejects_empty_sep_without_flatten() {
+        let err = load_pack_str(
+            "name: demo\ninclude:\n  - general/**\ninstall:\n  sep: \"\"\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("install.sep is empty"));
+
+        let loaded = load_pack_str(
+            "name: demo\ninclude:\n  - general/**\ninstall:\n  sep: \"\"\n  flatten: true\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap();
+        assert_eq!(loaded.install_name("general/writing"), "demowriting");
+    }
+
+    #[test]
+    fn load_pack_accepts_safe_sep() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let pack = temp.child("pack.yaml");
+        pack.write_str("name: demo\ninclude:\n  - general/**\ninstall:\n  sep: \"-\"\n")
+            .unwrap();
+
+        let loaded = load_pack(pack.path(), &PackDefaults::default()).unwrap();
+        assert_eq!(loaded.install_sep, "-");
+    }
 }
@@ -0,0 +1,88 @@
+//! `include:` entries, `include_from:` lists and pattern normalization.
+
+use super::validate::validate_agents;
+use super::{AgentScopes, IncludeEntry};
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Flattens `include:` entries into patterns plus the agent scope of each
+/// scoped one. A pattern that is also listed plainly applies to every agent.
+pub(super) fn split_includes(entries: &[IncludeEntry]) -> Result<(Vec<String>, AgentScopes)> {
+    let mut include = Vec::with_capacity(entries.len());
+    let mut scoped = AgentScopes::new();
+    let mut plain = HashSet::new();
+    for entry in entries {
+        match entry {
+            IncludeEntry::Pattern(pattern) => {
+                plain.insert(normalize_pattern(pattern));
+                include.push(pattern.clone());
+            }
+            IncludeEntry::Scoped { pattern, agents } => {
+                if agents.is_empty() {
+                    return Err(
+                        eyre!("include {pattern} has an empty agents: list").suggestion(
+                            "List agents such as codex or claude, or use a plain pattern",
+                        ),
+                    );
+                }
+                validate_agents(agents)?;
+                let targets = scoped.entry(normalize_pattern(pattern)).or_default();
+                targets.extend(agents.iter().cloned());
+                targets.sort();
+                targets.dedup();
+                include.push(pattern.clone());
+            }
+        }
+    }
+    scoped.retain(|pattern, _| !plain.contains(pattern));
+    Ok((include, scoped))
+}
+
+/// Patterns from an `include_from:` file under `base_dir`: one per line, blank
+/// lines and `#` comments skipped.
+pub(super) fn read_include_list(base_dir: &Path, list: &str) -> Result<Vec<String>> {
+    let path = base_dir.join(list);
+    let content = std::fs::read_to_string(&path).map_err(|err| {
+        eyre!("failed to read include_from file {}: {err}", path.display()).suggestion(format!(
+            "include_from is resolved relative to {}",
+            base_dir.display()
+        ))
+    })?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Trims whitespace and surrounding slashes (`general/` → `general`), then drops
+/// repeated patterns, keeping the first occurrence.
+pub(super) fn normalize_patterns(
+    field: &str,
+    patterns: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::with_capacity(patterns.len());
+    for pattern in patterns.drain(..) {
+        let pattern = normalize_pattern(&pattern);
+        if seen.insert(pattern.clone()) {
+            normalized.push(pattern);
+        } else {
+            warnings.push(format!("duplicate {field} pattern dropped: {pattern}"));
+        }
+    }
+    *patterns = normalized;
+}
+
+fn normalize_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim();
+    // A `repo#pattern` exclude keeps its repo as written; only the id part is normalized.
+    match pattern.rsplit_once('#') {
+        Some((repo, id)) => format!("{repo}#{}", id.trim().trim_matches('/')),
+        None => pattern.trim_matches('/').to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+//! Checks on a parsed pack file and on the names it puts into sinks.
+
+use super::PackFile;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+
+pub(super) fn validate_pack(pack: &PackFile) -> Result<()> {
+    if pack.name.trim().is_empty() {
+        return Err(
+            eyre!("pack name is required").suggestion("Set name: <pack-name> in the pack file")
+        );
+    }
+    let has_local = !pack.include.is_empty();
+    let has_imports = pack
+        .imports
+        .as_ref()
+        .map(|imports| !imports.is_empty())
+        .unwrap_or(false);
+    if !has_local && !has_imports {
+        return Err(eyre!("pack must include local skills or imports")
+            .suggestion("Add include: or imports: to the pack file"));
+    }
+    if let Some(imports) = &pack.imports {
+        for import in imports {
+            if import.repo.trim().is_empty() {
+                return Err(
+                    eyre!("import repo is required").suggestion("Set repo: <git-url> in imports")
+                );
+            }
+            if import.include.is_empty() {
+                return Err(eyre!("import include must be non-empty")
+                    .suggestion("Add include: patterns under the import"));
+            }
+        }
+    }
+    validate_agents(&pack.agents)?;
+    for required in &pack.requires {
+        if required.trim().is_empty() || *required == pack.name {
+            return Err(eyre!("invalid required pack: {required:?}")
+                .suggestion("List other pack names under requires:"));
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn validate_agents(agents: &[String]) -> Result<()> {
+    for agent in agents {
+        if agent.trim().is_empty() || agent == "custom" {
+            return Err(eyre!("invalid pack agent: {agent:?}")
+                .suggestion("List configured agents such as codex or claude under agents:"));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_install_component(field: &str, value: &str) -> Result<()> {
+    let invalid = value.chars().find(|ch| {
+        ch.is_control() || matches!(ch, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*')
+    });
+    if let Some(ch) = invalid {
+        return Err(eyre!("{field} contains invalid character: {ch:?}").suggestion(
+            "Use characters that are valid in folder names (no / \\ < > : \" | ? * or control characters)",
+        ));
+    }
+    Ok(())
+}
+
+pub(super) fn validate_rename(from: &str, to: &str) -> Result<()> {
+    let bad_segment = to
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..");
+    if bad_segment {
+        return Err(
+            eyre!("rename target for {from} is not a valid skill id: {to:?}")
+                .suggestion("Use a relative id like writing or general/writing"),
+        );
+    }
+    let field = format!("rename target for {from}");
+    validate_install_component(&field, &to.replace('/', ""))
+}
+
+pub(super) fn validate_subdir(subdir: &str) -> Result<()> {
+    let bad_segment = subdir
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..");
+    if bad_segment {
+        return Err(eyre!("install.subdir is not a relative folder: {subdir:?}")
+            .suggestion("Use a path inside the sink like packs/demo"));
+    }
+    validate_install_component("install.subdir", &subdir.replace('/', ""))
+}
@@ -42,6 +42,14 @@ impl PatternSet {
             .map(|matcher| texts.iter().filter(|t| matcher.is_match(t)).count())
             .collect()
     }
+
+    /// Whether each pattern, in order, matches `text`.
+    pub fn matches_per_pattern(&self, text: &str) -> Vec<bool> {
+        self.per_pattern
+            .iter()
+            .map(|matcher| matcher.is_match(text))
+            .collect()
+    }
 }
 
 pub fn is_valid_pattern(pattern: &str) -> bool {
@@ -59,6 +67,20 @@ pub fn is_valid_pattern(pattern: &str) -> bool {
     })
 }
 
+/// Matcher for a whole-name glob such as `team-*`, `team-?`, `team-[ab]` or
+/// `{alpha,beta}`; `None` when `name` has no glob syntax and names one thing.
+/// Unlike skill patterns, every glob character is live here.
+pub fn name_glob(name: &str) -> Result<Option<GlobMatcher>> {
+    if !name.contains(['*', '?', '[', '{']) {
+        return Ok(None);
+    }
+    let glob = Glob::new(name).map_err(|err| {
+        eyre!("invalid pattern: {name}: {err}")
+            .suggestion("Use *, ?, [abc] or {a,b} to match pack names")
+    })?;
+    Ok(Some(glob.compile_matcher()))
+}
+
 pub fn match_pattern(pattern: &str, text: &str) -> bool {
     if !is_valid_pattern(pattern) {
         return false;
@@ -134,7 +156,20 @@ fn escape_pattern(pattern: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::match_pattern;
+    use super::{match_pattern, name_glob};
+
+    #[test]
+    fn name_glob_uses_every_glob_character() {
+        assert!(name_glob("team").unwrap().is_none());
+        let matches = |glob: &str, name: &str| name_glob(glob).unwrap().unwrap().is_match(name);
+        assert!(matches("team-*", "team-a"));
+        assert!(matches("team-?", "team-b"));
+        assert!(!matches("team-?", "team-ab"));
+        assert!(matches("team-[ab]", "team-a"));
+        assert!(!matches("team-[ab]", "team-c"));
+        assert!(matches("{solo,duo}", "duo"));
+        assert!(name_glob("team-[").is_err());
+    }
 
     #[test]
     fn match_pattern_segments() {
@@ -154,4 +189,24 @@ mod tests {
         assert!(!match_pattern("general/*style", "general/writing/ins"));
         assert!(!match_pattern("general/writing-style", "general/writing"));
     }
+
+    #[test]
+    fn match_pattern_without_wildcards_is_exact() {
+        let id = "tools/agent/skills/general/writing";
+        assert!(match_pattern(id, id));
+        assert!(!match_pattern(
+            id,
+            "tools/agent/skills/general/writing/extra"
+        ));
+        assert!(!match_pattern(
+            id,
+            "tools/agent/skills/general/writing-style"
+        ));
+        // A bare folder is not a prefix; `general/**` is how to select under it.
+        assert!(!match_pattern("general", "general/writing"));
+        assert!(match_pattern("general", "general"));
+        // Glob syntax other than `*` is literal.
+        assert!(match_pattern("a?b/[x]", "a?b/[x]"));
+        assert!(!match_pattern("a?b/[x]", "acb/x"));
+    }
 }
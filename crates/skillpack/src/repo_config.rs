@@ -0,0 +1,57 @@
+//! Repo-level `.skillpack.yaml`: settings for the authoring repo itself, such as
+//! extra local skills roots (`skills_roots: [skills, shared/skills]`).
+
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::Deserialize;
+use std::path::{Component, Path};
+
+pub const REPO_CONFIG_FILE: &str = ".skillpack.yaml";
+
+/// Local skills roots when `.skillpack.yaml` does not set `skills_roots`.
+pub const DEFAULT_SKILLS_ROOT: &str = "skills";
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RepoConfig {
+    #[serde(default)]
+    skills_roots: Option<Vec<String>>,
+}
+
+impl RepoConfig {
+    /// Relative folders scanned for local skills, in order.
+    pub fn skills_roots(&self) -> Vec<String> {
+        self.skills_roots
+            .clone()
+            .unwrap_or_else(|| vec![DEFAULT_SKILLS_ROOT.to_string()])
+    }
+}
+
+/// Reads `<repo_root>/.skillpack.yaml`; a missing file means defaults.
+pub fn load_repo_config(repo_root: &Path) -> Result<RepoConfig> {
+    let path = repo_root.join(REPO_CONFIG_FILE);
+    if !path.exists() {
+        return Ok(RepoConfig::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("failed to read repo config: {}", path.display()))?;
+    let config: RepoConfig = serde_yaml::from_str(&content)
+        .wrap_err_with(|| format!("failed to parse repo config: {}", path.display()))?;
+    if let Some(roots) = &config.skills_roots {
+        if roots.is_empty() {
+            return Err(eyre!("skills_roots is empty in {}", path.display())
+                .suggestion("List at least one folder or remove skills_roots"));
+        }
+        for root in roots {
+            let relative = Path::new(root);
+            if root.is_empty()
+                || !relative
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(eyre!("skills root is not a relative folder: {root:?}")
+                    .suggestion("Use a path inside the repo such as shared/skills"));
+            }
+        }
+    }
+    Ok(config)
+}
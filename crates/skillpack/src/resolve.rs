@@ -1,20 +1,44 @@
+mod collisions;
+mod excludes;
+mod explain;
+mod skill_deps;
+
+pub use collisions::{
+    CASE_INSENSITIVE_ENV, case_insensitive_names, detect_collisions, find_collisions,
+};
+use excludes::{ExcludeSet, apply_excludes};
+pub use explain::{SkillExplanation, SourceMatch, explain_skill};
+use skill_deps::add_skill_deps;
+
+use crate::config::load_pack_defaults;
 use crate::discover::{Skill, discover_local_skills, discover_remote_skills};
-use crate::git::resolve_repo;
-use crate::pack::{ImportSpec, Pack, load_pack};
+use crate::git::{GitOptions, resolve_repo};
+use crate::pack::{AgentScopes, ImportSpec, Pack, load_pack, validate_install_component};
 use crate::patterns::PatternSet;
-use crate::util::install_name;
+use crate::sources::{Sources, load_sources};
 use color_eyre::Section as _;
 use color_eyre::eyre::{Result, eyre};
-use std::collections::HashSet;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tracing::debug;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SkillSource {
     Local,
     Remote { repo: String },
 }
 
+impl fmt::Display for SkillSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkillSource::Local => write!(f, "local"),
+            SkillSource::Remote { repo } => write!(f, "{repo}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ResolvedSkill {
     pub id: String,
@@ -27,28 +51,152 @@ pub struct ResolvedImport {
     pub repo: String,
     pub ref_name: Option<String>,
     pub commit: String,
+    /// Branch or tag at `commit` when `ref_name` pins a commit hash.
+    pub display_ref: Option<String>,
     pub skills: Vec<ResolvedSkill>,
+    /// Cached checkout the skills were read from.
+    pub path: PathBuf,
+    /// `ref_name` came from `--repo-ref` rather than the pack.
+    pub ref_overridden: bool,
+}
+
+/// Per-run settings for resolving a pack, passed in by the caller rather than read
+/// from the pack file.
+#[derive(Debug, Clone, Default)]
+pub struct ResolveOptions {
+    /// `--namespace`: prepended with `install.sep` to every install name, for
+    /// sinks shared between users.
+    pub namespace: Option<String>,
+    /// `--repo-ref`: the ref every import resolves at instead of its own.
+    pub import_ref: Option<String>,
+    /// `--with-skill-deps`: also select the skills each selected skill lists
+    /// under `depends_on:`.
+    pub with_skill_deps: bool,
+    /// The `<repo>#<pack>` the pack file was fetched from, so installs can
+    /// record where to fetch it again.
+    pub remote_source: Option<String>,
+    /// `--no-bundled`: ignore bundled packs and skills when locating a pack.
+    pub no_bundled: bool,
+    /// How imports are fetched.
+    pub git: GitOptions,
+}
+
+impl ResolveOptions {
+    fn validate(&self) -> Result<()> {
+        if let Some(namespace) = &self.namespace {
+            if namespace.is_empty() {
+                return Err(
+                    eyre!("--namespace is empty").suggestion("Pass a name such as your username")
+                );
+            }
+            validate_install_component("--namespace", namespace)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedPack {
     pub pack: Pack,
+    /// The settings this pack was resolved with.
+    pub options: ResolveOptions,
     pub pack_file: PathBuf,
     pub local: Vec<ResolvedSkill>,
     pub imports: Vec<ResolvedImport>,
     pub final_skills: Vec<ResolvedSkill>,
+    /// `(original id, renamed id)` for each `rename:` entry applied to `final_skills`.
+    pub renamed: Vec<(String, String)>,
+    /// Final skill id → the only agents it installs for, from agent-scoped includes.
+    pub agent_only: AgentScopes,
+    /// `(pattern, removed ids)` for each exclude that removed skills; import
+    /// excludes are written `repo#pattern`.
+    pub excluded: Vec<(String, Vec<String>)>,
+    pub warnings: Vec<String>,
+    /// How long local discovery and import resolution took, for `--timings`.
+    pub phase_times: Vec<(&'static str, Duration)>,
+}
+
+impl ResolvedPack {
+    /// Folder name `id` installs under: the pack's install name, behind the
+    /// namespace when there is one.
+    pub fn install_name(&self, id: &str) -> String {
+        let name = self.pack.install_name(id);
+        match &self.options.namespace {
+            Some(namespace) => format!("{namespace}{}{name}", self.pack.install_sep),
+            None => name,
+        }
+    }
+
+    /// The pack as installed for `agent`: skills scoped to other agents dropped.
+    pub fn for_agent(&self, agent: &str) -> ResolvedPack {
+        let mut resolved = self.clone();
+        let skipped = |id: &String| {
+            self.agent_only
+                .get(id)
+                .is_some_and(|agents| !agents.iter().any(|scoped| scoped == agent))
+        };
+        resolved.final_skills.retain(|skill| !skipped(&skill.id));
+        resolved.agent_only.clear();
+        resolved
+    }
+}
+
+/// Loads and resolves a pack file, reading `defaults:` from `config.yaml` once.
+pub fn resolve_pack(
+    repo_root: &Path,
+    pack_path: &Path,
+    cache_dir: &Path,
+    options: ResolveOptions,
+) -> Result<ResolvedPack> {
+    let pack = load_pack(pack_path, &load_pack_defaults()?)?;
+    resolve_loaded_pack(repo_root, pack, pack_path, cache_dir, options)
 }
 
-pub fn resolve_pack(repo_root: &Path, pack_path: &Path, cache_dir: &Path) -> Result<ResolvedPack> {
-    let pack = load_pack(pack_path)?;
+/// Local skill ids a pack selects (after excludes and renames), without touching
+/// imports or the network. Unlike full resolution, patterns matching nothing are
+/// not errors, since excludes may target imported skills.
+pub fn local_skill_ids(
+    repo_root: &Path,
+    pack: &Pack,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<String>> {
+    if pack.include.is_empty() {
+        return Ok(Vec::new());
+    }
+    let include = PatternSet::new(&pack.include)?;
+    let exclude = ExcludeSet::new(&pack.exclude)?;
+    let mut ids: Vec<String> = discover_local_skills(repo_root, warnings)?
+        .into_iter()
+        .filter(|skill| {
+            include.is_match(&skill.id)
+                && exclude.matching(&SkillSource::Local, &skill.id).is_empty()
+        })
+        .map(|skill| pack.rename.get(&skill.id).cloned().unwrap_or(skill.id))
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Resolves an already parsed pack; `pack_path` is only recorded, not read.
+pub fn resolve_loaded_pack(
+    repo_root: &Path,
+    pack: Pack,
+    pack_path: &Path,
+    cache_dir: &Path,
+    options: ResolveOptions,
+) -> Result<ResolvedPack> {
     debug!(pack = %pack_path.display(), "resolve pack");
+    options.validate()?;
+    validate_patterns(&pack)?;
+    let mut warnings = pack.warnings.clone();
 
+    let started = Instant::now();
     // Only discover local skills if pack has local includes
-    let local_resolved: Vec<ResolvedSkill> = if pack.include.is_empty() {
+    let mut local_resolved: Vec<ResolvedSkill> = if pack.include.is_empty() {
         debug!("no local includes, skipping local skill discovery");
         Vec::new()
     } else {
-        let local_skills = discover_local_skills(repo_root)?;
+        let local_skills = discover_local_skills(repo_root, &mut warnings)?;
         debug!(count = local_skills.len(), "discovered local skills");
         let local_selected = select_included(&local_skills, &pack.include, "local include")?;
         local_selected
@@ -61,12 +209,29 @@ pub fn resolve_pack(repo_root: &Path, pack_path: &Path, cache_dir: &Path) -> Res
             .collect()
     };
     debug!(count = local_resolved.len(), "selected local skills");
+    let discover_time = started.elapsed();
+
+    let started = Instant::now();
 
+    let sources = if pack.imports.is_empty() {
+        Sources::default()
+    } else {
+        load_sources(repo_root)?
+    };
     let mut import_results = Vec::new();
+    let mut excluded = Vec::new();
     for import in &pack.imports {
-        let resolved = resolve_import(cache_dir, import)?;
+        let resolved = resolve_import(
+            cache_dir,
+            &sources,
+            import,
+            &options,
+            &mut excluded,
+            &mut warnings,
+        )?;
         import_results.push(resolved);
     }
+    let imports_time = started.elapsed();
 
     let mut union = Vec::new();
     union.extend(local_resolved.clone());
@@ -74,29 +239,154 @@ pub fn resolve_pack(repo_root: &Path, pack_path: &Path, cache_dir: &Path) -> Res
         union.extend(import.skills.clone());
     }
 
-    let final_skills = apply_excludes(&union, &pack.exclude, "pack exclude")?;
+    // Excludes run before dedup so a `repo#id` exclude can drop one source's copy
+    // and let another source's copy of the same id through. The sort inside is
+    // stable, so dedup still sees each id's copies in precedence order.
+    let union = apply_excludes(
+        &union,
+        &pack.exclude,
+        "pack exclude",
+        &mut excluded,
+        &mut warnings,
+    )?;
+    let mut final_skills = dedup_by_id(union, &mut warnings);
     debug!(count = final_skills.len(), "final skills after excludes");
+    if options.with_skill_deps {
+        add_skill_deps(
+            repo_root,
+            &pack,
+            &mut final_skills,
+            &mut local_resolved,
+            &mut import_results,
+        )?;
+    }
+    // Precedence above follows pack order; reported imports are sorted so
+    // reordering the pack file does not churn output or state.
+    import_results.sort_by(|a, b| (&a.repo, &a.ref_name).cmp(&(&b.repo, &b.ref_name)));
+    let (final_skills, renamed) = apply_renames(final_skills, &pack.rename, &mut warnings);
+    let agent_only = agent_scopes(&pack, &final_skills, &renamed)?;
 
     Ok(ResolvedPack {
         pack,
+        options,
         pack_file: pack_path.to_path_buf(),
         local: local_resolved,
         imports: import_results,
         final_skills,
+        renamed,
+        agent_only,
+        excluded,
+        warnings,
+        phase_times: vec![
+            ("discover", discover_time),
+            ("resolve imports", imports_time),
+        ],
     })
 }
 
-fn resolve_import(cache_dir: &Path, import: &ImportSpec) -> Result<ResolvedImport> {
+/// Agents each local final skill is limited to: set only for skills that no
+/// unscoped include matches, as the union of the matching scoped includes' agents.
+fn agent_scopes(
+    pack: &Pack,
+    final_skills: &[ResolvedSkill],
+    renamed: &[(String, String)],
+) -> Result<AgentScopes> {
+    let mut scopes = AgentScopes::new();
+    if pack.include_agents.is_empty() {
+        return Ok(scopes);
+    }
+    let unscoped: Vec<String> = pack
+        .include
+        .iter()
+        .filter(|pattern| !pack.include_agents.contains_key(*pattern))
+        .cloned()
+        .collect();
+    let unscoped = PatternSet::new(&unscoped)?;
+    let scoped = pack
+        .include_agents
+        .iter()
+        .map(|(pattern, agents)| Ok((PatternSet::new(std::slice::from_ref(pattern))?, agents)))
+        .collect::<Result<Vec<_>>>()?;
+    for skill in final_skills {
+        if !matches!(skill.source, SkillSource::Local) {
+            continue;
+        }
+        // Patterns matched the id before any rename.
+        let id = renamed
+            .iter()
+            .find(|(_, to)| *to == skill.id)
+            .map_or(skill.id.as_str(), |(from, _)| from.as_str());
+        if unscoped.is_match(id) {
+            continue;
+        }
+        let mut agents: Vec<String> = scoped
+            .iter()
+            .filter(|(matcher, _)| matcher.is_match(id))
+            .flat_map(|(_, agents)| agents.iter().cloned())
+            .collect();
+        // No include matched: a `--with-skill-deps` dependency, installed everywhere.
+        if agents.is_empty() {
+            continue;
+        }
+        agents.sort();
+        agents.dedup();
+        scopes.insert(skill.id.clone(), agents);
+    }
+    Ok(scopes)
+}
+
+/// Swaps in renamed ids (excludes already matched the original ids); warns about
+/// rename entries that match no selected skill.
+fn apply_renames(
+    mut skills: Vec<ResolvedSkill>,
+    rename: &BTreeMap<String, String>,
+    warnings: &mut Vec<String>,
+) -> (Vec<ResolvedSkill>, Vec<(String, String)>) {
+    let mut renamed = Vec::new();
+    for (from, to) in rename {
+        match skills.iter_mut().find(|skill| &skill.id == from) {
+            Some(skill) => {
+                skill.id = to.clone();
+                renamed.push((from.clone(), to.clone()));
+            }
+            None => warnings.push(format!("rename for {from} matches no selected skill")),
+        }
+    }
+    if !renamed.is_empty() {
+        skills.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+    (skills, renamed)
+}
+
+fn resolve_import(
+    cache_dir: &Path,
+    sources: &Sources,
+    import: &ImportSpec,
+    options: &ResolveOptions,
+    excluded: &mut Vec<(String, Vec<String>)>,
+    warnings: &mut Vec<String>,
+) -> Result<ResolvedImport> {
+    let mut import = sources.expand(import)?;
+    if let Some(ref_name) = &options.import_ref {
+        import.ref_name = Some(ref_name.to_string());
+    }
+    let import = &import;
     debug!(
         repo = %import.repo,
         reference = %import.ref_name.as_deref().unwrap_or("default"),
         "resolve import"
     );
-    let resolved = resolve_repo(cache_dir, &import.repo, import.ref_name.as_deref())?;
+    let resolved = resolve_repo(
+        cache_dir,
+        &import.repo,
+        import.ref_name.as_deref(),
+        options.git,
+    )?;
     debug!(commit = %resolved.commit, "resolved commit");
-    let skills = discover_remote_skills(&resolved.path)?;
+    let skills = discover_remote_skills(&resolved.path, warnings)?;
     debug!(count = skills.len(), "discovered remote skills");
     let selected = select_included(&skills, &import.include, "import include")?;
+    let mut removed = Vec::new();
     let selected = apply_excludes(
         &selected
             .into_iter()
@@ -110,16 +400,59 @@ fn resolve_import(cache_dir: &Path, import: &ImportSpec) -> Result<ResolvedImpor
             .collect::<Vec<_>>(),
         import.exclude.as_deref().unwrap_or(&[]),
         "import exclude",
+        &mut removed,
+        warnings,
     )?;
+    excluded.extend(
+        removed
+            .into_iter()
+            .map(|(pattern, ids)| (format!("{}#{pattern}", import.repo), ids)),
+    );
 
     Ok(ResolvedImport {
         repo: import.repo.clone(),
         ref_name: import.ref_name.clone(),
         commit: resolved.commit,
+        display_ref: resolved.display_ref,
         skills: selected,
+        path: resolved.path,
+        ref_overridden: options.import_ref.is_some(),
     })
 }
 
+/// Compiles every pattern in the pack up front, so a typo fails before any
+/// import is cloned or fetched.
+fn validate_patterns(pack: &Pack) -> Result<()> {
+    PatternSet::new(&pack.include)?;
+    ExcludeSet::new(&pack.exclude)?;
+    PatternSet::new(&pack.install_preserve)?;
+    for import in &pack.imports {
+        PatternSet::new(&import.include)?;
+        ExcludeSet::new(import.exclude.as_deref().unwrap_or(&[]))?;
+    }
+    Ok(())
+}
+
+/// Keeps the first skill for each id: local skills win over imports, and earlier
+/// imports win over later ones (pack file order).
+fn dedup_by_id(skills: Vec<ResolvedSkill>, warnings: &mut Vec<String>) -> Vec<ResolvedSkill> {
+    let mut seen: BTreeMap<String, SkillSource> = BTreeMap::new();
+    skills
+        .into_iter()
+        .filter(|skill| {
+            if let Some(winner) = seen.get(&skill.id) {
+                warnings.push(format!(
+                    "duplicate skill id {}: using {winner}, ignoring {}",
+                    skill.id, skill.source
+                ));
+                return false;
+            }
+            seen.insert(skill.id.clone(), skill.source.clone());
+            true
+        })
+        .collect()
+}
+
 fn select_included(skills: &[Skill], include: &[String], label: &str) -> Result<Vec<Skill>> {
     let ids: Vec<String> = skills.iter().map(|s| s.id.clone()).collect();
     let matcher = PatternSet::new(include)?;
@@ -149,78 +482,40 @@ fn select_included(skills: &[Skill], include: &[String], label: &str) -> Result<
     Ok(selected)
 }
 
-fn apply_excludes(
-    skills: &[ResolvedSkill],
-    exclude: &[String],
-    label: &str,
-) -> Result<Vec<ResolvedSkill>> {
-    if exclude.is_empty() {
-        return Ok(skills.to_vec());
-    }
-    let matcher = PatternSet::new(exclude)?;
-    let ids: Vec<String> = skills.iter().map(|s| s.id.clone()).collect();
-    let counts = matcher.match_count_per_pattern(&ids);
-    debug!(
-        label = label,
-        patterns = exclude.len(),
-        skills = skills.len(),
-        "exclude scan"
-    );
-    for (pat, count) in exclude.iter().zip(counts.iter()) {
-        debug!(label = label, pattern = %pat, matched = *count, "exclude match");
-    }
-    let mut filtered: Vec<ResolvedSkill> = skills
-        .iter()
-        .filter(|s| !matcher.is_match(&s.id))
-        .cloned()
-        .collect();
-    filtered.sort_by(|a, b| a.id.cmp(&b.id));
-    debug!(
-        label = label,
-        before = skills.len(),
-        after = filtered.len(),
-        "exclude filtered"
-    );
-    Ok(filtered)
-}
-
-pub fn detect_collisions(
-    skills: &[ResolvedSkill],
-    prefix: &str,
-    sep: &str,
-    flatten: bool,
-) -> Result<()> {
-    let mut seen = HashSet::new();
-    for skill in skills {
-        let name = install_name(prefix, sep, &skill.id, flatten);
-        if !seen.insert(name.clone()) {
-            return Err(eyre!("installed folder name collision: {name}")
-                .suggestion("Adjust install.prefix/install.sep/install.flatten or rename skills"));
-        }
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
-    use super::detect_collisions;
-    use crate::resolve::{ResolvedSkill, SkillSource};
+    use super::select_included;
+    use crate::discover::Skill;
 
     #[test]
-    fn detect_collisions_fails() {
-        let skills = vec![
-            ResolvedSkill {
-                id: "a/b".to_string(),
-                dir: "/tmp/a".into(),
-                source: SkillSource::Local,
-            },
-            ResolvedSkill {
-                id: "a__b".to_string(),
-                dir: "/tmp/b".into(),
-                source: SkillSource::Local,
-            },
-        ];
-        let err = detect_collisions(&skills, "p", "__", false).unwrap_err();
-        assert!(err.to_string().contains("collision"));
+    fn select_included_literal_pattern_selects_one_id() {
+        let skills: Vec<Skill> = ["general/writing", "general/writing-style", "generalist"]
+            .into_iter()
+            .map(|id| Skill {
+                id: id.to_string(),
+                dir: format!("/tmp/{id}").into(),
+            })
+            .collect();
+        let ids = |include: &[&str]| {
+            let include: Vec<String> = include.iter().map(|pat| pat.to_string()).collect();
+            select_included(&skills, &include, "include")
+                .unwrap()
+                .into_iter()
+                .map(|skill| skill.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&["general/writing"]), vec!["general/writing"]);
+        assert_eq!(
+            ids(&["general/**"]),
+            vec!["general/writing", "general/writing-style"]
+        );
+
+        // Neither a bare folder nor a partial id selects anything.
+        for pattern in ["general", "general/writ"] {
+            let include = vec![pattern.to_string()];
+            let err = select_included(&skills, &include, "include").unwrap_err();
+            assert!(err.to_string().contains("matched zero skills"), "{err}");
+        }
     }
 }
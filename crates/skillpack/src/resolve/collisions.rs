@@ -0,0 +1,169 @@
+//! Install folder name collisions between the skills a pack selects.
+
+use super::ResolvedSkill;
+use crate::pack::Pack;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Set to a non-empty value other than `0` to treat install names that differ only
+/// in case as colliding, e.g. for a case-insensitive volume mounted on Linux.
+pub const CASE_INSENSITIVE_ENV: &str = "SKILLPACK_CASE_INSENSITIVE";
+
+/// Whether install names are compared case-insensitively: the default on macOS and
+/// Windows, whose filesystems usually fold case, and opt-in elsewhere.
+pub fn case_insensitive_names() -> bool {
+    cfg!(any(target_os = "macos", windows))
+        || std::env::var(CASE_INSENSITIVE_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+pub fn detect_collisions(skills: &[ResolvedSkill], pack: &Pack) -> Result<()> {
+    if let Some(collision) = group_collisions(skills, pack, case_insensitive_names()).first() {
+        let suggestion = match collision.kind {
+            CollisionKind::CaseOnly => {
+                "These names differ only in case and would share one folder on a case-insensitive \
+filesystem; rename skills or adjust install.prefix"
+            }
+            CollisionKind::SameName => {
+                "Adjust install.prefix/install.sep/install.flatten/install.prefix_mode or rename skills"
+            }
+        };
+        return Err(
+            eyre!("installed folder name collision: {}", collision.name).suggestion(suggestion)
+        );
+    }
+    Ok(())
+}
+
+/// Groups skill ids by install name, keeping only names claimed by more than one skill.
+/// Under [`case_insensitive_names`], names differing only in case form one group,
+/// reported as its distinct names joined with ` / `.
+pub fn find_collisions(skills: &[ResolvedSkill], pack: &Pack) -> Vec<(String, Vec<String>)> {
+    group_collisions(skills, pack, case_insensitive_names())
+        .into_iter()
+        .map(|collision| (collision.name, collision.ids))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CollisionKind {
+    /// Several skills map to the exact same install name.
+    SameName,
+    /// The install names differ only in case.
+    CaseOnly,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct NameCollision {
+    name: String,
+    ids: Vec<String>,
+    kind: CollisionKind,
+}
+
+fn group_collisions(skills: &[ResolvedSkill], pack: &Pack, fold_case: bool) -> Vec<NameCollision> {
+    let mut by_name: BTreeMap<String, (BTreeSet<String>, Vec<String>)> = BTreeMap::new();
+    for skill in skills {
+        let name = pack.install_name(&skill.id);
+        let key = if fold_case {
+            name.to_lowercase()
+        } else {
+            name.clone()
+        };
+        let (names, ids) = by_name.entry(key).or_default();
+        names.insert(name);
+        ids.push(skill.id.clone());
+    }
+    by_name
+        .into_values()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|(names, ids)| NameCollision {
+            kind: if names.len() > 1 {
+                CollisionKind::CaseOnly
+            } else {
+                CollisionKind::SameName
+            },
+            name: names.into_iter().collect::<Vec<_>>().join(" / "),
+            ids,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CollisionKind, NameCollision, detect_collisions, find_collisions, group_collisions,
+    };
+    use crate::config::PackDefaults;
+    use crate::pack::load_pack_str;
+    use crate::resolve::{ResolvedSkill, SkillSource};
+
+    fn local_skill(id: &str) -> ResolvedSkill {
+        ResolvedSkill {
+            id: id.to_string(),
+            dir: format!("/tmp/{id}").into(),
+            source: SkillSource::Local,
+        }
+    }
+
+    #[test]
+    fn detect_collisions_fails() {
+        let skills = vec![local_skill("a/b"), local_skill("a__b")];
+        let pack = load_pack_str(
+            "name: p\ninclude:\n  - \"**\"\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap();
+        let err = detect_collisions(&skills, &pack).unwrap_err();
+        assert!(err.to_string().contains("collision"));
+
+        let groups = find_collisions(&skills, &pack);
+        assert_eq!(
+            groups,
+            vec![(
+                "p__a__b".to_string(),
+                vec!["a/b".to_string(), "a__b".to_string()]
+            )]
+        );
+        // Identical names stay a plain collision even when folding case.
+        assert_eq!(
+            group_collisions(&skills, &pack, true)[0].kind,
+            CollisionKind::SameName
+        );
+    }
+
+    #[test]
+    fn case_only_install_names_collide_when_folding_case() {
+        let skills = vec![local_skill("Coding"), local_skill("coding")];
+        let pack = load_pack_str(
+            "name: p\ninclude:\n  - \"**\"\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap();
+
+        assert!(group_collisions(&skills, &pack, false).is_empty());
+        assert_eq!(
+            group_collisions(&skills, &pack, true),
+            vec![NameCollision {
+                name: "p__Coding / p__coding".to_string(),
+                ids: vec!["Coding".to_string(), "coding".to_string()],
+                kind: CollisionKind::CaseOnly,
+            }]
+        );
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn detect_collisions_folds_case_on_case_insensitive_platforms() {
+        let pack = load_pack_str(
+            "name: p\ninclude:\n  - \"**\"\n",
+            "pack.yaml",
+            &PackDefaults::default(),
+        )
+        .unwrap();
+        let err =
+            detect_collisions(&[local_skill("Coding"), local_skill("coding")], &pack).unwrap_err();
+        assert!(err.to_string().contains("p__Coding / p__coding"), "{err}");
+    }
+}
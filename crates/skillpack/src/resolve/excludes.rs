@@ -0,0 +1,95 @@
+//! Pack and import `exclude:` patterns, including `repo#pattern` qualified ones.
+
+use super::{ResolvedSkill, SkillSource};
+use crate::git::expand_repo;
+use crate::patterns::PatternSet;
+use color_eyre::eyre::Result;
+use tracing::debug;
+
+pub(super) fn apply_excludes(
+    skills: &[ResolvedSkill],
+    exclude: &[String],
+    label: &str,
+    removed: &mut Vec<(String, Vec<String>)>,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<ResolvedSkill>> {
+    if exclude.is_empty() {
+        let mut sorted = skills.to_vec();
+        sorted.sort_by(|a, b| a.id.cmp(&b.id));
+        return Ok(sorted);
+    }
+    let matcher = ExcludeSet::new(exclude)?;
+    debug!(
+        label = label,
+        patterns = exclude.len(),
+        skills = skills.len(),
+        "exclude scan"
+    );
+    for (index, pat) in exclude.iter().enumerate() {
+        let mut ids: Vec<String> = skills
+            .iter()
+            .filter(|s| matcher.matching(&s.source, &s.id).contains(&index))
+            .map(|s| s.id.clone())
+            .collect();
+        debug!(label = label, pattern = %pat, matched = ids.len(), "exclude match");
+        if ids.is_empty() {
+            warnings.push(format!("{label} pattern matched zero skills: {pat}"));
+        } else {
+            ids.sort();
+            ids.dedup();
+            removed.push((pat.clone(), ids));
+        }
+    }
+    let mut filtered: Vec<ResolvedSkill> = skills
+        .iter()
+        .filter(|s| matcher.matching(&s.source, &s.id).is_empty())
+        .cloned()
+        .collect();
+    filtered.sort_by(|a, b| a.id.cmp(&b.id));
+    debug!(
+        label = label,
+        before = skills.len(),
+        after = filtered.len(),
+        "exclude filtered"
+    );
+    Ok(filtered)
+}
+
+/// Exclude patterns, optionally qualified as `repo#pattern` to match only skills
+/// imported from that repo. Unqualified patterns match ids from every source.
+pub(super) struct ExcludeSet {
+    entries: Vec<(Option<String>, PatternSet)>,
+}
+
+impl ExcludeSet {
+    pub(super) fn new(patterns: &[String]) -> Result<Self> {
+        let entries = patterns
+            .iter()
+            .map(|pat| {
+                let (repo, id_pattern) = match pat.rsplit_once('#') {
+                    Some((repo, id_pattern)) => (Some(expand_repo(repo)), id_pattern),
+                    None => (None, pat.as_str()),
+                };
+                Ok((repo, PatternSet::new(&[id_pattern.to_string()])?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Indices of the patterns matching a skill from `source`.
+    pub(super) fn matching(&self, source: &SkillSource, id: &str) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (repo, set))| {
+                let source_ok = match (repo, source) {
+                    (None, _) => true,
+                    (Some(repo), SkillSource::Remote { repo: from }) => *repo == expand_repo(from),
+                    (Some(_), SkillSource::Local) => false,
+                };
+                source_ok && set.is_match(id)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
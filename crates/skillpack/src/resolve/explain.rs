@@ -0,0 +1,148 @@
+//! `sp explain`: why a skill id is or is not part of a resolved pack.
+
+use super::{ExcludeSet, ResolvedPack, SkillSource};
+use crate::patterns::PatternSet;
+use color_eyre::eyre::Result;
+
+/// How one source (local skills or an import) treated a skill id.
+#[derive(Debug, Clone)]
+pub struct SourceMatch {
+    /// `local` or the import repo.
+    pub source: String,
+    pub include: Vec<String>,
+    /// Import-level excludes that matched (always empty for local).
+    pub exclude: Vec<String>,
+    pub selected: bool,
+}
+
+/// Why a skill id is (or is not) part of a resolved pack (`sp explain`).
+#[derive(Debug, Clone)]
+pub struct SkillExplanation {
+    /// Original skill id, before any rename.
+    pub id: String,
+    pub sources: Vec<SourceMatch>,
+    /// Pack-level excludes that matched.
+    pub exclude: Vec<String>,
+    pub renamed_to: Option<String>,
+    pub install_name: Option<String>,
+    /// Why the skill is absent; `None` when it is installed.
+    pub reason: Option<String>,
+}
+
+/// Explains the selection of `id` (original or renamed) within `resolved`.
+pub fn explain_skill(resolved: &ResolvedPack, id: &str) -> Result<SkillExplanation> {
+    let id = resolved
+        .renamed
+        .iter()
+        .find(|(_, to)| to == id)
+        .map(|(from, _)| from.as_str())
+        .unwrap_or(id);
+    let matched = |patterns: &[String]| -> Result<Vec<String>> {
+        let set = PatternSet::new(patterns)?;
+        Ok(patterns
+            .iter()
+            .zip(set.matches_per_pattern(id))
+            .filter(|(_, hit)| *hit)
+            .map(|(pat, _)| pat.clone())
+            .collect())
+    };
+
+    let excluded = |patterns: &[String], source: &SkillSource| -> Result<Vec<String>> {
+        let set = ExcludeSet::new(patterns)?;
+        Ok(set
+            .matching(source, id)
+            .into_iter()
+            .map(|index| patterns[index].clone())
+            .collect())
+    };
+
+    let mut sources = Vec::new();
+    // Every source the pack reads, and those that actually offered the skill.
+    let mut candidate_sources = Vec::new();
+    let mut selected_sources = Vec::new();
+    if !resolved.pack.include.is_empty() {
+        let selected = resolved.local.iter().any(|skill| skill.id == id);
+        sources.push(SourceMatch {
+            source: SkillSource::Local.to_string(),
+            include: matched(&resolved.pack.include)?,
+            exclude: Vec::new(),
+            selected,
+        });
+        if selected {
+            selected_sources.push(SkillSource::Local);
+        }
+        candidate_sources.push(SkillSource::Local);
+    }
+    for spec in &resolved.pack.imports {
+        let selected = resolved
+            .imports
+            .iter()
+            .filter(|import| import.repo == spec.repo && import.ref_name == spec.ref_name)
+            .any(|import| import.skills.iter().any(|skill| skill.id == id));
+        let source = SkillSource::Remote {
+            repo: spec.repo.clone(),
+        };
+        sources.push(SourceMatch {
+            source: spec.repo.clone(),
+            include: matched(&spec.include)?,
+            exclude: excluded(spec.exclude.as_deref().unwrap_or(&[]), &source)?,
+            selected,
+        });
+        if selected {
+            selected_sources.push(source.clone());
+        }
+        candidate_sources.push(source);
+    }
+    // Pack excludes are matched the way the resolver applies them, so a
+    // `repo#pattern` exclude only counts for skills from that repo.
+    let exclude_sources = if selected_sources.is_empty() {
+        &candidate_sources
+    } else {
+        &selected_sources
+    };
+    let mut exclude = Vec::new();
+    for source in exclude_sources {
+        for pat in excluded(&resolved.pack.exclude, source)? {
+            if !exclude.contains(&pat) {
+                exclude.push(pat);
+            }
+        }
+    }
+    let renamed_to = resolved.pack.rename.get(id).cloned();
+    let final_id = renamed_to.as_deref().unwrap_or(id);
+    let install_name = resolved
+        .final_skills
+        .iter()
+        .any(|skill| skill.id == final_id)
+        .then(|| resolved.install_name(final_id));
+
+    let reason = if install_name.is_some() {
+        None
+    } else if sources.iter().any(|source| source.selected) {
+        Some(match exclude.first() {
+            Some(pat) => format!("removed by pack exclude {pat}"),
+            None => "not in the final selection".to_string(),
+        })
+    } else if let Some(source) = sources.iter().find(|source| !source.exclude.is_empty()) {
+        Some(format!(
+            "removed by import exclude {} ({})",
+            source.exclude[0], source.source
+        ))
+    } else if let Some(source) = sources.iter().find(|source| !source.include.is_empty()) {
+        Some(format!(
+            "matched by {} but no such skill in {}",
+            source.include[0], source.source
+        ))
+    } else {
+        Some("no include pattern matches".to_string())
+    };
+
+    Ok(SkillExplanation {
+        id: id.to_string(),
+        sources,
+        exclude,
+        renamed_to,
+        install_name,
+        reason,
+    })
+}
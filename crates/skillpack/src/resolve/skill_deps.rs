@@ -0,0 +1,117 @@
+//! `--with-skill-deps`: pulling in the skills a selection lists under `depends_on:`.
+
+use super::{ExcludeSet, ResolvedImport, ResolvedSkill, SkillSource};
+use crate::discover::{Skill, discover_local_skills, discover_remote_skills, read_skill_metadata};
+use crate::pack::Pack;
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, eyre};
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::path::Path;
+use tracing::debug;
+
+/// `--with-skill-deps`: adds every skill a final skill lists under `depends_on:`,
+/// transitively, from the depending skill's own source (local skills, or the same
+/// import's checkout). Added skills are listed under that source as well, and
+/// must get past the excludes that apply to it: the pack's, plus that import's.
+pub(super) fn add_skill_deps(
+    repo_root: &Path,
+    pack: &Pack,
+    final_skills: &mut Vec<ResolvedSkill>,
+    local: &mut Vec<ResolvedSkill>,
+    imports: &mut [ResolvedImport],
+) -> Result<()> {
+    let pack_exclude = ExcludeSet::new(&pack.exclude)?;
+    let import_excludes = pack
+        .imports
+        .iter()
+        .map(|import| ExcludeSet::new(import.exclude.as_deref().unwrap_or(&[])))
+        .collect::<Result<Vec<_>>>()?;
+    // Every skill per source, discovered on first use; `None` is local.
+    let mut pools: BTreeMap<Option<usize>, Vec<Skill>> = BTreeMap::new();
+    let mut pending = final_skills.clone();
+    while let Some(skill) = pending.pop() {
+        let Some(metadata) = read_skill_metadata(&skill.dir)? else {
+            continue;
+        };
+        let source = match skill.source {
+            SkillSource::Local => None,
+            SkillSource::Remote { .. } => Some(
+                imports
+                    .iter()
+                    .position(|import| skill.dir.starts_with(&import.path))
+                    .ok_or_else(|| {
+                        eyre!(
+                            "cannot follow depends_on: of skill {}: no import checkout of {} contains it",
+                            skill.id,
+                            skill.source
+                        )
+                    })?,
+            ),
+        };
+        for dep in metadata.depends_on {
+            if let Some(selected) = final_skills.iter().find(|selected| selected.id == dep) {
+                if selected.source == skill.source {
+                    continue;
+                }
+                return Err(eyre!(
+                    "skill {} depends on {dep} from {}, but the pack selects {dep} from {}",
+                    skill.id,
+                    skill.source,
+                    selected.source
+                )
+                .suggestion("Exclude the other copy with a repo#id exclude"));
+            }
+            let excluded = !pack_exclude.matching(&skill.source, &dep).is_empty()
+                || source.is_some_and(|index| {
+                    !import_excludes[index]
+                        .matching(&skill.source, &dep)
+                        .is_empty()
+                });
+            if excluded {
+                return Err(eyre!(
+                    "skill {} depends on {dep}, which the pack excludes",
+                    skill.id
+                )
+                .suggestion("Drop the exclude or the depends_on: entry"));
+            }
+            let pool = match pools.entry(source) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    // Discovery already reported its warnings while selecting.
+                    let mut ignored = Vec::new();
+                    entry.insert(match source {
+                        None => discover_local_skills(repo_root, &mut ignored)?,
+                        Some(index) => discover_remote_skills(&imports[index].path, &mut ignored)?,
+                    })
+                }
+            };
+            let Some(found) = pool.iter().find(|candidate| candidate.id == dep) else {
+                return Err(eyre!(
+                    "skill {} depends on {dep}, which {} does not provide",
+                    skill.id,
+                    skill.source
+                )
+                .suggestion("Add the skill to that source or fix the depends_on: entry"));
+            };
+            debug!(skill = %skill.id, dependency = %dep, "add skill dependency");
+            let resolved = ResolvedSkill {
+                id: found.id.clone(),
+                dir: found.dir.clone(),
+                source: skill.source.clone(),
+            };
+            match source {
+                None => local.push(resolved.clone()),
+                Some(index) => imports[index].skills.push(resolved.clone()),
+            }
+            final_skills.push(resolved.clone());
+            pending.push(resolved);
+        }
+    }
+    final_skills.sort_by(|a, b| a.id.cmp(&b.id));
+    local.sort_by(|a, b| a.id.cmp(&b.id));
+    for import in imports {
+        import.skills.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Repo-level `sources.yaml`: short aliases for import repos, so packs can write
+//! `repo: "@shared-skills"` instead of repeating the URL and ref.
+
+use color_eyre::Section as _;
+use color_eyre::eyre::{Result, WrapErr, eyre};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::pack::ImportSpec;
+
+pub const SOURCES_FILE: &str = "sources.yaml";
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SourceSpec {
+    pub repo: String,
+    #[serde(rename = "ref")]
+    pub ref_name: Option<String>,
+}
+
+/// Alias (without the leading `@`) → source.
+#[derive(Debug, Default)]
+pub struct Sources {
+    aliases: BTreeMap<String, SourceSpec>,
+}
+
+/// Reads `<repo_root>/sources.yaml`; a missing file means no aliases.
+pub fn load_sources(repo_root: &Path) -> Result<Sources> {
+    let path = repo_root.join(SOURCES_FILE);
+    if !path.exists() {
+        return Ok(Sources::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("failed to read sources file: {}", path.display()))?;
+    let aliases: BTreeMap<String, SourceSpec> = serde_yaml::from_str(&content)
+        .wrap_err_with(|| format!("failed to parse sources file: {}", path.display()))?;
+    for (alias, source) in &aliases {
+        if source.repo.trim().is_empty() || source.repo.starts_with('@') {
+            return Err(eyre!("source {alias} needs a repo URL").suggestion(format!(
+                "Set repo: <git-url> under {alias} in {}",
+                path.display()
+            )));
+        }
+    }
+    Ok(Sources { aliases })
+}
+
+impl Sources {
+    /// Replaces an `@alias` repo with the source's URL. The source's ref applies
+    /// unless the import sets its own.
+    pub fn expand(&self, import: &ImportSpec) -> Result<ImportSpec> {
+        let Some(alias) = import.repo.strip_prefix('@') else {
+            return Ok(import.clone());
+        };
+        let source = self.aliases.get(alias).ok_or_else(|| {
+            eyre!("unknown import source: @{alias}")
+                .suggestion(format!("Define {alias} in {SOURCES_FILE} at the repo root"))
+        })?;
+        Ok(ImportSpec {
+            repo: source.repo.clone(),
+            ref_name: import.ref_name.clone().or_else(|| source.ref_name.clone()),
+            ..import.clone()
+        })
+    }
+}
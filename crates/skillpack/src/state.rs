@@ -1,6 +1,7 @@
-use crate::config::{ensure_config_dir, state_path};
+use crate::config::{ensure_config_dir, state_path, write_version_marker};
 use color_eyre::eyre::{Result, eyre};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::path::Path;
 
@@ -22,9 +23,24 @@ pub struct InstallRecord {
     pub sep: String,
     #[serde(default)]
     pub flatten: bool,
+    #[serde(default)]
+    pub frozen: bool,
     pub imports: Vec<ImportRecord>,
     pub installed_paths: Vec<String>,
     pub installed_at: String,
+    /// Installed path → content hash of the skill folder copied there.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hashes: BTreeMap<String, String>,
+    /// `--namespace` the install names were prefixed with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Installed with `--with-skill-deps`, so updates follow `depends_on:` too.
+    #[serde(default)]
+    pub with_skill_deps: bool,
+    /// `<repo>#<pack>` the pack file was fetched from; `sp update` fetches it again
+    /// instead of reading `pack_file` from the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -59,16 +75,34 @@ pub fn load_state_at(path: &Path) -> Result<StateFile> {
 pub fn write_state(state: &StateFile) -> Result<()> {
     ensure_config_dir()?;
     let path = state_path()?;
-    write_state_at(state, &path)
+    write_state_at(state, &path)?;
+    write_version_marker()
 }
 
+/// Writes records sorted by `(sink_path, pack)` with sorted paths and imports, so
+/// equal states produce identical files regardless of insertion order.
 pub fn write_state_at(state: &StateFile, path: &Path) -> Result<()> {
+    let mut sorted = state.clone();
+    for record in &mut sorted.installs {
+        record.installed_paths.sort();
+        record
+            .imports
+            .sort_by(|a, b| (&a.repo, &a.ref_name).cmp(&(&b.repo, &b.ref_name)));
+    }
+    sorted
+        .installs
+        .sort_by(|a, b| (&a.sink_path, &a.pack).cmp(&(&b.sink_path, &b.pack)));
+    write_json_atomic(&sorted, path)
+}
+
+/// Writes pretty JSON via a temp file in the same directory, then renames it into place.
+pub(crate) fn write_json_atomic<T: Serialize>(value: &T, path: &Path) -> Result<()> {
     let dir = path
         .parent()
         .ok_or_else(|| eyre!("state directory missing"))?;
     std::fs::create_dir_all(dir)?;
     let mut temp = tempfile::NamedTempFile::new_in(dir)?;
-    let data = serde_json::to_vec_pretty(state)?;
+    let data = serde_json::to_vec_pretty(value)?;
     use std::io::Write;
     temp.write_all(&data)?;
     temp.as_file().sync_all()?;
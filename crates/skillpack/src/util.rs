@@ -43,8 +43,25 @@ pub fn discover_repo_root(start: &Path) -> Option<PathBuf> {
     None
 }
 
-fn is_repo_root(dir: &Path) -> bool {
-    dir.join("skills").is_dir() || dir.join("packs").is_dir()
+pub fn is_repo_root(dir: &Path) -> bool {
+    dir.join("skills").is_dir()
+        || dir.join("packs").is_dir()
+        || dir.join(crate::repo_config::REPO_CONFIG_FILE).is_file()
+}
+
+/// Total size in bytes of regular files under `path` (0 when it does not exist).
+pub fn dir_size(path: &Path) -> Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let mut total = 0;
+    for entry in walkdir::WalkDir::new(path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            total += entry.metadata()?.len();
+        }
+    }
+    Ok(total)
 }
 
 pub fn now_rfc3339() -> Result<String> {
@@ -52,6 +69,49 @@ pub fn now_rfc3339() -> Result<String> {
     Ok(ts.format(&Rfc3339)?)
 }
 
+/// Parses an age like `30d`, `12h`, `45m`, `90s` or `2w`.
+pub fn parse_age(value: &str) -> std::result::Result<std::time::Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let count: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid duration {value:?}: expected a number and unit like 30d"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in {value:?}: use s, m, h, d or w"
+            ));
+        }
+    };
+    // Ages are compared as signed seconds (`installed_before`), so cap them there.
+    count
+        .checked_mul(unit_secs)
+        .filter(|secs| i64::try_from(*secs).is_ok())
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| format!("duration {value:?} is too large"))
+}
+
+/// Whether an RFC 3339 `installed_at` stamp is older than `age`. Unparseable stamps
+/// never count as old.
+pub fn installed_before(installed_at: &str, age: std::time::Duration) -> bool {
+    let Ok(stamp) = OffsetDateTime::parse(installed_at, &Rfc3339) else {
+        return false;
+    };
+    let Ok(secs) = i64::try_from(age.as_secs()) else {
+        return false;
+    };
+    OffsetDateTime::now_utc()
+        .checked_sub(time::Duration::seconds(secs))
+        .is_some_and(|cutoff| stamp < cutoff)
+}
+
 pub fn ensure_child_path(root: &Path, candidate: &Path) -> Result<()> {
     if candidate.starts_with(root) {
         Ok(())
@@ -63,11 +123,112 @@ pub fn ensure_child_path(root: &Path, candidate: &Path) -> Result<()> {
     }
 }
 
+/// Rejects folder names Windows cannot create: reserved device names (with or
+/// without an extension), a trailing dot or space, or more than 255 characters.
+#[cfg(windows)]
+pub fn validate_folder_name(name: &str) -> Result<()> {
+    use color_eyre::Section as _;
+
+    const RESERVED: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+    let stem = name
+        .split('.')
+        .next()
+        .unwrap_or(name)
+        .trim_end()
+        .to_ascii_uppercase();
+    let numbered = (stem.starts_with("COM") || stem.starts_with("LPT"))
+        && stem.len() == 4
+        && matches!(stem.as_bytes()[3], b'1'..=b'9');
+    let problem = if RESERVED.contains(&stem.as_str()) || numbered {
+        Some("is a reserved device name on Windows")
+    } else if name.ends_with('.') || name.ends_with(' ') {
+        Some("ends with a dot or space, which Windows strips")
+    } else if name.chars().count() > 255 {
+        Some("is longer than 255 characters")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(eyre!("install folder name {name:?} {problem}")
+            .suggestion("Rename the skill with rename:, or change install.prefix/sep/flatten")),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn validate_folder_name(_name: &str) -> Result<()> {
+    Ok(())
+}
+
+/// Adds the `\\?\` prefix to long absolute paths so deep sinks work without
+/// the Windows long-path registry setting.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    const MAX_PATH: usize = 260;
+    let text = path.as_os_str().to_string_lossy();
+    if !path.is_absolute() || text.len() < MAX_PATH || text.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+    match text.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{unc}")),
+        None => PathBuf::from(format!(r"\\?\{text}")),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
 #[cfg(test)]
 mod tests {
     use super::discover_repo_root;
     use assert_fs::prelude::*;
 
+    #[test]
+    fn parse_age_accepts_units_and_rejects_garbage() {
+        use super::parse_age;
+        use std::time::Duration;
+
+        assert_eq!(parse_age("30d"), Ok(Duration::from_secs(30 * 86_400)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 3_600)));
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("10y").is_err());
+        assert!(parse_age("10").is_err());
+        // Fits a u64 but not the signed seconds the cutoff is computed in.
+        assert!(parse_age("18446744073709000000s").is_err());
+        assert!(parse_age(&format!("{}s", i64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn installed_before_never_matches_ages_beyond_the_clock() {
+        use super::installed_before;
+        use std::time::Duration;
+
+        assert!(installed_before(
+            "2000-01-01T00:00:00Z",
+            Duration::from_secs(86_400)
+        ));
+        assert!(!installed_before(
+            "2000-01-01T00:00:00Z",
+            Duration::from_secs(u64::MAX)
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn validate_folder_name_rejects_reserved_names() {
+        use super::validate_folder_name;
+
+        let err = validate_folder_name("CON").unwrap_err();
+        assert!(err.to_string().contains("reserved device name"));
+        assert!(validate_folder_name("lpt1.md").is_err());
+        assert!(validate_folder_name("demo.").is_err());
+        assert!(validate_folder_name("demo__console").is_ok());
+        assert!(validate_folder_name("COM0").is_ok());
+    }
+
     #[test]
     fn discover_repo_root_finds_parent() {
         let temp = assert_fs::TempDir::new().unwrap();
@@ -0,0 +1,112 @@
+use crate::resolve::{ResolvedSkill, SkillSource};
+use color_eyre::eyre::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Files under a skill dir with their modification time and size.
+type Snapshot = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+/// Polls local skill directories for changes (`sp install --watch`).
+///
+/// Imported skills are skipped: they come from the git cache, not from files being edited.
+pub struct SkillWatcher {
+    dirs: Vec<(String, PathBuf)>,
+    snapshots: BTreeMap<String, Snapshot>,
+}
+
+impl SkillWatcher {
+    pub fn new(skills: &[ResolvedSkill]) -> Result<Self> {
+        let dirs: Vec<(String, PathBuf)> = skills
+            .iter()
+            .filter(|skill| matches!(skill.source, SkillSource::Local))
+            .map(|skill| (skill.id.clone(), skill.dir.clone()))
+            .collect();
+        let snapshots = snapshot_all(&dirs)?;
+        Ok(Self { dirs, snapshots })
+    }
+
+    pub fn len(&self) -> usize {
+        self.dirs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty()
+    }
+
+    /// Blocks until at least one skill changes, then keeps polling until a full
+    /// `interval` passes without further changes (debounce). Returns the changed ids.
+    pub fn wait_for_changes(&mut self, interval: Duration) -> Result<Vec<String>> {
+        loop {
+            std::thread::sleep(interval);
+            let mut current = snapshot_all(&self.dirs)?;
+            if current == self.snapshots {
+                continue;
+            }
+            loop {
+                std::thread::sleep(interval);
+                let next = snapshot_all(&self.dirs)?;
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+            let changed = current
+                .iter()
+                .filter(|(id, snapshot)| self.snapshots.get(*id) != Some(*snapshot))
+                .map(|(id, _)| id.clone())
+                .collect();
+            self.snapshots = current;
+            return Ok(changed);
+        }
+    }
+}
+
+fn snapshot_all(dirs: &[(String, PathBuf)]) -> Result<BTreeMap<String, Snapshot>> {
+    let mut snapshots = BTreeMap::new();
+    for (id, dir) in dirs {
+        let mut files = Vec::new();
+        if dir.exists() {
+            for entry in WalkDir::new(dir).sort_by_file_name() {
+                let entry = entry?;
+                if entry.file_type().is_dir() {
+                    continue;
+                }
+                let metadata = entry.metadata()?;
+                files.push((
+                    entry.path().to_path_buf(),
+                    metadata.modified().ok(),
+                    metadata.len(),
+                ));
+            }
+        }
+        snapshots.insert(id.clone(), files);
+    }
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkillWatcher;
+    use crate::resolve::{ResolvedSkill, SkillSource};
+    use assert_fs::prelude::*;
+    use std::time::Duration;
+
+    #[test]
+    fn reports_changed_local_skill() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        temp.child("alpha/SKILL.md").write_str("x").unwrap();
+        temp.child("beta/SKILL.md").write_str("x").unwrap();
+        let skill = |id: &str| ResolvedSkill {
+            id: id.to_string(),
+            dir: temp.child(id).path().to_path_buf(),
+            source: SkillSource::Local,
+        };
+        let mut watcher = SkillWatcher::new(&[skill("alpha"), skill("beta")]).unwrap();
+
+        temp.child("beta/notes.md").write_str("new").unwrap();
+        let changed = watcher.wait_for_changes(Duration::from_millis(20)).unwrap();
+        assert_eq!(changed, vec!["beta".to_string()]);
+    }
+}
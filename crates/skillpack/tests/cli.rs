@@ -2,18 +2,9 @@ use assert_cmd::Command;
 use assert_fs::prelude::*;
 use predicates::prelude::*;
 
-fn setup_bundled_repo(temp: &assert_fs::TempDir) -> assert_fs::fixture::ChildPath {
-    let bundled_root = temp.child(format!(".skillpack/bundled/{}", env!("CARGO_PKG_VERSION")));
-    bundled_root
-        .child("skills/alpha/SKILL.md")
-        .write_str("x")
-        .unwrap();
-    bundled_root
-        .child("packs/demo.yaml")
-        .write_str("name: demo\ninclude:\n  - alpha/**\n")
-        .unwrap();
-    bundled_root
-}
+mod common;
+
+use common::{setup_bundled_repo, sp};
 
 #[test]
 fn list_outputs_skill_ids() {
@@ -40,11 +31,8 @@ fn packs_outputs_pack_names() {
         .write_str("name: other\ninclude:\n  - beta/**\n")
         .unwrap();
 
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("packs")
-        .arg("--root")
-        .arg(temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    let mut cmd = sp(&temp);
+    cmd.arg("packs");
     cmd.assert().success().stdout(
         predicate::str::contains("Packs")
             .and(predicate::str::contains("demo"))
@@ -59,12 +47,8 @@ fn skills_includes_bundled_with_flag() {
     // skills/ directory is required even with --bundled
     temp.child("skills").create_dir_all().unwrap();
 
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("skills")
-        .arg("--bundled")
-        .arg("--root")
-        .arg(temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    let mut cmd = sp(&temp);
+    cmd.arg("skills").arg("--bundled");
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("github-fix-code-review"));
@@ -75,219 +59,199 @@ fn skills_requires_skills_directory() {
     let temp = assert_fs::TempDir::new().unwrap();
     // No skills/ directory created
 
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("skills")
-        .arg("--root")
-        .arg(temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    let mut cmd = sp(&temp);
+    cmd.arg("skills");
     cmd.assert()
         .failure()
         .stderr(predicate::str::contains("skills/ directory not found"));
 }
 
 #[test]
-fn show_outputs_final_names() {
+fn no_bundled_skips_bundled_packs() {
     let temp = assert_fs::TempDir::new().unwrap();
-    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
-    temp.child("packs/demo.yaml")
-        .write_str("name: demo\ninclude:\n  - alpha/**\n")
-        .unwrap();
-
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("show")
-        .arg("demo")
-        .arg("--root")
-        .arg(temp.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("Installs as").and(predicate::str::contains("demo__alpha")),
-    );
-}
-
-#[test]
-fn show_outputs_final_names_for_bundled_pack() {
-    let temp = assert_fs::TempDir::new().unwrap();
-    setup_bundled_repo(&temp);
     let work = temp.child("work");
     work.create_dir_all().unwrap();
 
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
     cmd.arg("show")
-        .arg("demo")
+        .arg("skillpack")
+        .arg("--no-bundled")
         .current_dir(work.path())
         .arg("--cache-dir")
         .arg(temp.child("cache").path())
         .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("Installs as").and(predicate::str::contains("demo__alpha")),
-    );
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("pack not found: skillpack"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("packs")
+        .arg("--format")
+        .arg("plain")
+        .current_dir(work.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .env("SKILLPACK_NO_BUNDLED", "1");
+    cmd.assert().success().stdout("");
+    assert!(!temp.child(".skillpack/bundled").path().exists());
 }
 
 #[test]
-fn install_hides_zero_counters() {
+fn auto_discovers_repo_root() {
     let temp = assert_fs::TempDir::new().unwrap();
     temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
-    temp.child("packs/demo.yaml")
-        .write_str("name: demo\ninclude:\n  - alpha/**\n")
-        .unwrap();
-    let sink = temp.child("sink");
-    sink.create_dir_all().unwrap();
+    let work = temp.child("work");
+    work.create_dir_all().unwrap();
 
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("install")
-        .arg("demo")
-        .arg("--custom")
-        .arg("--path")
-        .arg(sink.path())
-        .arg("--root")
-        .arg(temp.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path())
-        .env("HOME", temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("added")
-            .and(predicate::str::contains("1"))
-            .and(predicate::str::contains("updated").not())
-            .and(predicate::str::contains("removed").not()),
-    );
+    cmd.arg("skills").current_dir(work.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("alpha"));
 }
 
 #[test]
-fn install_bundled_pack() {
+fn falls_back_to_git_toplevel_for_repo_root() {
     let temp = assert_fs::TempDir::new().unwrap();
-    setup_bundled_repo(&temp);
-    let sink = temp.child("sink");
-    sink.create_dir_all().unwrap();
+    let repo = temp.child("repo");
+    repo.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    let status = std::process::Command::new("git")
+        .arg("init")
+        .arg("-q")
+        .current_dir(repo.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let work = temp.child("sibling/deep/dir");
+    work.create_dir_all().unwrap();
 
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("install")
-        .arg("demo")
-        .arg("--custom")
-        .arg("--path")
-        .arg(sink.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path())
-        .env("HOME", temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("Installed")
-            .and(predicate::str::contains("demo"))
-            .and(predicate::str::contains("added"))
-            .and(predicate::str::contains("1")),
-    );
+    let skills = |git_env: bool| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.args(["skills", "--no-bundled", "--verbose"])
+            .current_dir(work.path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        if git_env {
+            cmd.env("GIT_DIR", repo.child(".git").path())
+                .env("GIT_WORK_TREE", repo.path());
+        }
+        cmd.assert()
+    };
+    // No marker above the cwd, so only git knows where the skills are.
+    skills(false)
+        .failure()
+        .stderr(predicate::str::contains("skills/ directory not found"));
+    skills(true)
+        .success()
+        .stdout(predicate::str::contains("alpha"))
+        .stderr(predicate::str::contains("repo root from git toplevel"));
 }
 
 #[test]
-fn auto_discovers_repo_root() {
+fn packs_with_skills_nests_local_skills() {
     let temp = assert_fs::TempDir::new().unwrap();
     temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
-    let work = temp.child("work");
-    work.create_dir_all().unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
 
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("skills").current_dir(work.path());
-    cmd.assert()
-        .success()
-        .stdout(predicate::str::contains("alpha"));
+    let run = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.arg("packs")
+            .arg("--with-skills")
+            .arg("--no-bundled")
+            .arg("--format")
+            .arg(format);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let pretty = run("pretty");
+    assert!(pretty.contains("demo"));
+    assert!(pretty.contains("└─ alpha"));
+    assert!(!pretty.contains("beta"));
+
+    let json: serde_json::Value = serde_json::from_str(&run("json")).unwrap();
+    assert_eq!(json["packs"][0]["skills"], serde_json::json!(["alpha"]));
+    assert_eq!(json["packs"][0]["imports"], 0);
 }
 
 #[test]
-fn switch_uninstalls_all_and_installs_new() {
+fn skills_roots_config_lists_skills_from_every_root() {
     let temp = assert_fs::TempDir::new().unwrap();
-    // Create two skills and two packs
+    temp.child(".skillpack.yaml")
+        .write_str("skills_roots: [skills, shared/skills]\n")
+        .unwrap();
     temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
-    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
-    temp.child("packs/pack1.yaml")
-        .write_str("name: pack1\ninclude:\n  - alpha/**\n")
+    temp.child("shared/skills/beta/SKILL.md")
+        .write_str("x")
         .unwrap();
-    temp.child("packs/pack2.yaml")
-        .write_str("name: pack2\ninclude:\n  - beta/**\n")
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - beta\n")
         .unwrap();
-    let sink = temp.child("sink");
-    sink.create_dir_all().unwrap();
 
-    // First install pack1
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("install")
-        .arg("pack1")
-        .arg("--custom")
-        .arg("--path")
-        .arg(sink.path())
-        .arg("--root")
-        .arg(temp.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path())
-        .env("HOME", temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success();
+    let mut cmd = sp(&temp);
+    cmd.arg("skills").arg("--format").arg("plain");
+    cmd.assert().success().stdout("alpha\nbeta\n");
 
-    // Verify pack1 is installed
-    assert!(sink.child("pack1__alpha").exists());
-    assert!(!sink.child("pack2__beta").exists());
-
-    // Switch to pack2
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("switch")
-        .arg("pack2")
-        .arg("--custom")
-        .arg("--path")
-        .arg(sink.path())
-        .arg("--root")
-        .arg(temp.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path())
-        .env("HOME", temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("Switched")
-            .and(predicate::str::contains("uninstalled"))
-            .and(predicate::str::contains("pack1"))
-            .and(predicate::str::contains("installed"))
-            .and(predicate::str::contains("pack2")),
+    let mut cmd = sp(&temp);
+    cmd.arg("install").arg("demo").arg("--codex");
+    cmd.assert().success();
+    assert!(
+        temp.child(".codex/skills/demo__beta/SKILL.md")
+            .path()
+            .exists()
     );
-
-    // Verify pack1 is gone and pack2 is installed
-    assert!(!sink.child("pack1__alpha").exists());
-    assert!(sink.child("pack2__beta").exists());
 }
 
 #[test]
-fn switch_installs_multiple_packs() {
+fn skills_skips_unreadable_skill_md_with_warning() {
     let temp = assert_fs::TempDir::new().unwrap();
     temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
-    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
-    temp.child("packs/pack1.yaml")
-        .write_str("name: pack1\ninclude:\n  - alpha/**\n")
+    temp.child("skills/broken/SKILL.md")
+        .write_binary(&[0xff, 0xfe, 0x00, 0x80])
         .unwrap();
-    temp.child("packs/pack2.yaml")
-        .write_str("name: pack2\ninclude:\n  - beta/**\n")
-        .unwrap();
-    let sink = temp.child("sink");
-    sink.create_dir_all().unwrap();
 
-    // Switch to both packs at once
-    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
-    cmd.arg("switch")
-        .arg("pack1")
-        .arg("pack2")
-        .arg("--custom")
-        .arg("--path")
-        .arg(sink.path())
-        .arg("--root")
-        .arg(temp.path())
-        .arg("--cache-dir")
-        .arg(temp.child("cache").path())
-        .env("HOME", temp.path())
-        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
-    cmd.assert().success().stdout(
-        predicate::str::contains("Switched")
-            .and(predicate::str::contains("pack1"))
-            .and(predicate::str::contains("pack2")),
+    let mut cmd = sp(&temp);
+    cmd.arg("skills").arg("--format").arg("json");
+    let stdout = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["skills"], serde_json::json!(["alpha"]));
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0]
+            .as_str()
+            .unwrap()
+            .starts_with("skipped skill with unreadable SKILL.md:"),
+        "{warnings:?}"
     );
 
-    // Verify both packs are installed
-    assert!(sink.child("pack1__alpha").exists());
-    assert!(sink.child("pack2__beta").exists());
+    let mut cmd = sp(&temp);
+    cmd.arg("skills").arg("--strict");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unreadable SKILL.md"));
+}
+
+#[test]
+fn skills_bundled_reports_repo_skill_shadowing_bundled_one() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let bundled_root = setup_bundled_repo(&temp);
+    bundled_root
+        .child("skills/gamma/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+
+    for _ in 0..2 {
+        let mut cmd = sp(&temp);
+        cmd.args(["skills", "--bundled", "--format", "json"]);
+        let stdout = cmd.assert().success().get_output().stdout.clone();
+        let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(json["sources"]["alpha"], "repo");
+        assert_eq!(json["sources"]["gamma"], "bundled");
+        let skills = json["skills"].as_array().unwrap();
+        assert_eq!(skills.iter().filter(|id| *id == "alpha").count(), 1);
+    }
 }
@@ -0,0 +1,43 @@
+//! Fixtures shared by the CLI test binaries.
+
+use assert_cmd::Command;
+use assert_fs::TempDir;
+use assert_fs::prelude::*;
+
+/// `sp --root <temp>` with `HOME` and `SKILLPACK_HOME` inside `temp`, so state,
+/// config and the default cache never touch the real home directory.
+#[allow(dead_code)]
+pub fn sp(temp: &TempDir) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("--root")
+        .arg(temp.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd
+}
+
+/// A bundled content dir under `temp/.skillpack` with skill `alpha` and pack `demo`.
+#[allow(dead_code)]
+pub fn setup_bundled_repo(temp: &TempDir) -> assert_fs::fixture::ChildPath {
+    let bundled_root = temp.child(format!(".skillpack/bundled/{}", env!("CARGO_PKG_VERSION")));
+    bundled_root
+        .child("skills/alpha/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    bundled_root
+        .child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    bundled_root
+}
+
+/// Runs `git` in `dir`, failing the test if it exits unsuccessfully.
+#[allow(dead_code)]
+pub fn run_git(args: &[&str], dir: &std::path::Path) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
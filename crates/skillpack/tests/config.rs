@@ -0,0 +1,297 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn cache_path_prints_default_location() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("cache")
+        .arg("path")
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    let expected = format!("{}\n", temp.path().join(".skillpack/cache").display());
+    cmd.assert().success().stdout(expected);
+}
+
+#[test]
+fn cache_size_sums_cached_bytes() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let cache = temp.child("cache");
+    cache.child("abc/file.txt").write_str("12345").unwrap();
+    cache.child("def/nested/file.txt").write_str("123").unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("cache")
+        .arg("size")
+        .arg("--format")
+        .arg("plain")
+        .arg("--cache-dir")
+        .arg(cache.path())
+        .env("HOME", temp.path());
+    cmd.assert().success().stdout("8\n");
+}
+
+#[test]
+fn config_check_json_reports_relative_path_error() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".skillpack/config.yaml")
+        .write_str("sinks:\n  codex: relative/skills\n")
+        .unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("config")
+        .arg("--check")
+        .arg("--format")
+        .arg("json")
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let view: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(view["errors"], 1);
+    assert_eq!(
+        view["findings"][0],
+        serde_json::json!({
+            "severity": "error",
+            "sink": "codex",
+            "message": "path is not absolute: relative/skills"
+        })
+    );
+}
+
+#[test]
+fn config_check_covers_env_sink_overrides() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let check = |config: &str, env: &str| {
+        temp.child(".skillpack/config.yaml")
+            .write_str(config)
+            .unwrap();
+        let mut cmd = sp(&temp);
+        cmd.args(["config", "--check", "--format", "json"])
+            .env("SKILLPACK_SINK_CODEX", env);
+        let output = cmd.assert().get_output().stdout.clone();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+
+    let view = check("", "relative/skills");
+    assert_eq!(view["errors"], 1);
+    assert_eq!(
+        view["findings"][0]["message"],
+        "path is not absolute: relative/skills (from SKILLPACK_SINK_CODEX)"
+    );
+
+    // The environment wins, so a bad path it replaces is not reported.
+    let absolute = temp.child("codex").path().display().to_string();
+    let view = check("sinks:\n  codex: relative/skills\n", &absolute);
+    assert_eq!(view["errors"], 0, "{view}");
+}
+
+#[test]
+fn list_agents_includes_builtin_and_config_sinks() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".skillpack/config.yaml")
+        .write_str("sinks:\n  gemini: ~/.gemini/skills\n")
+        .unwrap();
+
+    let list = |format: &str| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.args(["list-agents", "--format", format])
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    assert_eq!(
+        list("plain"),
+        "claude\ncodex\ncopilot\ncursor\ngemini\nwindsurf\n"
+    );
+    let json: serde_json::Value = serde_json::from_str(&list("json")).unwrap();
+    let gemini = json["agents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|agent| agent["name"] == "gemini")
+        .unwrap();
+    assert_eq!(gemini["source"], "config");
+    assert_eq!(
+        gemini["path"],
+        temp.child(".gemini/skills").path().display().to_string()
+    );
+}
+
+#[test]
+fn config_default_sep_applies_when_pack_omits_it() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".skillpack/config.yaml")
+        .write_str("defaults:\n  sep: \".\"\n")
+        .unwrap();
+    temp.child("skills/tools/lint/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - tools/**\n")
+        .unwrap();
+    temp.child("packs/own.yaml")
+        .write_str("name: own\ninclude:\n  - tools/**\ninstall:\n  sep: \"-\"\n")
+        .unwrap();
+
+    for pack in ["demo", "own"] {
+        let mut cmd = sp(&temp);
+        cmd.arg("install").arg(pack).arg("--codex");
+        cmd.assert().success();
+    }
+    let sink = temp.child(".codex/skills");
+    assert!(sink.child("demo.tools.lint/SKILL.md").path().exists());
+    assert!(sink.child("own-tools-lint/SKILL.md").path().exists());
+}
+
+#[test]
+fn config_check_reports_invalid_defaults() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".skillpack/config.yaml")
+        .write_str("defaults:\n  sep: \"/\"\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.args(["config", "--check", "--format", "json"]);
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let view: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        view["findings"][0],
+        serde_json::json!({
+            "severity": "error",
+            "sink": "defaults",
+            "message": "sep contains invalid character: '/'"
+        })
+    );
+
+    // A defaults block that does not parse names the config file.
+    temp.child(".skillpack/config.yaml")
+        .write_str("defaults:\n  prefix_style: sideways\n")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - tools/**\n")
+        .unwrap();
+    let mut cmd = sp(&temp);
+    cmd.args(["install", "demo", "--codex"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to parse config file"));
+}
+
+#[cfg(unix)]
+#[test]
+fn config_edit_creates_template_and_runs_editor() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let editor = temp.child("editor.sh");
+    editor
+        .write_str("#!/bin/sh\nprintf '%s' \"$1\" > \"$EDITOR_LOG\"\n")
+        .unwrap();
+    std::fs::set_permissions(editor.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    let log = temp.child("editor.log");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("config")
+        .arg("--edit")
+        .env("EDITOR", editor.path())
+        .env("EDITOR_LOG", log.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert().success();
+
+    let config = temp.child(".skillpack/config.yaml");
+    assert_eq!(
+        std::fs::read_to_string(log.path()).unwrap(),
+        config.path().display().to_string()
+    );
+    let template = std::fs::read_to_string(config.path()).unwrap();
+    assert!(
+        template.contains("#   codex: ~/.codex/skills"),
+        "{template}"
+    );
+}
+
+#[test]
+fn doctor_fix_drops_records_of_missing_sinks() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    let sp = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd.assert().success().get_output().stdout.clone()
+    };
+    sp(&["install", "demo", "--codex"]);
+    std::fs::remove_dir_all(temp.child(".codex").path()).unwrap();
+
+    let report = sp(&["doctor", "--format", "plain"]);
+    let sink = temp.child(".codex/skills").path().display().to_string();
+    assert_eq!(
+        String::from_utf8(report).unwrap(),
+        format!("dangling-record {sink} -\n")
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&sp(&["doctor", "--fix", "--format", "json"])).unwrap();
+    assert_eq!(json["findings"][0]["kind"], "dangling-record");
+    assert_eq!(json["findings"][0]["fix"], "fixed");
+    let state: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state["installs"], serde_json::json!([]));
+    assert!(sp(&["doctor", "--format", "plain"]).is_empty());
+}
+
+#[test]
+fn newer_version_marker_warns_and_fails_under_strict() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    let marker = temp.child(".skillpack/version");
+    marker.write_str("99.0.0\n").unwrap();
+    let sp = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd.assert()
+    };
+
+    let stdout = sp(&["installed", "--format", "json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert!(
+        json["warnings"][0]
+            .as_str()
+            .unwrap()
+            .contains("last written by skillpack 99.0.0"),
+        "{json}"
+    );
+    sp(&["installed", "--strict"])
+        .failure()
+        .stderr(predicate::str::contains("newer than this"));
+
+    // Writing state never lowers the marker, so the warning stays.
+    sp(&["install", "demo", "--codex"]).success();
+    assert_eq!(std::fs::read_to_string(marker.path()).unwrap(), "99.0.0\n");
+
+    // An older marker is raised to the running version.
+    marker.write_str("0.0.1\n").unwrap();
+    sp(&["install", "demo", "--codex"]).success();
+    assert_eq!(
+        std::fs::read_to_string(marker.path()).unwrap().trim(),
+        env!("CARGO_PKG_VERSION")
+    );
+}
@@ -0,0 +1,132 @@
+use assert_fs::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn show_explain_excludes_lists_removed_ids_per_pattern() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta", "beta-two", "gamma"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"*\"\nexclude:\n  - beta*\n  - gamma\n")
+        .unwrap();
+
+    let show = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo", "--explain-excludes", "--format", format])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    let json: serde_json::Value = serde_json::from_str(&show("json")).unwrap();
+    assert_eq!(
+        json["excluded"],
+        serde_json::json!([["beta*", ["beta", "beta-two"]], ["gamma", ["gamma"]]])
+    );
+    assert!(show("plain").contains("exclude beta* beta beta-two\n"));
+}
+
+#[test]
+fn explain_reports_matching_include_and_exclude() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("skills/general/draft/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - general/**\nexclude:\n  - general/draft\n")
+        .unwrap();
+
+    let run = |skill: &str| {
+        let mut cmd = sp(&temp);
+        cmd.arg("explain")
+            .arg("demo")
+            .arg(skill)
+            .arg("--format")
+            .arg("json");
+        let stdout = cmd.assert().success().get_output().stdout.clone();
+        serde_json::from_slice::<serde_json::Value>(&stdout).unwrap()
+    };
+
+    let writing = run("general/writing");
+    assert_eq!(writing["included"], true);
+    assert_eq!(
+        writing["sources"][0]["include"],
+        serde_json::json!(["general/**"])
+    );
+    assert_eq!(writing["install_name"], "demo__general__writing");
+
+    let draft = run("general/draft");
+    assert_eq!(draft["included"], false);
+    assert_eq!(draft["exclude"], serde_json::json!(["general/draft"]));
+    assert_eq!(draft["reason"], "removed by pack exclude general/draft");
+}
+
+#[test]
+fn normalize_fills_in_install_defaults() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("normalize").arg("demo");
+    let stdout = cmd.assert().success().get_output().stdout.clone();
+    let text = String::from_utf8(stdout).unwrap();
+    assert!(text.contains("  sep: __\n"), "{text}");
+    assert!(text.contains("  prefix: demo\n"), "{text}");
+
+    // The output is itself a loadable pack.
+    let normalized =
+        skillpack::pack::load_pack_str(&text, "normalized.yaml", &Default::default()).unwrap();
+    assert_eq!(normalized.include, vec!["alpha"]);
+    assert_eq!(normalized.install_sep, "__");
+}
+
+#[test]
+fn show_explain_emits_a_decision_record_per_skill() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["a/b", "a__b", "beta"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"**\"\nexclude:\n  - beta\n")
+        .unwrap();
+
+    let stdout = sp(&temp)
+        .args(["show", "demo", "--explain", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    let skills = json["skills"].as_array().unwrap();
+    let ids: Vec<&str> = skills
+        .iter()
+        .map(|skill| skill["skill"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["a/b", "a__b", "beta"]);
+
+    assert_eq!(skills[0]["included"], true);
+    assert_eq!(skills[0]["install_name"], "demo__a__b");
+    assert_eq!(
+        skills[0]["sources"][0]["include"],
+        serde_json::json!(["**"])
+    );
+    assert_eq!(skills[0]["collides_with"], serde_json::json!(["a__b"]));
+    assert_eq!(skills[2]["included"], false);
+    assert_eq!(skills[2]["exclude"], serde_json::json!(["beta"]));
+    assert!(skills[2].get("install_name").is_none());
+    assert!(skills[2]["reason"].is_string());
+}
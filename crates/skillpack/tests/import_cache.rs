@@ -0,0 +1,228 @@
+use assert_fs::prelude::*;
+
+mod common;
+
+use common::run_git;
+
+#[test]
+fn git_timeout_kills_slow_git() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let fake_git = temp.child("bin/git");
+    fake_git.write_str("#!/bin/sh\nexec sleep 30\n").unwrap();
+    std::fs::set_permissions(fake_git.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str("name: demo\nimports:\n  - repo: https://example.invalid/slow.git\n    include:\n      - \"**\"\n")
+        .unwrap();
+
+    let path = format!(
+        "{}:{}",
+        temp.child("bin").path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let started = std::time::Instant::now();
+    assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+        .arg("show")
+        .arg("demo")
+        .arg("--git-timeout")
+        .arg("1")
+        .arg("--root")
+        .arg(repo_root.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("PATH", path)
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("timed out after 1s"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+}
+
+#[test]
+fn update_skips_recopy_when_commit_moves_but_skill_is_identical() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    include:\n      - skills/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let sp = |args: &[&str]| {
+        let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .args(args)
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let sink_arg = sink.path().to_str().unwrap();
+    sp(&[
+        "install", "demo", "--custom", "--path", sink_arg, "--format", "json",
+    ]);
+    // A marker only survives if update leaves the installed folder alone.
+    let marker = sink.child("demo__skills__one/marker");
+    marker.write_str("kept").unwrap();
+
+    remote.child("README.md").write_str("docs").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "docs"], remote.path());
+    let view = sp(&["update", "--format", "json"]);
+    let item = &view["updates"][0];
+    assert_eq!(item["status"], "unchanged");
+    assert_eq!(item["reason"], "commit advanced");
+    assert_eq!(item["changed"], 0);
+    assert!(marker.path().exists());
+
+    remote.child("skills/one/SKILL.md").write_str("y").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "edit"], remote.path());
+    let view = sp(&["update", "--format", "json"]);
+    let item = &view["updates"][0];
+    assert_eq!(item["status"], "updated");
+    assert_eq!(item["changed"], 1);
+    assert!(!marker.path().exists());
+    assert_eq!(
+        std::fs::read_to_string(sink.child("demo__skills__one/SKILL.md").path()).unwrap(),
+        "y"
+    );
+}
+
+#[test]
+fn prune_cache_on_success_removes_only_used_checkouts() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    include:\n      - skills/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+    let cache = temp.child("cache");
+    let unrelated = cache.child("unrelated");
+    unrelated.create_dir_all().unwrap();
+    let sink = temp.child("sink");
+
+    assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+        .args(["install", "demo", "--custom", "--prune-cache-on-success"])
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--root")
+        .arg(repo_root.path())
+        .arg("--cache-dir")
+        .arg(cache.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .assert()
+        .success();
+
+    assert!(sink.child("demo__skills__one/SKILL.md").path().exists());
+    let left: Vec<_> = std::fs::read_dir(cache.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(left, vec![std::ffi::OsString::from("unrelated")]);
+}
+
+#[test]
+fn cache_readonly_uses_cached_checkout_without_fetching() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+    run_git(&["tag", "v1"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let pack = |reference: &str| {
+        repo_root
+            .child("packs/demo.yaml")
+            .write_str(&format!(
+                "name: demo\nimports:\n  - repo: {}\n    ref: {reference}\n    include:\n      - skills/**\n",
+                remote.path().display()
+            ))
+            .unwrap();
+    };
+    let cache = temp.child("cache");
+    let show = |readonly: bool| {
+        let mut cmd = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.args(["show", "demo", "--format", "json"])
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(cache.path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        if readonly {
+            cmd.arg("--cache-readonly");
+        }
+        cmd.assert()
+    };
+
+    pack("v1");
+    show(false).success();
+    let snapshot = |dir: &std::path::Path| {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let modified = entry.metadata().unwrap().modified().unwrap();
+                (entry.path().to_path_buf(), modified)
+            })
+            .collect::<Vec<_>>()
+    };
+    let before = snapshot(cache.path());
+
+    // With the remote gone, any fetch would fail.
+    std::fs::remove_dir_all(remote.path()).unwrap();
+    show(true).success();
+    assert_eq!(snapshot(cache.path()), before);
+
+    pack("v2");
+    show(true)
+        .failure()
+        .stderr(predicates::str::contains("is checked out at"));
+}
@@ -0,0 +1,127 @@
+use assert_fs::prelude::*;
+use skillpack::resolve::{explain_skill, resolve_pack};
+use skillpack::util::make_absolute;
+
+mod common;
+
+use common::run_git;
+
+#[test]
+fn qualified_exclude_drops_one_import_copy() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let mut remotes = Vec::new();
+    for name in ["remote-a", "remote-b"] {
+        let remote = temp.child(name);
+        remote.create_dir_all().unwrap();
+        run_git(&["init"], remote.path());
+        run_git(&["config", "user.email", "test@example.com"], remote.path());
+        run_git(&["config", "user.name", "Test"], remote.path());
+        remote
+            .child("general/writing/SKILL.md")
+            .write_str(name)
+            .unwrap();
+        run_git(&["add", "."], remote.path());
+        run_git(&["commit", "-m", "init"], remote.path());
+        remotes.push(remote.path().display().to_string());
+    }
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let import = |repo: &str| format!("  - repo: {repo}\n    include:\n      - general/**\n");
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n{}{}exclude:\n  - {}#general/writing\n",
+            import(&remotes[0]),
+            import(&remotes[1]),
+            remotes[0]
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let pack_path = repo_root_abs.join("packs/demo.yaml");
+    let resolved = resolve_pack(
+        &repo_root_abs,
+        &pack_path,
+        temp.child("cache").path(),
+        Default::default(),
+    )
+    .unwrap();
+
+    assert_eq!(resolved.final_skills.len(), 1);
+    let skill = &resolved.final_skills[0];
+    assert_eq!(skill.id, "general/writing");
+    assert_eq!(skill.source.to_string(), remotes[1]);
+    assert!(resolved.warnings.is_empty());
+}
+
+#[test]
+fn explain_matches_qualified_pack_excludes_by_source() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote
+        .child("general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+    let remote_path = remote.path().display().to_string();
+
+    let repo_root = temp.child("repo");
+    repo_root
+        .child("skills/general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    // The import drops its own copy and the pack exclude targets that import, so
+    // the local copy stays and the skill is not reported as excluded for it.
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\ninclude:\n  - general/**\nimports:\n  - repo: {remote_path}\n    include:\n      - general/**\n    exclude:\n      - general/writing\nexclude:\n  - {remote_path}#general/writing\n"
+        ))
+        .unwrap();
+    repo_root
+        .child("packs/remote-only.yaml")
+        .write_str(&format!(
+            "name: remote-only\nimports:\n  - repo: {remote_path}\n    include:\n      - general/**\n    exclude:\n      - general/writing\nexclude:\n  - {remote_path}#general/writing\n"
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let explain = |pack: &str| {
+        let pack_path = repo_root_abs.join(format!("packs/{pack}.yaml"));
+        let resolved = resolve_pack(
+            &repo_root_abs,
+            &pack_path,
+            temp.child("cache").path(),
+            Default::default(),
+        )
+        .unwrap();
+        explain_skill(&resolved, "general/writing").unwrap()
+    };
+
+    let local = explain("demo");
+    assert!(local.exclude.is_empty(), "{:?}", local.exclude);
+    assert_eq!(
+        local.install_name.as_deref(),
+        Some("demo__general__writing")
+    );
+
+    // With no source offering the skill, the qualified exclude still matches
+    // the import it names.
+    let remote_only = explain("remote-only");
+    assert_eq!(
+        remote_only.exclude,
+        vec![format!("{remote_path}#general/writing")]
+    );
+    assert_eq!(
+        remote_only.reason,
+        Some(format!(
+            "removed by import exclude general/writing ({remote_path})"
+        ))
+    );
+}
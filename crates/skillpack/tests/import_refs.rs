@@ -0,0 +1,159 @@
+use assert_fs::prelude::*;
+use skillpack::resolve::resolve_pack;
+use skillpack::util::make_absolute;
+use std::process::Command;
+
+mod common;
+
+use common::run_git;
+
+#[test]
+fn pinned_commit_records_tag_name() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+    run_git(&["tag", "v1.0"], remote.path());
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(remote.path())
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(commit.stdout).unwrap().trim().to_string();
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    ref: {}\n    include:\n      - skills/**\n",
+            remote.path().display(),
+            commit
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let pack_path = repo_root_abs.join("packs/demo.yaml");
+    let cache_dir = repo_root_abs.join("cache");
+
+    let resolved =
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap();
+    let import = &resolved.imports[0];
+    assert_eq!(import.commit, commit);
+    assert_eq!(import.ref_name.as_deref(), Some(commit.as_str()));
+    assert_eq!(import.display_ref.as_deref(), Some("v1.0"));
+}
+
+#[test]
+fn short_commit_ref_records_full_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(remote.path())
+        .output()
+        .unwrap();
+    let commit = String::from_utf8(commit.stdout).unwrap().trim().to_string();
+    remote.child("skills/two/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "second"], remote.path());
+
+    let short = &commit[..7];
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    ref: {short}\n    include:\n      - skills/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let pack_path = repo_root_abs.join("packs/demo.yaml");
+    let cache_dir = repo_root_abs.join("cache");
+
+    let resolved =
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap();
+    let import = &resolved.imports[0];
+    assert_eq!(import.commit, commit);
+    assert_eq!(import.ref_name.as_deref(), Some(short));
+    assert_eq!(import.skills.len(), 1);
+}
+
+#[test]
+fn repo_ref_overrides_every_import_ref() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let mut remotes = Vec::new();
+    for name in ["first", "second"] {
+        let remote = temp.child(name);
+        remote.create_dir_all().unwrap();
+        run_git(&["init"], remote.path());
+        run_git(&["config", "user.email", "test@example.com"], remote.path());
+        run_git(&["config", "user.name", "Test"], remote.path());
+        remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+        run_git(&["add", "."], remote.path());
+        run_git(&["commit", "-m", "init"], remote.path());
+        run_git(&["tag", "v1"], remote.path());
+        run_git(&["checkout", "-b", "next"], remote.path());
+        remote.child("skills/two/SKILL.md").write_str("x").unwrap();
+        run_git(&["add", "."], remote.path());
+        run_git(&["commit", "-m", "next"], remote.path());
+        remotes.push(remote);
+    }
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let imports: String = remotes
+        .iter()
+        .map(|remote| {
+            format!(
+                "  - repo: {}\n    ref: v1\n    include:\n      - skills/**\n",
+                remote.path().display()
+            )
+        })
+        .collect();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!("name: demo\nimports:\n{imports}"))
+        .unwrap();
+
+    let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+        .args(["show", "demo", "--repo-ref", "next", "--format", "json"])
+        .arg("--root")
+        .arg(repo_root.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let imports = json["imports"].as_array().unwrap();
+    assert_eq!(imports.len(), 2);
+    for import in imports {
+        assert_eq!(import["reference"], "next");
+        assert_eq!(import["ref_overridden"], true);
+        assert_eq!(
+            import["skills"],
+            serde_json::json!(["skills/one", "skills/two"])
+        );
+    }
+}
@@ -1,16 +1,10 @@
 use assert_fs::prelude::*;
 use skillpack::resolve::resolve_pack;
 use skillpack::util::make_absolute;
-use std::process::Command;
 
-fn run_git(args: &[&str], dir: &std::path::Path) {
-    let status = Command::new("git")
-        .args(args)
-        .current_dir(dir)
-        .status()
-        .unwrap();
-    assert!(status.success());
-}
+mod common;
+
+use common::run_git;
 
 #[test]
 fn resolves_imported_skills() {
@@ -48,7 +42,8 @@ fn resolves_imported_skills() {
     let pack_path = repo_root_abs.join("packs/demo.yaml");
     let cache_dir = repo_root_abs.join("cache");
 
-    let resolved = resolve_pack(&repo_root_abs, &pack_path, &cache_dir).unwrap();
+    let resolved =
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap();
     assert_eq!(resolved.imports.len(), 1);
     let import = &resolved.imports[0];
     assert_eq!(import.skills.len(), 1);
@@ -88,10 +83,392 @@ fn resolves_imported_skills_without_local_include() {
     let pack_path = repo_root_abs.join("packs/demo.yaml");
     let cache_dir = repo_root_abs.join("cache");
 
-    let resolved = resolve_pack(&repo_root_abs, &pack_path, &cache_dir).unwrap();
+    let resolved =
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap();
     assert_eq!(resolved.imports.len(), 1);
     let import = &resolved.imports[0];
     assert_eq!(import.skills.len(), 1);
     assert_eq!(import.skills[0].id, "tools/agent/skills/general/writing");
     assert!(resolved.local.is_empty());
 }
+
+#[test]
+fn warns_when_import_duplicates_local_id() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+
+    remote.child("shared/tool/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root
+        .child("skills/shared/tool/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\ninclude:\n  - shared/**\nimports:\n  - repo: {}\n    include:\n      - shared/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let pack_path = repo_root_abs.join("packs/demo.yaml");
+    let cache_dir = repo_root_abs.join("cache");
+
+    let resolved =
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap();
+    assert_eq!(resolved.final_skills.len(), 1);
+    assert!(matches!(
+        resolved.final_skills[0].source,
+        skillpack::resolve::SkillSource::Local
+    ));
+    assert_eq!(resolved.warnings.len(), 1);
+    let warning = &resolved.warnings[0];
+    assert!(warning.contains("duplicate skill id shared/tool"));
+    assert!(warning.contains("using local"));
+    assert!(warning.contains(&format!("ignoring {}", remote.path().display())));
+}
+
+#[test]
+fn show_orders_imports_independent_of_pack_order() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let mut remotes = Vec::new();
+    for name in ["remote-a", "remote-b"] {
+        let remote = temp.child(name);
+        remote.create_dir_all().unwrap();
+        run_git(&["init"], remote.path());
+        run_git(&["config", "user.email", "test@example.com"], remote.path());
+        run_git(&["config", "user.name", "Test"], remote.path());
+        remote
+            .child(format!("{name}/skill/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+        run_git(&["add", "."], remote.path());
+        run_git(&["commit", "-m", "init"], remote.path());
+        remotes.push(remote.path().display().to_string());
+    }
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let import = |repo: &str| format!("  - repo: {repo}\n    include:\n      - \"**\"\n");
+    repo_root
+        .child("packs/forward.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n{}{}",
+            import(&remotes[0]),
+            import(&remotes[1])
+        ))
+        .unwrap();
+    repo_root
+        .child("packs/reverse.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n{}{}",
+            import(&remotes[1]),
+            import(&remotes[0])
+        ))
+        .unwrap();
+
+    let show = |pack: &str| {
+        let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .arg("show")
+            .arg(pack)
+            .arg("--format")
+            .arg("json")
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let mut view: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        view["pack"]["file"] = serde_json::Value::Null;
+        view
+    };
+
+    let forward = show("forward");
+    assert_eq!(forward, show("reverse"));
+    assert_eq!(forward["imports"][0]["repo"], remotes[0].as_str());
+}
+
+#[test]
+fn show_max_skills_truncates_import_listing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    for name in ["a", "b", "c", "d", "e"] {
+        remote
+            .child(format!("skills/{name}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    include:\n      - skills/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+
+    let show = |extra: &[&str]| {
+        let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .arg("show")
+            .arg("demo")
+            .args(extra)
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let pretty = show(&["--max-skills", "2"]);
+    assert!(pretty.contains("skills/b"));
+    assert!(!pretty.contains("skills/c"));
+    assert!(pretty.contains("... and 3 more"));
+
+    let json = show(&["--max-skills", "2", "--format", "json"]);
+    assert!(json.contains("skills/e"));
+}
+
+#[test]
+fn show_local_only_omits_imports() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote
+        .child("remote/skill/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root
+        .child("skills/local/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\ninclude:\n  - local/**\nimports:\n  - repo: {}\n    include:\n      - remote/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+
+    let show = |filter: &str, format: &str| {
+        let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .arg("show")
+            .arg("demo")
+            .arg(filter)
+            .arg("--format")
+            .arg(format)
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let pretty = show("--local-only", "pretty");
+    assert!(pretty.contains("Local"));
+    assert!(!pretty.contains("Imports"));
+    assert!(pretty.contains("demo__local"));
+    assert!(!pretty.contains("demo__remote__skill"));
+
+    let json: serde_json::Value = serde_json::from_str(&show("--imports-only", "json")).unwrap();
+    assert_eq!(json["local"], serde_json::json!([]));
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["demo__remote__skill"])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn aliased_import_resolves_like_inline_import() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote
+        .child("general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+    run_git(&["tag", "v1"], remote.path());
+    remote
+        .child("general/later/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "later"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("sources.yaml")
+        .write_str(&format!(
+            "shared:\n  repo: {}\n  ref: v1\n",
+            remote.path().display()
+        ))
+        .unwrap();
+    repo_root
+        .child("packs/aliased.yaml")
+        .write_str(
+            "name: aliased\nimports:\n  - repo: \"@shared\"\n    include:\n      - general/**\n",
+        )
+        .unwrap();
+    repo_root
+        .child("packs/inline.yaml")
+        .write_str(&format!(
+            "name: inline\nimports:\n  - repo: {}\n    ref: v1\n    include:\n      - general/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let cache_dir = repo_root_abs.join("cache");
+    let resolve = |pack: &str| {
+        let pack_path = repo_root_abs.join(format!("packs/{pack}.yaml"));
+        resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap()
+    };
+    let aliased = resolve("aliased");
+    let inline = resolve("inline");
+
+    let (aliased, inline) = (&aliased.imports[0], &inline.imports[0]);
+    assert_eq!(aliased.repo, inline.repo);
+    assert_eq!(aliased.ref_name.as_deref(), Some("v1"));
+    assert_eq!(aliased.commit, inline.commit);
+    let ids = |import: &skillpack::resolve::ResolvedImport| {
+        import
+            .skills
+            .iter()
+            .map(|skill| skill.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(aliased), vec!["general/writing"]);
+    assert_eq!(ids(aliased), ids(inline));
+}
+
+#[test]
+fn import_only_pack_installs_with_empty_skills_dir() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    include:\n      - skills/**\n",
+            remote.path().display()
+        ))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_dir(repo_root.child("skills").path())
+            .unwrap()
+            .count(),
+        0
+    );
+    let sink = temp.child("sink");
+
+    let sp = |args: &[&str]| {
+        let output = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .args(args)
+            .arg("--format")
+            .arg("json")
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    let packs = sp(&["packs", "--with-skills"]);
+    assert_eq!(packs["packs"][0]["skills"], serde_json::json!([]));
+    let show = sp(&["show", "demo"]);
+    assert_eq!(show["local"], serde_json::json!([]));
+    assert_eq!(
+        show["final_install_names"],
+        serde_json::json!(["demo__skills__one"])
+    );
+    sp(&[
+        "install",
+        "demo",
+        "--custom",
+        "--path",
+        sink.path().to_str().unwrap(),
+    ]);
+    assert!(sink.child("demo__skills__one/SKILL.md").path().exists());
+}
+
+#[test]
+fn invalid_import_pattern_fails_before_clone() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!(
+            "name: demo\nimports:\n  - repo: {}\n    include:\n      - skills/**x\n",
+            temp.child("never-cloned").path().display()
+        ))
+        .unwrap();
+
+    let repo_root_abs = make_absolute(repo_root.path()).unwrap();
+    let pack_path = repo_root_abs.join("packs/demo.yaml");
+    let cache_dir = repo_root_abs.join("cache");
+
+    let err = resolve_pack(&repo_root_abs, &pack_path, &cache_dir, Default::default()).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid pattern: skills/**x"),
+        "{err}"
+    );
+    assert!(!cache_dir.exists());
+}
@@ -1,5 +1,7 @@
 use assert_fs::prelude::*;
-use skillpack::install::{install_pack, uninstall_pack};
+use skillpack::install::{
+    InstallOptions, find_orphans, install_pack, install_to_sink, uninstall_pack,
+};
 use skillpack::pack::Pack;
 use skillpack::resolve::{ResolvedPack, ResolvedSkill, SkillSource};
 use skillpack::state::StateFile;
@@ -10,21 +12,35 @@ fn base_pack() -> Pack {
     Pack {
         name: "demo".to_string(),
         include: vec![],
+        include_agents: Default::default(),
         exclude: vec![],
         imports: vec![],
         install_prefix: "demo".to_string(),
         install_sep: "__".to_string(),
         install_flatten: false,
+        install_prefix_mode: Default::default(),
+        install_subdir: None,
+        install_preserve: vec![],
+        rename: Default::default(),
+        agents: vec![],
+        requires: vec![],
+        warnings: vec![],
     }
 }
 
 fn resolved_pack(skill: ResolvedSkill, pack_file: PathBuf) -> ResolvedPack {
     ResolvedPack {
         pack: base_pack(),
+        options: Default::default(),
         pack_file,
         local: vec![],
         imports: vec![],
         final_skills: vec![skill],
+        renamed: vec![],
+        agent_only: Default::default(),
+        excluded: vec![],
+        warnings: vec![],
+        phase_times: vec![],
     }
 }
 
@@ -49,10 +65,57 @@ fn install_errors_on_unowned_dest() {
     let pack = resolved_pack(skill, temp.child("packs/demo.yaml").path().to_path_buf());
     let mut state = StateFile::default();
 
-    let err = install_pack(&pack, "codex", sink.path(), &mut state).unwrap_err();
+    let err = install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap_err();
     assert!(err.to_string().contains("not owned"));
 }
 
+#[test]
+fn install_force_takes_over_unowned_dest() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    let skill_dir = temp.child("skill");
+    skill_dir.create_dir_all().unwrap();
+    skill_dir.child("SKILL.md").write_str("x").unwrap();
+
+    let dest = sink.child(install_name("demo", "__", "a/b", false));
+    dest.child("stale.txt").write_str("old").unwrap();
+
+    let skill = ResolvedSkill {
+        id: "a/b".to_string(),
+        dir: skill_dir.path().to_path_buf(),
+        source: SkillSource::Local,
+    };
+    let pack = resolved_pack(skill, temp.child("packs/demo.yaml").path().to_path_buf());
+    let mut state = StateFile::default();
+    let options = InstallOptions {
+        force: true,
+        ..InstallOptions::default()
+    };
+
+    let record = install_pack(&pack, "codex", sink.path(), &mut state, &options).unwrap();
+    assert!(dest.child("SKILL.md").exists());
+    assert!(!dest.child("stale.txt").exists());
+    assert_eq!(
+        record.installed_paths,
+        vec![dest.path().display().to_string()]
+    );
+    assert!(skillpack::state::record_owned_path(
+        &state,
+        sink.path(),
+        "demo",
+        dest.path()
+    ));
+}
+
 #[test]
 fn install_reconciles_old_paths() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -85,17 +148,186 @@ fn install_reconciles_old_paths() {
         prefix: "demo".to_string(),
         sep: "__".to_string(),
         flatten: false,
+        frozen: false,
         imports: vec![],
         installed_paths: vec![old_path.path().display().to_string()],
         installed_at: "2025-01-01T00:00:00Z".to_string(),
+        hashes: Default::default(),
+        namespace: None,
+        with_skill_deps: false,
+        remote: None,
     });
 
     let pack = resolved_pack(skill, pack_file.path().to_path_buf());
-    install_pack(&pack, "codex", sink.path(), &mut state).unwrap();
+    install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap();
 
     assert!(!old_path.path().exists());
 }
 
+#[test]
+fn install_flatten_pack_twice_reconciles_leaf_paths() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+    let skill = |id: &str| {
+        let dir = temp.child(format!("skills/{id}"));
+        dir.child("SKILL.md").write_str("x").unwrap();
+        ResolvedSkill {
+            id: id.to_string(),
+            dir: dir.path().to_path_buf(),
+            source: SkillSource::Local,
+        }
+    };
+    let mut pack = resolved_pack(
+        skill("general/old"),
+        temp.child("packs/demo.yaml").path().to_path_buf(),
+    );
+    pack.pack.install_flatten = true;
+    pack.final_skills.push(skill("general/kept"));
+    let mut state = StateFile::default();
+
+    install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap();
+    assert!(sink.child("demo__old").path().exists());
+
+    pack.final_skills = vec![skill("general/kept"), skill("coding/new")];
+    let record = install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap();
+
+    assert!(!sink.child("demo__old").path().exists());
+    assert!(sink.child("demo__kept/SKILL.md").path().exists());
+    assert!(sink.child("demo__new/SKILL.md").path().exists());
+    assert_eq!(
+        record.installed_paths,
+        vec![
+            sink.child("demo__kept").path().display().to_string(),
+            sink.child("demo__new").path().display().to_string(),
+        ]
+    );
+    assert_eq!(state.installs.len(), 1);
+}
+
+#[test]
+fn install_no_reconcile_keeps_old_paths() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+    let old_path = sink.child("demo__old");
+    old_path.child("SKILL.md").write_str("old").unwrap();
+
+    let skill_dir = temp.child("skill");
+    skill_dir.child("SKILL.md").write_str("x").unwrap();
+    let skill = ResolvedSkill {
+        id: "new".to_string(),
+        dir: skill_dir.path().to_path_buf(),
+        source: SkillSource::Local,
+    };
+    let pack_file = temp.child("packs/demo.yaml").path().to_path_buf();
+
+    let mut state = StateFile::default();
+    state.installs.push(skillpack::state::InstallRecord {
+        sink: "codex".to_string(),
+        sink_path: sink.path().display().to_string(),
+        pack: "demo".to_string(),
+        pack_file: pack_file.display().to_string(),
+        prefix: "demo".to_string(),
+        sep: "__".to_string(),
+        flatten: false,
+        frozen: false,
+        imports: vec![],
+        installed_paths: vec![old_path.path().display().to_string()],
+        installed_at: "2025-01-01T00:00:00Z".to_string(),
+        hashes: Default::default(),
+        namespace: None,
+        with_skill_deps: false,
+        remote: None,
+    });
+
+    let pack = resolved_pack(skill, pack_file);
+    let options = InstallOptions {
+        no_reconcile: true,
+        ..InstallOptions::default()
+    };
+    let view = install_to_sink(
+        &pack,
+        &pack.pack_file,
+        "codex",
+        sink.path(),
+        &mut state,
+        &options,
+    )
+    .unwrap();
+
+    assert!(old_path.child("SKILL.md").path().exists());
+    assert_eq!(view.removed, 0);
+    let new_path = sink.child("demo__new").path().display().to_string();
+    assert_eq!(state.installs[0].installed_paths, vec![new_path]);
+}
+
+#[test]
+fn install_to_sink_returns_change_summary() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+
+    let skill_dir = temp.child("skill");
+    skill_dir.create_dir_all().unwrap();
+    skill_dir.child("SKILL.md").write_str("x").unwrap();
+
+    let skill = ResolvedSkill {
+        id: "alpha".to_string(),
+        dir: skill_dir.path().to_path_buf(),
+        source: SkillSource::Local,
+    };
+    let pack_file = temp.child("packs/demo.yaml").path().to_path_buf();
+    let pack = resolved_pack(skill, pack_file.clone());
+    let mut state = StateFile::default();
+    let options = InstallOptions::default();
+
+    let first = install_to_sink(
+        &pack,
+        &pack_file,
+        "codex",
+        sink.path(),
+        &mut state,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(first.sink, "codex");
+    assert_eq!(first.pack.name, "demo");
+    assert_eq!(first.added_names, vec!["demo__alpha".to_string()]);
+    assert_eq!((first.added, first.updated, first.removed), (1, 0, 0));
+
+    let second = install_to_sink(
+        &pack,
+        &pack_file,
+        "codex",
+        sink.path(),
+        &mut state,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(second.updated_names, vec!["demo__alpha".to_string()]);
+    assert_eq!((second.added, second.updated, second.removed), (0, 1, 0));
+    assert_eq!(state.installs.len(), 1);
+}
+
 #[test]
 fn uninstall_removes_recorded_paths() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -114,9 +346,14 @@ fn uninstall_removes_recorded_paths() {
         prefix: "demo".to_string(),
         sep: "__".to_string(),
         flatten: false,
+        frozen: false,
         imports: vec![],
         installed_paths: vec![installed.path().display().to_string()],
         installed_at: "2025-01-01T00:00:00Z".to_string(),
+        hashes: Default::default(),
+        namespace: None,
+        with_skill_deps: false,
+        remote: None,
     });
 
     let record = uninstall_pack(&mut state, sink.path(), "demo").unwrap();
@@ -125,6 +362,76 @@ fn uninstall_removes_recorded_paths() {
     assert_eq!(record.pack, "demo");
 }
 
+#[test]
+fn install_subdir_nests_skill_folders() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+    let skill_dir = temp.child("skill");
+    skill_dir.child("SKILL.md").write_str("x").unwrap();
+
+    let mut pack = resolved_pack(
+        ResolvedSkill {
+            id: "a/b".to_string(),
+            dir: skill_dir.path().to_path_buf(),
+            source: SkillSource::Local,
+        },
+        temp.child("packs/demo.yaml").path().to_path_buf(),
+    );
+    pack.pack.install_subdir = Some("packs/demo".to_string());
+    let mut state = StateFile::default();
+
+    let record = install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap();
+    let nested = sink.child("packs/demo/demo__a__b");
+    assert!(nested.child("SKILL.md").path().exists());
+    assert_eq!(record.sink_path, sink.path().display().to_string());
+    assert_eq!(
+        record.installed_paths,
+        vec![nested.path().display().to_string()]
+    );
+    assert!(find_orphans(&state, sink.path()).unwrap().is_empty());
+
+    uninstall_pack(&mut state, sink.path(), "demo").unwrap();
+    assert!(!sink.child("packs").path().exists());
+    assert!(sink.path().exists());
+}
+
+#[cfg(windows)]
+#[test]
+fn reserved_folder_name_fails_before_creating_the_sink() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skill_dir = temp.child("skill");
+    skill_dir.child("SKILL.md").write_str("x").unwrap();
+    let skill = ResolvedSkill {
+        id: "md".to_string(),
+        dir: skill_dir.path().to_path_buf(),
+        source: SkillSource::Local,
+    };
+    let mut pack = resolved_pack(skill, temp.child("packs/demo.yaml").path().to_path_buf());
+    // `CON.md` is a reserved device name.
+    pack.pack.install_prefix = "CON".to_string();
+    pack.pack.install_sep = ".".to_string();
+    let mut state = StateFile::default();
+
+    let sink = temp.child("sink");
+    let err = install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("reserved device name"), "{err}");
+    assert!(!sink.path().exists());
+}
+
 #[cfg(unix)]
 #[test]
 fn copy_symlink_as_file() {
@@ -152,7 +459,14 @@ fn copy_symlink_as_file() {
     let pack = resolved_pack(skill, temp.child("packs/demo.yaml").path().to_path_buf());
     let mut state = StateFile::default();
 
-    install_pack(&pack, "codex", sink.path(), &mut state).unwrap();
+    install_pack(
+        &pack,
+        "codex",
+        sink.path(),
+        &mut state,
+        &InstallOptions::default(),
+    )
+    .unwrap();
 
     let dest = sink.child(install_name("demo", "__", "a/b", false));
     let link = dest.child("link.txt");
@@ -0,0 +1,534 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::{setup_bundled_repo, sp};
+
+#[test]
+fn install_hides_zero_counters() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("added")
+            .and(predicate::str::contains("1"))
+            .and(predicate::str::contains("updated").not())
+            .and(predicate::str::contains("removed").not()),
+    );
+}
+
+#[test]
+fn install_bundled_pack() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    setup_bundled_repo(&temp);
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("Installed")
+            .and(predicate::str::contains("demo"))
+            .and(predicate::str::contains("added"))
+            .and(predicate::str::contains("1")),
+    );
+}
+
+#[test]
+fn install_targets_env_sink_override() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("env-sink");
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--codex")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("SKILLPACK_SINK_CODEX", sink.path());
+    cmd.assert().success();
+    assert!(sink.child("demo__alpha").exists());
+    assert!(!temp.child(".codex/skills/demo__alpha").exists());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("config")
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .env("SKILLPACK_SINK_CODEX", sink.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("(env)"));
+}
+
+#[test]
+fn install_verbose_lists_removed_names() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    let pack = temp.child("packs/demo.yaml");
+    pack.write_str("name: demo\ninclude:\n  - alpha/**\n  - beta/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let install = || {
+        let mut cmd = sp(&temp);
+        cmd.arg("install")
+            .arg("demo")
+            .arg("--custom")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--verbose")
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd
+    };
+    install().assert().success();
+
+    pack.write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    install().assert().success().stdout(
+        predicate::str::contains("- demo__beta").and(predicate::str::contains("~ demo__alpha")),
+    );
+}
+
+#[test]
+fn install_reads_pack_from_stdin() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    let sink = temp.child("sink");
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("-")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .write_stdin("name: piped\ninclude:\n  - alpha/**\n");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Installed").and(predicate::str::contains("piped")));
+    assert!(sink.child("piped__alpha").exists());
+
+    let state = std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap();
+    assert!(state.contains("\"pack_file\": \"<stdin>\""));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("uninstall")
+        .arg("piped")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert().success();
+    assert!(!sink.child("piped__alpha").exists());
+}
+
+#[test]
+fn install_watch_recopies_changed_skill() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("v1").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    let installed = sink.child("demo__alpha/SKILL.md");
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+        .arg("install")
+        .arg("demo")
+        .arg("--watch")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--root")
+        .arg(temp.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let wait_for = |expected: &str| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while std::time::Instant::now() < deadline {
+            if std::fs::read_to_string(installed.path()).is_ok_and(|text| text == expected) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        false
+    };
+    let initial = wait_for("v1");
+    temp.child("skills/alpha/SKILL.md").write_str("v2").unwrap();
+    let updated = initial && wait_for("v2");
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(initial, "initial install did not happen");
+    assert!(updated, "watch did not reinstall the changed skill");
+}
+
+#[cfg(unix)]
+#[test]
+fn install_watch_survives_a_failed_reinstall_and_ends_on_ctrl_c() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("v1").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    let folder = sink.child("demo__alpha");
+    let stderr = temp.child("stderr.log");
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+        .args(["install", "demo", "--watch", "--custom", "--path"])
+        .arg(sink.path())
+        .arg("--root")
+        .arg(temp.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::fs::File::create(stderr.path()).unwrap())
+        .spawn()
+        .unwrap();
+
+    let wait_for = |done: &dyn Fn() -> bool| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while std::time::Instant::now() < deadline {
+            if done() {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        false
+    };
+    let installed = |expected: &str| {
+        std::fs::read_to_string(folder.child("SKILL.md").path()).is_ok_and(|text| text == expected)
+    };
+    let initial = wait_for(&|| installed("v1"));
+
+    // A file where the folder was makes the next reinstall fail.
+    std::fs::remove_dir_all(folder.path()).unwrap();
+    folder.write_str("in the way").unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("v2").unwrap();
+    let failed = initial
+        && wait_for(&|| {
+            std::fs::read_to_string(stderr.path())
+                .is_ok_and(|log| log.contains("reinstall failed, still watching"))
+        });
+
+    std::fs::remove_file(folder.path()).unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("v3").unwrap();
+    let recovered = failed && wait_for(&|| installed("v3"));
+
+    let interrupted = std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap()
+        .success();
+    let status = child.wait().unwrap();
+
+    assert!(initial, "initial install did not happen");
+    assert!(failed, "the failed reinstall was not reported");
+    assert!(recovered, "watch stopped after the failed reinstall");
+    use std::os::unix::process::ExitStatusExt;
+    assert!(
+        interrupted && status.signal() == Some(2),
+        "Ctrl-C did not end the watch: {status}"
+    );
+    // The last reinstall's state is intact.
+    let state: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state["installs"].as_array().unwrap().len(), 1);
+    assert!(installed("v3"));
+}
+
+#[test]
+fn link_manifest_indexes_install_and_prunes_on_uninstall_and_migrate() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    let index = temp.child(".skillpack/index.json");
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args)
+            .arg("demo")
+            .arg("--custom")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert().success();
+    };
+    let read_index = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(index.path()).unwrap()).unwrap()
+    };
+
+    run(&["install", "--link-manifest"]);
+    let entry = &read_index()["skills"]["demo__alpha"][0];
+    assert_eq!(entry["sink"], "custom");
+    assert_eq!(entry["pack"], "demo");
+    assert_eq!(entry["source"], "local");
+    assert_eq!(
+        entry["path"],
+        sink.path().join("demo__alpha").display().to_string()
+    );
+
+    run(&["uninstall"]);
+    assert_eq!(read_index()["skills"], serde_json::json!({}));
+
+    // Moving the sink with migrate-state drops index entries for the old path.
+    run(&["install", "--link-manifest"]);
+    sp(&temp)
+        .arg("migrate-state")
+        .arg(sink.path())
+        .arg(temp.child("moved").path())
+        .arg("--move")
+        .assert()
+        .success();
+    assert_eq!(read_index()["skills"], serde_json::json!({}));
+}
+
+#[test]
+fn install_progress_lists_copied_skills_on_stderr() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md")
+        .write_str("abc")
+        .unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"**\"\n")
+        .unwrap();
+
+    let install = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args([
+            "install",
+            "demo",
+            "--codex",
+            "--progress",
+            "--format",
+            format,
+        ]);
+        let output = cmd.assert().success().get_output().clone();
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let stderr = install("plain");
+    assert!(stderr.contains("copied alpha (3 bytes)"), "{stderr}");
+    assert!(stderr.contains("copied beta (1 bytes)"), "{stderr}");
+
+    let stderr = install("json");
+    assert!(!stderr.contains("copied"), "{stderr}");
+}
+
+#[test]
+fn install_no_state_copies_without_writing_state() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+
+    let install = |force: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(["install", "demo", "--codex", "--no-state"]);
+        if force {
+            cmd.arg("--force");
+        }
+        cmd.assert()
+    };
+
+    install(false).success();
+    assert!(temp.child(".codex/skills/demo__alpha/SKILL.md").exists());
+    assert!(!temp.child(".skillpack/state.json").exists());
+
+    // Nothing recorded ownership, so the second copy needs --force.
+    install(false).failure();
+    install(true).success();
+    assert!(!temp.child(".skillpack/state.json").exists());
+
+    // `requires:` is checked against recorded installs, which stay untouched.
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/base.yaml")
+        .write_str("name: base\ninclude:\n  - beta\n")
+        .unwrap();
+    temp.child("packs/app.yaml")
+        .write_str("name: app\ninclude:\n  - alpha\nrequires: [base]\n")
+        .unwrap();
+    let app = || {
+        sp(&temp)
+            .args(["install", "app", "--codex", "--no-state"])
+            .assert()
+    };
+    app().failure().stderr(predicate::str::contains(
+        "requires base, which is not installed",
+    ));
+    sp(&temp)
+        .args(["install", "base", "--codex"])
+        .assert()
+        .success();
+    let state = std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap();
+    app().success();
+    assert!(temp.child(".codex/skills/app__alpha/SKILL.md").exists());
+    assert_eq!(
+        std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap(),
+        state
+    );
+}
+
+#[test]
+fn install_namespace_prefixes_folders_and_uninstalls_them() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+
+    let sp = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd.assert().success();
+    };
+
+    sp(&["install", "demo", "--codex", "--namespace", "ada"]);
+    let folder = temp.child(".codex/skills/ada__demo__alpha");
+    assert!(folder.child("SKILL.md").exists());
+    assert!(!temp.child(".codex/skills/demo__alpha").exists());
+
+    // Update reinstalls under the recorded namespace.
+    temp.child("skills/alpha/SKILL.md").write_str("y").unwrap();
+    sp(&["update", "--codex"]);
+    assert_eq!(
+        std::fs::read_to_string(folder.child("SKILL.md").path()).unwrap(),
+        "y"
+    );
+
+    sp(&["uninstall", "demo", "--codex"]);
+    assert!(!folder.exists());
+}
+
+#[test]
+fn preserve_on_reconcile_keeps_matching_files_in_installed_folders() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/alpha/notes.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    let pack = temp.child("packs/demo.yaml");
+    pack.write_str(
+        "name: demo\ninclude:\n  - alpha\n  - beta\ninstall:\n  preserve_on_reconcile: [.env]\n",
+    )
+    .unwrap();
+
+    let install = || {
+        let mut cmd = sp(&temp);
+        cmd.args(["install", "demo", "--codex"]);
+        cmd.assert().success();
+    };
+    install();
+    let alpha = temp.child(".codex/skills/demo__alpha");
+    let beta = temp.child(".codex/skills/demo__beta");
+    alpha.child(".env").write_str("TOKEN=1").unwrap();
+    beta.child(".env").write_str("TOKEN=2").unwrap();
+
+    // Reinstall replaces skill files but leaves the preserved one.
+    temp.child("skills/alpha/SKILL.md").write_str("y").unwrap();
+    std::fs::remove_file(temp.child("skills/alpha/notes.md").path()).unwrap();
+    install();
+    assert_eq!(
+        std::fs::read_to_string(alpha.child("SKILL.md").path()).unwrap(),
+        "y"
+    );
+    assert!(!alpha.child("notes.md").exists());
+    assert_eq!(
+        std::fs::read_to_string(alpha.child(".env").path()).unwrap(),
+        "TOKEN=1"
+    );
+
+    // Reconcile strips a dropped skill down to its preserved files.
+    pack.write_str("name: demo\ninclude:\n  - alpha\ninstall:\n  preserve_on_reconcile: [.env]\n")
+        .unwrap();
+    install();
+    assert!(!beta.child("SKILL.md").exists());
+    assert!(beta.child(".env").exists());
+}
+
+#[test]
+fn timings_lists_each_install_phase_on_stderr() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+
+    let output = sp(&temp)
+        .args([
+            "install",
+            "demo",
+            "--codex",
+            "--timings",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // stdout stays a single install document.
+    let stdout: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(stdout.get("phases").is_none());
+    let timings: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    let phases: Vec<&str> = timings["phases"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|timing| timing["phase"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        phases,
+        vec![
+            "discover",
+            "resolve imports",
+            "collision check",
+            "copy",
+            "state write"
+        ]
+    );
+}
@@ -0,0 +1,255 @@
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn install_warns_about_foreign_entries_in_new_sink() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.child("notes.txt").write_str("mine").unwrap();
+
+    let install = |strict: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(["install", "demo", "--custom", "--format", "json", "--path"])
+            .arg(sink.path());
+        if strict {
+            cmd.arg("--strict");
+        }
+        cmd.assert()
+    };
+
+    install(true)
+        .failure()
+        .stderr(predicate::str::contains("did not install (notes.txt)"));
+    assert!(!sink.child("demo__alpha").path().exists());
+
+    let stdout = install(false).success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    let warnings = json["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].as_str().unwrap().contains("(notes.txt)"),
+        "{warnings:?}"
+    );
+    assert!(sink.child("demo__alpha/SKILL.md").path().exists());
+
+    // Once skillpack has a record for the sink, later installs stay quiet.
+    let stdout = install(true).success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["warnings"], serde_json::json!([]));
+}
+
+#[test]
+fn strict_foreign_entries_in_a_later_sink_fail_before_any_copy() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    temp.child(".claude/skills/notes.txt")
+        .write_str("mine")
+        .unwrap();
+
+    sp(&temp)
+        .args(["install", "demo", "--codex", "--claude", "--strict"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did not install (notes.txt)"));
+    assert!(!temp.child(".codex/skills/demo__alpha").exists());
+    assert!(!temp.child(".skillpack/state.json").exists());
+}
+
+#[test]
+fn install_prints_finished_sinks_when_a_later_sink_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    temp.child(".claude/skills/demo__alpha/SKILL.md")
+        .write_str("mine")
+        .unwrap();
+
+    sp(&temp)
+        .args(["install", "demo", "--codex", "--claude"])
+        .args(["--format", "compact-json"])
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains("\"sink\":\"codex\"")
+                .and(predicate::str::contains("\"sink\":\"claude\"").not()),
+        )
+        .stderr(predicate::str::contains(
+            "destination exists but is not owned by pack",
+        ));
+    assert!(temp.child(".codex/skills/demo__alpha/SKILL.md").exists());
+}
+
+#[test]
+fn install_uses_pack_default_agents_without_flags() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\nagents: [codex, claude]\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install").arg("demo");
+    cmd.assert().success();
+
+    assert!(
+        temp.child(".codex/skills/demo__alpha/SKILL.md")
+            .path()
+            .exists()
+    );
+    assert!(
+        temp.child(".claude/skills/demo__alpha/SKILL.md")
+            .path()
+            .exists()
+    );
+    assert!(!temp.child(".cursor/skills").path().exists());
+
+    // Explicit flags override the pack's list.
+    let mut cmd = sp(&temp);
+    cmd.arg("install").arg("demo").arg("--cursor");
+    cmd.assert().success();
+    assert!(
+        temp.child(".cursor/skills/demo__alpha/SKILL.md")
+            .path()
+            .exists()
+    );
+}
+
+#[test]
+fn install_rejects_unknown_pack_default_agent() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\nagents: [codexx]\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install").arg("demo");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown agent: codexx"));
+}
+
+#[test]
+fn install_requires_installed_pack_or_with_deps() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/base/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/extra/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/base.yaml")
+        .write_str("name: base\ninclude:\n  - base\n")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - extra\nrequires: [base]\n")
+        .unwrap();
+
+    let install = |extra: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.arg("install").arg("demo").arg("--codex").args(extra);
+        cmd
+    };
+
+    install(&[])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "pack demo requires base, which is not installed",
+        ));
+    assert!(!temp.child(".codex/skills/demo__extra").path().exists());
+
+    install(&["--with-deps"]).assert().success();
+    assert!(
+        temp.child(".codex/skills/base__base/SKILL.md")
+            .path()
+            .exists()
+    );
+    assert!(
+        temp.child(".codex/skills/demo__extra/SKILL.md")
+            .path()
+            .exists()
+    );
+
+    // Once the requirement is recorded, a plain install is enough.
+    install(&[]).assert().success();
+}
+
+#[test]
+fn agent_scoped_include_installs_only_for_listed_agents() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/general/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("skills/claude-only/hooks/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str(
+            "name: demo\ninclude:\n  - general\n  - pattern: \"claude-only/**\"\n    agents: [claude]\n",
+        )
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.args(["install", "demo", "--codex", "--claude"]);
+    cmd.assert().success();
+
+    assert!(temp.child(".codex/skills/demo__general").exists());
+    assert!(
+        !temp
+            .child(".codex/skills/demo__claude-only__hooks")
+            .exists()
+    );
+    assert!(temp.child(".claude/skills/demo__general").exists());
+    assert!(
+        temp.child(".claude/skills/demo__claude-only__hooks")
+            .exists()
+    );
+}
+
+#[test]
+fn install_warns_when_sink_parent_is_missing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    let sink = temp.child("ops/teams/skils/demo");
+
+    let install = |strict: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(["install", "demo", "--custom", "--format", "json"])
+            .arg("--path")
+            .arg(sink.path());
+        if strict {
+            cmd.arg("--strict");
+        }
+        cmd.assert()
+    };
+
+    install(true)
+        .failure()
+        .stderr(predicate::str::contains("along with its missing parent"));
+    assert!(!sink.exists());
+
+    let stdout = install(false).success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    let warnings = json["warnings"].as_array().unwrap();
+    assert!(
+        warnings[0]
+            .as_str()
+            .unwrap()
+            .contains("check the path for a typo"),
+        "{warnings:?}"
+    );
+    assert!(sink.child("demo__alpha/SKILL.md").exists());
+}
@@ -0,0 +1,202 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn installed_orphans_lists_unowned_folders() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.child("stray").create_dir_all().unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("installed")
+        .arg("--orphans")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--format")
+        .arg("plain")
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert()
+        .success()
+        .stdout(format!("{}\n", sink.child("stray").path().display()));
+}
+
+#[test]
+fn status_counts_packs_and_skills_per_sink() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/one.yaml")
+        .write_str("name: one\ninclude:\n  - alpha/**\n  - beta/**\n")
+        .unwrap();
+    temp.child("packs/two.yaml")
+        .write_str("name: two\ninclude:\n  - alpha/**\n")
+        .unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd.assert().success().get_output().stdout.clone()
+    };
+    run(&["install", "one", "--codex", "--claude"]);
+    run(&["install", "two", "--codex"]);
+    std::fs::remove_dir_all(temp.child(".claude/skills/one__beta").path()).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&run(&["status", "--format", "json"])).unwrap();
+    let sink = |name: &str| {
+        json["sinks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["sink"] == name)
+            .cloned()
+            .unwrap()
+    };
+    let codex = sink("codex");
+    assert_eq!(codex["packs"], 2);
+    assert_eq!(codex["skills"], 3);
+    assert_eq!(codex["missing"], serde_json::json!([]));
+    let claude = sink("claude");
+    assert_eq!(claude["packs"], 1);
+    assert_eq!(claude["skills"], 2);
+    assert_eq!(claude["missing"].as_array().unwrap().len(), 1);
+    assert_eq!(sink("cursor")["packs"], 0);
+    assert!(codex.get("stale").is_none());
+}
+
+#[test]
+fn migrate_state_rewrites_prefix_and_moves_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    sp(&temp)
+        .args(["install", "demo", "--codex", "--claude"])
+        .assert()
+        .success();
+
+    let from = temp.child(".codex");
+    let to = temp.child("moved/codex");
+    let records = || {
+        let state: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap(),
+        )
+        .unwrap();
+        state["installs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| {
+                (
+                    record["sink_path"].as_str().unwrap().to_string(),
+                    record["installed_paths"][0].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let before = records();
+
+    sp(&temp)
+        .arg("migrate-state")
+        .arg(from.path())
+        .arg(to.path())
+        .args(["--move", "--dry-run", "--format", "plain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("codex demo 1"));
+    assert_eq!(records(), before);
+    assert!(from.child("skills/demo__alpha").path().exists());
+
+    sp(&temp)
+        .arg("migrate-state")
+        .arg(from.path())
+        .arg(to.path())
+        .arg("--move")
+        .assert()
+        .success();
+    let sink = to.child("skills").path().display().to_string();
+    let moved = to.child("skills/demo__alpha").path().display().to_string();
+    let claude = temp.child(".claude/skills");
+    assert_eq!(
+        records(),
+        vec![
+            (
+                claude.path().display().to_string(),
+                claude.child("demo__alpha").path().display().to_string()
+            ),
+            (sink, moved),
+        ]
+    );
+    assert!(to.child("skills/demo__alpha/SKILL.md").path().exists());
+    assert!(!from.child("skills/demo__alpha").path().exists());
+    assert!(claude.child("demo__alpha/SKILL.md").path().exists());
+}
+
+#[test]
+fn installed_group_by_sink_keys_json_by_sink() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    for name in ["demo", "extra"] {
+        temp.child(format!("packs/{name}.yaml"))
+            .write_str(&format!("name: {name}\ninclude:\n  - alpha\n"))
+            .unwrap();
+    }
+    let sp = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd
+    };
+    sp(&["install", "demo", "--codex", "--claude"])
+        .assert()
+        .success();
+    sp(&["install", "extra", "--codex"]).assert().success();
+
+    let stdout = sp(&["installed", "--group-by", "sink", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["group_by"], "sink");
+    let installs = json["installs"].as_object().unwrap();
+    assert_eq!(installs.keys().collect::<Vec<_>>(), vec!["claude", "codex"]);
+    let codex_packs: Vec<&str> = installs["codex"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["pack"].as_str().unwrap())
+        .collect();
+    assert_eq!(codex_packs, vec!["demo", "extra"]);
+
+    let stdout = sp(&["installed", "--group-by", "pack", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["installs"]["demo"].as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,291 @@
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn plain_print0_separates_with_nul() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("skills")
+        .arg("--format")
+        .arg("plain")
+        .arg("--print0");
+    cmd.assert().success().stdout("alpha\0beta\0");
+}
+
+#[test]
+fn color_always_emits_ansi_when_piped() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("skills")
+        .arg("--color")
+        .arg("always")
+        .env("NO_COLOR", "1");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}["));
+
+    let mut cmd = sp(&temp);
+    cmd.arg("skills")
+        .arg("--color")
+        .arg("always")
+        .arg("--no-color");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}[").not());
+}
+
+#[test]
+fn log_file_records_install_events() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    let log = temp.child("sp.log");
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--log-file")
+        .arg(log.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("install pack").not());
+
+    let content = std::fs::read_to_string(log.path()).unwrap();
+    let event = content
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|event| event["fields"]["message"] == "install pack")
+        .unwrap();
+    assert_eq!(event["level"], "DEBUG");
+    assert_eq!(event["span"]["name"], "install");
+    assert_eq!(event["span"]["pack"], "demo");
+}
+
+#[test]
+fn json_output_is_byte_stable_across_runs() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["zeta", "alpha", "tools/mid", "tools/beta"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str(
+            "name: demo\ninclude:\n  - \"**\"\nexclude:\n  - tools/mid\nrename:\n  zeta: last\n  tools/beta: beta\n",
+        )
+        .unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.env("HOME", temp.path())
+            .args(args)
+            .arg("--no-bundled")
+            .arg("--format")
+            .arg("json");
+        cmd.assert().success().get_output().stdout.clone()
+    };
+
+    for args in [
+        &["show", "demo"][..],
+        &["skills"][..],
+        &["packs", "--with-skills"][..],
+    ] {
+        let first = run(args);
+        assert!(!first.is_empty());
+        assert_eq!(first, run(args), "json for {args:?} changed between runs");
+    }
+}
+
+#[test]
+fn compact_json_prints_one_line_per_document() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--format")
+        .arg("compact-json");
+    let stdout = String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap();
+
+    let line = stdout.strip_suffix('\n').unwrap();
+    assert!(!line.contains('\n'));
+    let view: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(
+        view["final_install_names"],
+        serde_json::json!(["demo__alpha"])
+    );
+}
+
+#[test]
+fn json_output_lists_zero_match_exclude_warning() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\nexclude:\n  - missing/**\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo").arg("--format").arg("json");
+    let stdout = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(
+        json["warnings"],
+        serde_json::json!(["demo: pack exclude pattern matched zero skills: missing/**"])
+    );
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "! demo: pack exclude pattern matched zero skills: missing/**",
+    ));
+}
+
+#[test]
+fn strict_turns_zero_match_exclude_into_error() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\nexclude:\n  - missing/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let sp = |args: &[&str], strict: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        if strict {
+            cmd.arg("--strict");
+        }
+        cmd.assert()
+    };
+    let install = [
+        "install",
+        "demo",
+        "--custom",
+        "--path",
+        sink.path().to_str().unwrap(),
+    ];
+
+    sp(&install, true)
+        .failure()
+        .stderr(predicate::str::contains(
+            "pack exclude pattern matched zero skills: missing/**",
+        ));
+    assert!(!sink.path().exists());
+
+    sp(&install, false)
+        .success()
+        .stdout(predicate::str::contains("matched zero skills"));
+    assert!(sink.child("demo__alpha/SKILL.md").path().exists());
+}
+
+#[test]
+fn select_prints_only_the_chosen_json_field() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+
+    let show = |select: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo", "--format", "json", "--select", select])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert()
+    };
+    show("pack.name").success().stdout("\"demo\"\n");
+    show("/final_install_names/0")
+        .success()
+        .stdout("\"demo__alpha\"\n");
+    show("pack.nmae").failure().stderr(predicate::str::contains(
+        "--select path not found in output: pack.nmae",
+    ));
+}
+
+#[test]
+fn pager_passes_output_through_when_stdout_is_not_a_terminal() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    let show = |pager: Option<&str>| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo"]);
+        if let Some(pager) = pager {
+            cmd.arg("--pager").env("PAGER", pager);
+        }
+        cmd.assert().success().get_output().stdout.clone()
+    };
+
+    let plain = show(None);
+    assert!(String::from_utf8_lossy(&plain).contains("alpha"));
+    assert_eq!(show(Some("cat")), plain);
+    // Piped output never starts the pager, so a broken one is harmless.
+    assert_eq!(show(Some("sp-no-such-pager")), plain);
+}
+
+#[test]
+fn warnings_reach_stderr_when_the_command_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str(
+            "name: demo\ninclude:\n  - alpha\n  - beta\nexclude:\n  - nothing\nrename:\n  alpha: beta\n",
+        )
+        .unwrap();
+
+    for format in ["json", "pretty"] {
+        sp(&temp)
+            .args(["show", "demo", "--format", format])
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(
+                predicate::str::contains(
+                    "warning: demo: pack exclude pattern matched zero skills: nothing",
+                )
+                .and(predicate::str::contains("installed folder name collision")),
+            );
+    }
+}
+
+#[test]
+fn select_writes_warnings_to_stderr() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\nexclude:\n  - nothing\n")
+        .unwrap();
+
+    sp(&temp)
+        .args(["show", "demo", "--format", "json", "--select", "pack.name"])
+        .assert()
+        .success()
+        .stdout("\"demo\"\n")
+        .stderr(predicate::str::contains(
+            "warning: demo: pack exclude pattern matched zero skills: nothing",
+        ));
+}
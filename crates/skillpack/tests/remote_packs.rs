@@ -0,0 +1,116 @@
+use assert_fs::prelude::*;
+
+mod common;
+
+use common::run_git;
+
+#[test]
+fn show_reads_pack_from_remote_repo() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote
+        .child("skills/general/one/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    remote
+        .child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - general/**\n")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    // An empty local root: every skill must come from the remote checkout.
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let show = |pack: &str| {
+        assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"))
+            .args(["show", pack, "--format", "json"])
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path())
+            .assert()
+    };
+
+    let output = show(&format!("{}#demo", remote.path().display())).success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["local"], serde_json::json!(["general/one"]));
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["demo__general__one"])
+    );
+
+    show(&format!("{}#missing", remote.path().display()))
+        .failure()
+        .stderr(predicates::str::contains("pack missing not found in"));
+}
+
+#[test]
+fn update_fetches_remote_pack_again() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote.child("skills/one/SKILL.md").write_str("x").unwrap();
+    remote.child("skills/two/SKILL.md").write_str("x").unwrap();
+    remote
+        .child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - one\n")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let repo_root = temp.child("repo");
+    repo_root
+        .child("skills/mine/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    // A local pack whose name contains `#` wins over the remote reading.
+    repo_root
+        .child("packs/team#local.yaml")
+        .write_str("name: local\ninclude:\n  - mine\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    let sp = |args: &[&str]| {
+        let mut cmd = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.args(args)
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        cmd.assert()
+    };
+
+    sp(&["show", "team#local", "--format", "json"])
+        .success()
+        .stdout(predicates::str::contains("\"name\": \"local\""));
+
+    let spec = format!("{}#demo", remote.path().display());
+    let sink_arg = sink.path().to_str().unwrap();
+    sp(&["install", &spec, "--custom", "--path", sink_arg]).success();
+    assert!(sink.child("demo__one").exists());
+    let state = std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap();
+    assert!(
+        state.contains(&format!("\"remote\": \"{spec}\"")),
+        "{state}"
+    );
+
+    remote
+        .child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - one\n  - two\n")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "add two"], remote.path());
+    sp(&["update"]).success();
+    assert!(sink.child("demo__two/SKILL.md").exists());
+}
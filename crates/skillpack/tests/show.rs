@@ -0,0 +1,480 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::{setup_bundled_repo, sp};
+
+#[test]
+fn show_outputs_final_names() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("Installs as").and(predicate::str::contains("demo__alpha")),
+    );
+}
+
+#[test]
+fn show_count_prints_final_skill_total() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta", "gamma"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n  - beta\n")
+        .unwrap();
+
+    let show = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo", "--count", "--format", format])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert().success().get_output().stdout.clone()
+    };
+    assert_eq!(show("plain"), b"2\n");
+    let json: serde_json::Value = serde_json::from_slice(&show("json")).unwrap();
+    assert_eq!(json["count"], 2);
+}
+
+#[test]
+fn show_with_metadata_lists_skill_descriptions() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/writing/SKILL.md")
+        .write_str("---\nname: writing\ndescription: Tightens prose\n---\n# Writing\n")
+        .unwrap();
+    temp.child("skills/plain/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"*\"\n")
+        .unwrap();
+
+    let show = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo", "--with-metadata", "--format", format])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    assert!(show("pretty").contains("writing — Tightens prose"));
+    assert!(show("plain").contains("writing\tTightens prose\n"));
+    let json: serde_json::Value = serde_json::from_str(&show("json")).unwrap();
+    assert_eq!(
+        json["metadata"],
+        serde_json::json!({"writing": {"name": "writing", "description": "Tightens prose"}})
+    );
+}
+
+#[test]
+fn show_diff_against_splits_skills_by_pack() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta", "gamma"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo-v1.yaml")
+        .write_str("name: demo-v1\ninclude:\n  - alpha\n  - beta\n")
+        .unwrap();
+    temp.child("packs/demo-v2.yaml")
+        .write_str("name: demo-v2\ninclude:\n  - beta\n  - gamma\n")
+        .unwrap();
+
+    let show = |format: &str| {
+        let mut cmd = sp(&temp);
+        cmd.args([
+            "show",
+            "demo-v1",
+            "--diff-against",
+            "demo-v2",
+            "--format",
+            format,
+        ])
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    let json: serde_json::Value = serde_json::from_str(&show("json")).unwrap();
+    assert_eq!(json["only_in_pack"], serde_json::json!(["alpha"]));
+    assert_eq!(json["only_in_other"], serde_json::json!(["gamma"]));
+    assert_eq!(json["in_both"], serde_json::json!(["beta"]));
+    assert_eq!(show("plain"), "- alpha\n+ gamma\n= beta\n");
+}
+
+#[test]
+fn show_bare_plain_prints_only_ids_and_install_names() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"*\"\nrename:\n  beta: gamma\n")
+        .unwrap();
+
+    let show = |bare: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", "demo", "--format", "plain"])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        if bare {
+            cmd.arg("--bare");
+        }
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    assert_eq!(
+        show(false),
+        "local\nalpha\nbeta\nrename beta gamma\nfinal\ndemo__alpha\ndemo__gamma\n"
+    );
+    assert_eq!(show(true), "alpha\nbeta\ndemo__alpha\ndemo__gamma\n");
+}
+
+#[test]
+fn with_skill_deps_pulls_in_frontmatter_dependencies() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md")
+        .write_str("---\nname: alpha\ndepends_on: [beta]\n---\n")
+        .unwrap();
+    temp.child("skills/beta/SKILL.md")
+        .write_str("---\ndepends_on: [gamma]\n---\n")
+        .unwrap();
+    temp.child("skills/gamma/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/delta/SKILL.md")
+        .write_str("---\ndepends_on: [missing]\n---\n")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+    temp.child("packs/broken.yaml")
+        .write_str("name: broken\ninclude:\n  - delta\n")
+        .unwrap();
+
+    let show = |pack: &str, with_deps: bool| {
+        let mut cmd = sp(&temp);
+        cmd.args(["show", pack, "--format", "json"])
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        if with_deps {
+            cmd.arg("--with-skill-deps");
+        }
+        cmd.assert()
+    };
+    let resolved = |with_deps: bool| {
+        let stdout = show("demo", with_deps)
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        serde_json::from_slice::<serde_json::Value>(&stdout).unwrap()
+    };
+
+    let json = resolved(false);
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["demo__alpha"])
+    );
+    let json = resolved(true);
+    assert_eq!(json["local"], serde_json::json!(["alpha", "beta", "gamma"]));
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["demo__alpha", "demo__beta", "demo__gamma"])
+    );
+    show("broken", true)
+        .failure()
+        .stderr(predicate::str::contains(
+            "skill delta depends on missing, which local does not provide",
+        ));
+}
+
+#[test]
+fn show_outputs_final_names_for_bundled_pack() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    setup_bundled_repo(&temp);
+    let work = temp.child("work");
+    work.create_dir_all().unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("show")
+        .arg("demo")
+        .current_dir(work.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("Installs as").and(predicate::str::contains("demo__alpha")),
+    );
+}
+
+#[test]
+fn show_allow_collisions_reports_groups() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/a/tool/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/b/tool/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"**\"\ninstall:\n  flatten: true\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("collision"));
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--allow-collisions")
+        .arg("--format")
+        .arg("json")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let view: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        view["collisions"],
+        serde_json::json!([["demo__tool", ["a/tool", "b/tool"]]])
+    );
+}
+
+#[test]
+fn rename_maps_long_id_to_short_install_name() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/tools/agent/skills/general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("skills/review/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str(
+            "name: demo\ninclude:\n  - \"**\"\nrename:\n  tools/agent/skills/general/writing: writing\n",
+        )
+        .unwrap();
+    temp.child("packs/clash.yaml")
+        .write_str(
+            "name: clash\ninclude:\n  - \"**\"\nrename:\n  tools/agent/skills/general/writing: review\n",
+        )
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--format")
+        .arg("json")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let view: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        view["renames"],
+        serde_json::json!([["tools/agent/skills/general/writing", "writing"]])
+    );
+    assert_eq!(
+        view["final_install_names"],
+        serde_json::json!(["demo__review", "demo__writing"])
+    );
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success();
+    assert!(sink.child("demo__writing/SKILL.md").path().exists());
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("clash")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("collision"));
+}
+
+#[test]
+fn duplicate_include_patterns_collapse_and_still_match() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/general/alpha/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - general/**\n  - general/**/\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show")
+        .arg("demo")
+        .arg("--format")
+        .arg("plain")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("demo__general__alpha"))
+        .stderr(predicate::str::contains(
+            "duplicate include pattern dropped: general/**",
+        ));
+}
+
+#[test]
+fn prefix_mode_first_segment_prefixes_by_category() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["coding/dotnet/efcore", "general/writing", "solo"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - \"**\"\ninstall:\n  prefix_mode: first-segment\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo").arg("--format").arg("json");
+    let stdout = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["pack"]["prefix_mode"], "first-segment");
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["coding__dotnet__efcore", "general__writing", "demo__solo"])
+    );
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install").arg("demo").arg("--codex");
+    cmd.assert().success();
+    // A one-segment id takes the pack prefix, so it cannot clash with another pack's `solo`.
+    for name in ["coding__dotnet__efcore", "general__writing", "demo__solo"] {
+        assert!(
+            temp.child(format!(".codex/skills/{name}/SKILL.md"))
+                .path()
+                .exists()
+        );
+    }
+}
+
+#[test]
+fn show_install_names_match_installed_folders() {
+    for (flatten, expected) in [
+        (false, ["demo__coding__review", "demo__general__writing"]),
+        (true, ["demo__review", "demo__writing"]),
+    ] {
+        let temp = assert_fs::TempDir::new().unwrap();
+        for id in ["coding/review", "general/writing"] {
+            temp.child(format!("skills/{id}/SKILL.md"))
+                .write_str("x")
+                .unwrap();
+        }
+        temp.child("packs/demo.yaml")
+            .write_str(&format!(
+                "name: demo\ninclude:\n  - \"**\"\ninstall:\n  flatten: {flatten}\n"
+            ))
+            .unwrap();
+        let sp = |args: &[&str]| {
+            let mut cmd = sp(&temp);
+            cmd.args(args);
+            cmd
+        };
+
+        let stdout = sp(&["show", "demo", "--format", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(json["pack"]["flatten"], flatten);
+        assert_eq!(json["final_install_names"], serde_json::json!(expected));
+
+        sp(&["install", "demo", "--codex"]).assert().success();
+        let mut installed: Vec<String> = std::fs::read_dir(temp.child(".codex/skills").path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        installed.sort();
+        assert_eq!(installed, expected);
+    }
+}
+
+#[test]
+fn show_pretty_reports_flatten() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/general/writing/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - general/**\ninstall:\n  flatten: true\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo").arg("--color").arg("never");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "install prefix=demo sep=__ flatten=leaf",
+        ))
+        .stdout(predicate::str::contains("demo__writing"));
+}
+
+#[test]
+fn include_from_file_selects_listed_skills() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta", "gamma"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/lists/demo.txt")
+        .write_str("# generated by tooling\nbeta\ngamma\n")
+        .unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\ninclude_from: lists/demo.txt\n")
+        .unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo").arg("--format").arg("json");
+    let stdout = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(
+        json["final_install_names"],
+        serde_json::json!(["demo__alpha", "demo__beta", "demo__gamma"])
+    );
+
+    // The suggestion names the directory the list was looked up in.
+    std::fs::remove_file(temp.child("packs/lists/demo.txt").path()).unwrap();
+    let mut cmd = sp(&temp);
+    cmd.arg("show").arg("demo");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "include_from is resolved relative to {}",
+            temp.child("packs").path().display()
+        )));
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("-")
+        .arg("--codex")
+        .current_dir(temp.path())
+        .write_stdin("name: piped\ninclude:\n  - alpha\ninclude_from: lists/demo.txt\n");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "include_from is resolved relative to {}",
+            temp.path().display()
+        )));
+}
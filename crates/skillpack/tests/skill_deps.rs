@@ -0,0 +1,82 @@
+use assert_fs::prelude::*;
+
+mod common;
+
+use common::run_git;
+
+#[test]
+fn with_skill_deps_follows_import_dependencies_and_update_keeps_following() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let remote = temp.child("remote");
+    remote.create_dir_all().unwrap();
+    run_git(&["init"], remote.path());
+    run_git(&["config", "user.email", "test@example.com"], remote.path());
+    run_git(&["config", "user.name", "Test"], remote.path());
+    remote
+        .child("skills/one/SKILL.md")
+        .write_str("---\ndepends_on: [skills/two]\n---\n")
+        .unwrap();
+    remote.child("skills/two/SKILL.md").write_str("x").unwrap();
+    remote
+        .child("skills/three/SKILL.md")
+        .write_str("x")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "init"], remote.path());
+
+    let remote_path = remote.path().display().to_string();
+    let repo_root = temp.child("repo");
+    repo_root.child("skills").create_dir_all().unwrap();
+    let import = format!("imports:\n  - repo: {remote_path}\n    include:\n      - skills/one\n");
+    repo_root
+        .child("packs/demo.yaml")
+        .write_str(&format!("name: demo\n{import}"))
+        .unwrap();
+    repo_root
+        .child("packs/excluded.yaml")
+        .write_str(&format!(
+            "name: excluded\n{import}exclude:\n  - {remote_path}#skills/two\n"
+        ))
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let sp = |args: &[&str]| {
+        let mut cmd = assert_cmd::Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.args(args)
+            .arg("--root")
+            .arg(repo_root.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path())
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        cmd.assert()
+    };
+    let sink_arg = sink.path().to_str().unwrap();
+    sp(&[
+        "install",
+        "demo",
+        "--with-skill-deps",
+        "--custom",
+        "--path",
+        sink_arg,
+    ])
+    .success();
+    assert!(sink.child("demo__skills__two/SKILL.md").exists());
+    assert!(!sink.child("demo__skills__three").exists());
+
+    // The install was recorded as following deps, so update keeps doing so.
+    remote
+        .child("skills/one/SKILL.md")
+        .write_str("---\ndepends_on: [skills/two, skills/three]\n---\n")
+        .unwrap();
+    run_git(&["add", "."], remote.path());
+    run_git(&["commit", "-m", "dep"], remote.path());
+    sp(&["update"]).success();
+    assert!(sink.child("demo__skills__three/SKILL.md").exists());
+
+    sp(&["show", "excluded", "--with-skill-deps"])
+        .failure()
+        .stderr(predicates::str::contains(
+            "skill skills/one depends on skills/two, which the pack excludes",
+        ));
+}
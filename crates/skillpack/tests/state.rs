@@ -14,9 +14,14 @@ fn state_round_trip() {
         prefix: "demo".to_string(),
         sep: "__".to_string(),
         flatten: false,
+        frozen: false,
         imports: vec![],
         installed_paths: vec!["/tmp/sink/demo__a".to_string()],
         installed_at: "2025-01-01T00:00:00Z".to_string(),
+        hashes: Default::default(),
+        namespace: None,
+        with_skill_deps: false,
+        remote: None,
     };
     let state = StateFile {
         version: 1,
@@ -28,3 +33,50 @@ fn state_round_trip() {
     assert_eq!(loaded.installs.len(), 1);
     assert_eq!(loaded.installs[0].pack, record.pack);
 }
+
+#[test]
+fn state_serializes_independent_of_insertion_order() {
+    let temp = TempDir::new().unwrap();
+    let record = |sink_path: &str, pack: &str, paths: &[&str]| InstallRecord {
+        sink: "codex".to_string(),
+        sink_path: sink_path.to_string(),
+        pack: pack.to_string(),
+        pack_file: format!("/tmp/packs/{pack}.yaml"),
+        prefix: pack.to_string(),
+        sep: "__".to_string(),
+        flatten: false,
+        frozen: false,
+        imports: vec![],
+        installed_paths: paths.iter().map(|path| path.to_string()).collect(),
+        installed_at: "2025-01-01T00:00:00Z".to_string(),
+        hashes: Default::default(),
+        namespace: None,
+        with_skill_deps: false,
+        remote: None,
+    };
+    let forward = StateFile {
+        version: 1,
+        installs: vec![
+            record("/tmp/a", "demo", &["/tmp/a/demo__x", "/tmp/a/demo__y"]),
+            record("/tmp/a", "extra", &["/tmp/a/extra__z"]),
+            record("/tmp/b", "demo", &["/tmp/b/demo__x"]),
+        ],
+    };
+    let shuffled = StateFile {
+        version: 1,
+        installs: vec![
+            record("/tmp/b", "demo", &["/tmp/b/demo__x"]),
+            record("/tmp/a", "extra", &["/tmp/a/extra__z"]),
+            record("/tmp/a", "demo", &["/tmp/a/demo__y", "/tmp/a/demo__x"]),
+        ],
+    };
+
+    let forward_path = temp.path().join("forward.json");
+    let shuffled_path = temp.path().join("shuffled.json");
+    write_state_at(&forward, &forward_path).unwrap();
+    write_state_at(&shuffled, &shuffled_path).unwrap();
+    assert_eq!(
+        std::fs::read(&forward_path).unwrap(),
+        std::fs::read(&shuffled_path).unwrap()
+    );
+}
@@ -0,0 +1,373 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn uninstall_if_installed_is_idempotent() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    for _ in 0..2 {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+        cmd.arg("uninstall")
+            .arg("demo")
+            .arg("--custom")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--if-installed")
+            .env("HOME", temp.path())
+            .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("not installed"));
+    }
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("uninstall")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("pack not installed"));
+}
+
+#[test]
+fn uninstall_counts_missing_paths_apart_from_removed() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for id in ["alpha", "beta"] {
+        temp.child(format!("skills/{id}/SKILL.md"))
+            .write_str("x")
+            .unwrap();
+    }
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n  - beta\n")
+        .unwrap();
+    let uninstall = |extra: &[&str]| {
+        let mut install = sp(&temp);
+        install
+            .args(["install", "demo", "--codex"])
+            .assert()
+            .success();
+        std::fs::remove_dir_all(temp.child(".codex/skills/demo__beta").path()).unwrap();
+        let mut cmd = sp(&temp);
+        cmd.args(["uninstall", "demo", "--codex", "--format", "json"])
+            .args(extra);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        serde_json::from_slice::<serde_json::Value>(&output).unwrap()
+    };
+
+    let view = uninstall(&[]);
+    assert_eq!(
+        (&view["removed"], &view["missing"], &view["unmanaged"]),
+        (
+            &serde_json::json!(1),
+            &serde_json::json!(1),
+            &serde_json::json!(0)
+        )
+    );
+    let view = uninstall(&["--keep-files"]);
+    assert_eq!(
+        (&view["removed"], &view["missing"], &view["unmanaged"]),
+        (
+            &serde_json::json!(0),
+            &serde_json::json!(1),
+            &serde_json::json!(1)
+        )
+    );
+}
+
+#[test]
+fn uninstall_prune_empty_removes_sink_dir() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args)
+            .arg("demo")
+            .arg("--custom")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert().success();
+    };
+
+    run(&["install"]);
+    run(&["uninstall"]);
+    assert!(sink.path().is_dir());
+
+    run(&["install"]);
+    run(&["uninstall", "--prune-empty"]);
+    assert!(!sink.path().exists());
+
+    // A built-in agent folder is left in place even when empty.
+    let codex = temp.child(".codex/skills");
+    for args in [&["install"][..], &["uninstall", "--prune-empty"]] {
+        sp(&temp)
+            .args(args)
+            .args(["demo", "--codex"])
+            .assert()
+            .success();
+    }
+    assert!(codex.path().is_dir());
+}
+
+#[test]
+fn uninstall_dry_run_keeps_files_and_state() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n  - beta/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args)
+            .arg("demo")
+            .arg("--custom")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert().success()
+    };
+
+    run(&["install"]);
+    run(&["uninstall", "--dry-run", "--format", "json"])
+        .stdout(predicate::str::contains("\"dry_run\": true"))
+        .stdout(predicate::str::contains("\"removed\": 2"));
+    assert!(sink.child("demo__alpha/SKILL.md").path().exists());
+    assert!(sink.child("demo__beta/SKILL.md").path().exists());
+
+    // The record survives the dry run, so a real uninstall still succeeds.
+    run(&["uninstall"]);
+    assert!(!sink.child("demo__alpha").path().exists());
+}
+
+#[test]
+fn uninstall_by_path_without_agent_flag() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args)
+            .arg("demo")
+            .arg("--path")
+            .arg(sink.path())
+            .arg("--cache-dir")
+            .arg(temp.child("cache").path());
+        cmd.assert()
+    };
+
+    run(&["install", "--claude"]).success();
+    assert!(sink.child("demo__alpha/SKILL.md").path().exists());
+
+    run(&["uninstall", "--format", "json"])
+        .success()
+        .stdout(predicate::str::contains("\"sink\": \"claude\""))
+        .stdout(predicate::str::contains("\"removed\": 1"));
+    assert!(!sink.child("demo__alpha").path().exists());
+}
+
+#[test]
+fn uninstall_all_dry_run_lists_every_pack_and_keeps_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/one.yaml")
+        .write_str("name: one\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    temp.child("packs/two.yaml")
+        .write_str("name: two\ninclude:\n  - beta/**\n")
+        .unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args).arg("--codex");
+        cmd.assert().success()
+    };
+    run(&["install", "one"]);
+    run(&["install", "two"]);
+    let sink = temp.child(".codex/skills");
+
+    let output = run(&["uninstall", "--all", "--dry-run", "--format", "plain"])
+        .get_output()
+        .stdout
+        .clone();
+    let plain = String::from_utf8(output).unwrap();
+    assert!(plain.contains("would uninstall one"));
+    assert!(plain.contains("would uninstall two"));
+    assert!(sink.child("one__alpha/SKILL.md").path().exists());
+    assert!(sink.child("two__beta/SKILL.md").path().exists());
+
+    run(&["uninstall", "--all"]);
+    assert!(!sink.child("one__alpha").path().exists());
+    assert!(!sink.child("two__beta").path().exists());
+}
+
+#[test]
+fn uninstall_older_than_removes_only_stale_installs() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/old.yaml")
+        .write_str("name: old\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    temp.child("packs/new.yaml")
+        .write_str("name: new\ninclude:\n  - beta/**\n")
+        .unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args).arg("--codex");
+        cmd.assert().success()
+    };
+    run(&["install", "old"]);
+    run(&["install", "new"]);
+
+    let state_path = temp.child(".skillpack/state.json");
+    let state = std::fs::read_to_string(state_path.path()).unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&state).unwrap();
+    for record in json["installs"].as_array_mut().unwrap() {
+        if record["pack"] == "old" {
+            record["installed_at"] = serde_json::json!("2020-01-01T00:00:00Z");
+        }
+    }
+    std::fs::write(state_path.path(), serde_json::to_string(&json).unwrap()).unwrap();
+    let sink = temp.child(".codex/skills");
+
+    let output = run(&[
+        "uninstall",
+        "--older-than",
+        "30d",
+        "--dry-run",
+        "--format",
+        "plain",
+    ])
+    .get_output()
+    .stdout
+    .clone();
+    let plain = String::from_utf8(output).unwrap();
+    assert!(plain.contains("would uninstall old"), "{plain}");
+    assert!(!plain.contains("new"), "{plain}");
+    assert!(sink.child("old__alpha/SKILL.md").path().exists());
+
+    run(&["uninstall", "--older-than", "30d"]);
+    assert!(!sink.child("old__alpha").path().exists());
+    assert!(sink.child("new__beta/SKILL.md").path().exists());
+}
+
+#[test]
+fn uninstall_keep_files_drops_record_but_leaves_folders() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n")
+        .unwrap();
+
+    let sp = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args);
+        cmd.assert().success().get_output().stdout.clone()
+    };
+    sp(&["install", "demo", "--codex"]);
+    let stdout = sp(&[
+        "uninstall",
+        "demo",
+        "--codex",
+        "--keep-files",
+        "--format",
+        "json",
+    ]);
+    let json: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(json["removed"], 0);
+    assert_eq!(json["unmanaged"], 1);
+
+    assert!(temp.child(".codex/skills/demo__alpha/SKILL.md").exists());
+    let state: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state["installs"], serde_json::json!([]));
+}
+
+#[test]
+fn uninstall_glob_removes_every_matching_pack() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    for name in ["team-a", "team-b", "solo"] {
+        temp.child(format!("packs/{name}.yaml"))
+            .write_str(&format!("name: {name}\ninclude:\n  - alpha\n"))
+            .unwrap();
+    }
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args).arg("--codex");
+        cmd.assert()
+    };
+    for name in ["team-a", "team-b", "solo"] {
+        run(&["install", name]).success();
+    }
+
+    // Any glob syntax selects by pattern, not just `*`.
+    run(&["uninstall", "team-[a]", "--format", "plain"]).success();
+    let skills = temp.child(".codex/skills");
+    assert!(!skills.child("team-a__alpha").exists());
+    assert!(skills.child("team-b__alpha").exists());
+    run(&["install", "team-a"]).success();
+
+    run(&["uninstall", "team-?", "--format", "plain"]).success();
+    assert!(!skills.child("team-a__alpha").exists());
+    assert!(!skills.child("team-b__alpha").exists());
+    assert!(skills.child("solo__alpha").exists());
+
+    run(&["uninstall", "team-*"])
+        .failure()
+        .stderr(predicate::str::contains("no installed pack matches team-*"));
+    run(&["uninstall", "team-*", "--if-installed"]).success();
+}
+
+#[test]
+fn uninstall_reports_recorded_folders_already_missing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\n  - beta\n")
+        .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args).arg("--codex");
+        cmd.assert().success().get_output().stdout.clone()
+    };
+    run(&["install", "demo"]);
+    std::fs::remove_dir_all(temp.child(".codex/skills/demo__beta").path()).unwrap();
+
+    let dry_run = run(&["uninstall", "demo", "--dry-run", "--format", "plain"]);
+    assert!(String::from_utf8(dry_run).unwrap().ends_with("missing 1\n"));
+    let json: serde_json::Value =
+        serde_json::from_slice(&run(&["uninstall", "demo", "--format", "json"])).unwrap();
+    assert_eq!(json["missing"], 1);
+    assert!(!temp.child(".codex/skills/demo__alpha").exists());
+}
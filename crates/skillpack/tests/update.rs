@@ -0,0 +1,270 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use common::sp;
+
+#[test]
+fn switch_uninstalls_all_and_installs_new() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    // Create two skills and two packs
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/pack1.yaml")
+        .write_str("name: pack1\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    temp.child("packs/pack2.yaml")
+        .write_str("name: pack2\ninclude:\n  - beta/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    // First install pack1
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("pack1")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success();
+
+    // Verify pack1 is installed
+    assert!(sink.child("pack1__alpha").exists());
+    assert!(!sink.child("pack2__beta").exists());
+
+    // Switch to pack2
+    let mut cmd = sp(&temp);
+    cmd.arg("switch")
+        .arg("pack2")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("Switched")
+            .and(predicate::str::contains("uninstalled"))
+            .and(predicate::str::contains("pack1"))
+            .and(predicate::str::contains("installed"))
+            .and(predicate::str::contains("pack2")),
+    );
+
+    // Verify pack1 is gone and pack2 is installed
+    assert!(!sink.child("pack1__alpha").exists());
+    assert!(sink.child("pack2__beta").exists());
+}
+
+#[test]
+fn switch_installs_multiple_packs() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/pack1.yaml")
+        .write_str("name: pack1\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    temp.child("packs/pack2.yaml")
+        .write_str("name: pack2\ninclude:\n  - beta/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    // Switch to both packs at once
+    let mut cmd = sp(&temp);
+    cmd.arg("switch")
+        .arg("pack1")
+        .arg("pack2")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success().stdout(
+        predicate::str::contains("Switched")
+            .and(predicate::str::contains("pack1"))
+            .and(predicate::str::contains("pack2")),
+    );
+
+    // Verify both packs are installed
+    assert!(sink.child("pack1__alpha").exists());
+    assert!(sink.child("pack2__beta").exists());
+}
+
+#[test]
+fn update_skips_frozen_install() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    let pack = temp.child("packs/demo.yaml");
+    pack.write_str("name: demo\ninclude:\n  - alpha/**\n")
+        .unwrap();
+    let sink = temp.child("sink");
+    sink.create_dir_all().unwrap();
+
+    let mut cmd = sp(&temp);
+    cmd.arg("install")
+        .arg("demo")
+        .arg("--custom")
+        .arg("--path")
+        .arg(sink.path())
+        .arg("--freeze")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path());
+    cmd.assert().success();
+
+    pack.write_str("name: demo\ninclude:\n  - alpha/**\n  - beta/**\n")
+        .unwrap();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("update")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("skipped"));
+    assert!(!sink.child("demo__beta").exists());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("installed")
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("frozen"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("sp"));
+    cmd.arg("update")
+        .arg("--force")
+        .arg("--cache-dir")
+        .arg(temp.child("cache").path())
+        .env("HOME", temp.path())
+        .env("SKILLPACK_HOME", temp.child(".skillpack").path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("updated"));
+    assert!(sink.child("demo__beta").exists());
+}
+
+#[test]
+fn update_reports_every_record_when_one_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    let broken = temp.child("packs/broken.yaml");
+    broken
+        .write_str("name: broken\ninclude:\n  - alpha\n")
+        .unwrap();
+    let demo = temp.child("packs/demo.yaml");
+    demo.write_str("name: demo\ninclude:\n  - alpha\n").unwrap();
+    for pack in ["broken", "demo"] {
+        let mut cmd = sp(&temp);
+        cmd.args(["install", pack, "--codex"]);
+        cmd.assert().success();
+    }
+
+    broken.write_str("name: [broken\n").unwrap();
+    demo.write_str("name: demo\ninclude:\n  - alpha\n  - beta\n")
+        .unwrap();
+    let mut cmd = sp(&temp);
+    cmd.args(["update", "--format", "json"]);
+    let assert = cmd.assert().failure().stderr(predicate::str::contains(
+        "1 of 2 install(s) failed to update",
+    ));
+    let view: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    let statuses: Vec<(&str, &str)> = view["updates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["pack"].as_str().unwrap(),
+                item["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(statuses, vec![("broken", "failed"), ("demo", "updated")]);
+    assert!(
+        view["updates"][0]["reason"]
+            .as_str()
+            .unwrap()
+            .contains("broken.yaml")
+    );
+    // The pack after the failure was still updated.
+    assert!(temp.child(".codex/skills/demo__beta").exists());
+    assert!(temp.child(".codex/skills/broken__alpha").exists());
+}
+
+#[test]
+fn reinstall_recopies_unchanged_skills_from_scratch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - alpha\ninstall:\n  preserve_on_reconcile: [.env]\n")
+        .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = sp(&temp);
+        cmd.args(args).arg("--codex");
+        cmd.assert().success()
+    };
+
+    run(&["install", "demo"]);
+    let alpha = temp.child(".codex/skills/demo__alpha");
+    alpha.child(".env").write_str("TOKEN=1").unwrap();
+    // A plain install keeps the preserved file even though nothing changed.
+    run(&["install", "demo"]);
+    assert!(alpha.child(".env").exists());
+
+    run(&["reinstall", "demo", "--format", "plain"])
+        .stdout(predicate::str::diff("reinstalled demo alpha\n"));
+    assert!(!alpha.child(".env").exists());
+    assert_eq!(
+        std::fs::read_to_string(alpha.child("SKILL.md").path()).unwrap(),
+        "x"
+    );
+    let state = std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap();
+    assert_eq!(state.matches("\"pack\": \"demo\"").count(), 1, "{state}");
+}
+
+#[test]
+fn failed_reinstall_keeps_the_previous_install() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/beta/SKILL.md").write_str("x").unwrap();
+    temp.child("skills/alpha/SKILL.md").write_str("x").unwrap();
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - beta\n")
+        .unwrap();
+    sp(&temp)
+        .args(["install", "demo", "--codex"])
+        .assert()
+        .success();
+
+    // alpha's destination belongs to nobody, so the copy fails before beta is recopied.
+    temp.child("packs/demo.yaml")
+        .write_str("name: demo\ninclude:\n  - beta\n  - alpha\n")
+        .unwrap();
+    let skills = temp.child(".codex/skills");
+    skills
+        .child("demo__alpha/SKILL.md")
+        .write_str("mine")
+        .unwrap();
+    sp(&temp)
+        .args(["reinstall", "demo", "--codex"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "destination exists but is not owned by pack",
+        ));
+
+    assert!(skills.child("demo__beta/SKILL.md").exists());
+    let state = std::fs::read_to_string(temp.child(".skillpack/state.json").path()).unwrap();
+    assert!(state.contains("demo__beta"), "{state}");
+    assert_eq!(
+        std::fs::read_to_string(skills.child("demo__alpha/SKILL.md").path()).unwrap(),
+        "mine"
+    );
+}